@@ -1,10 +1,70 @@
-use clap::Parser;
+use std::{env, path::PathBuf};
+
+use auth::domain::models::{ClaimPath, RoleMapping};
+use chrono::Duration;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use domain::{
+    DomainError,
+    quota::{Plan, Quota, QuotaPolicy},
+    vault::{KdfAlg, KdfBounds, KdfParams, KdfPolicy, RetentionPolicy},
+};
+use s3_storage::S3Config;
+use url::Url;
 
 #[derive(Debug, Clone, Parser)]
 #[command(about, version)]
 pub struct Args {
     #[command(flatten)]
     pub server: ServerArgs,
+
+    #[command(flatten)]
+    pub auth: AuthArgs,
+
+    #[command(flatten)]
+    pub history: HistoryArgs,
+
+    #[command(flatten)]
+    pub trash: TrashArgs,
+
+    #[command(flatten)]
+    pub attachments: AttachmentArgs,
+
+    #[command(flatten)]
+    pub kdf: KdfArgs,
+
+    #[command(flatten)]
+    pub quota: QuotaArgs,
+}
+
+/// Variables renamed since they were introduced, old name first. Their old
+/// flags are kept as aliases; clap has no such thing for variables, so
+/// [`Args::load`] reads the old ones itself.
+const RENAMED_ENV: [(&str, &str); 9] = [
+    ("KDF_MIN_M_KIB", "KDF_ARGON2ID_MIN_M_KIB"),
+    ("KDF_MIN_T", "KDF_ARGON2ID_MIN_T"),
+    ("KDF_MIN_P", "KDF_ARGON2ID_MIN_P"),
+    ("KDF_RECOMMENDED_M_KIB", "KDF_ARGON2ID_RECOMMENDED_M_KIB"),
+    ("KDF_RECOMMENDED_T", "KDF_ARGON2ID_RECOMMENDED_T"),
+    ("KDF_RECOMMENDED_P", "KDF_ARGON2ID_RECOMMENDED_P"),
+    ("KDF_MAX_M_KIB", "KDF_ARGON2ID_MAX_M_KIB"),
+    ("KDF_MAX_T", "KDF_ARGON2ID_MAX_T"),
+    ("KDF_MAX_P", "KDF_ARGON2ID_MAX_P"),
+];
+
+impl Args {
+    /// [`Parser::parse`], except that a renamed variable still set under its
+    /// old name is read from there when the new one is unset.
+    pub fn load() -> Self {
+        let mut command = Self::command();
+        for (old, new) in RENAMED_ENV {
+            if env::var_os(new).is_none() && env::var_os(old).is_some() {
+                command = command.mut_arg(new, |arg| arg.env(old));
+            }
+        }
+
+        let matches = command.get_matches_mut();
+        Self::from_arg_matches(&matches).unwrap_or_else(|e| e.format(&mut command).exit())
+    }
 }
 
 #[derive(clap::Args, Debug, Clone)]
@@ -26,4 +86,829 @@ pub struct ServerArgs {
         help = "The port to bind the server to"
     )]
     pub port: u16,
+
+    #[arg(
+        long,
+        env = "VAULT_STORE",
+        name = "VAULT_STORE",
+        value_enum,
+        default_value = "sqlite",
+        help = "Where vaults and attachment records are kept; memory loses them on restart"
+    )]
+    pub vault_store: VaultStore,
+
+    #[arg(
+        long,
+        env = "DATABASE_URL",
+        name = "DATABASE_URL",
+        required_if_eq("VAULT_STORE", "postgres"),
+        help = "The PostgreSQL connection URL, for the postgres vault store"
+    )]
+    pub database_url: Option<String>,
+
+    #[arg(
+        long,
+        env = "SQLITE_PATH",
+        name = "SQLITE_PATH",
+        default_value = "ferrispass.db",
+        help = "The database file of the sqlite vault store, created if missing"
+    )]
+    pub sqlite_path: PathBuf,
+
+    #[arg(
+        long,
+        env = "BLOB_STORE",
+        name = "BLOB_STORE",
+        value_enum,
+        default_value = "filesystem",
+        help = "Where attachment chunks and streamed ciphertexts are kept; memory loses them on restart"
+    )]
+    pub blob_store: BlobStorage,
+
+    #[arg(
+        long,
+        env = "BLOB_DIR",
+        name = "BLOB_DIR",
+        default_value = "blobs",
+        help = "The root directory of the filesystem blob store"
+    )]
+    pub blob_dir: PathBuf,
+
+    #[arg(
+        long,
+        env = "S3_ENDPOINT",
+        name = "S3_ENDPOINT",
+        required_if_eq("BLOB_STORE", "s3"),
+        help = "The endpoint of the S3-compatible service, e.g. http://localhost:9000 for MinIO"
+    )]
+    pub s3_endpoint: Option<Url>,
+
+    #[arg(
+        long,
+        env = "S3_BUCKET",
+        name = "S3_BUCKET",
+        required_if_eq("BLOB_STORE", "s3"),
+        help = "The bucket of the s3 blob store"
+    )]
+    pub s3_bucket: Option<String>,
+
+    #[arg(
+        long,
+        env = "S3_REGION",
+        name = "S3_REGION",
+        default_value = "us-east-1",
+        help = "The region requests to the s3 blob store are signed for"
+    )]
+    pub s3_region: String,
+
+    #[arg(
+        long,
+        env = "S3_ACCESS_KEY_ID",
+        name = "S3_ACCESS_KEY_ID",
+        required_if_eq("BLOB_STORE", "s3"),
+        help = "The access key id of the s3 blob store"
+    )]
+    pub s3_access_key_id: Option<String>,
+
+    #[arg(
+        long,
+        env = "S3_SECRET_ACCESS_KEY",
+        name = "S3_SECRET_ACCESS_KEY",
+        required_if_eq("BLOB_STORE", "s3"),
+        hide_env_values = true,
+        help = "The secret access key of the s3 blob store"
+    )]
+    pub s3_secret_access_key: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultStore {
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobStorage {
+    Filesystem,
+    S3,
+    Memory,
+}
+
+impl ServerArgs {
+    /// The s3 blob store settings, once all of them are given.
+    pub fn s3_config(&self) -> Option<S3Config> {
+        Some(S3Config {
+            endpoint: self.s3_endpoint.clone()?,
+            bucket: self.s3_bucket.clone()?,
+            region: self.s3_region.clone(),
+            access_key_id: self.s3_access_key_id.clone()?,
+            secret_access_key: self.s3_secret_access_key.clone()?,
+        })
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct AuthArgs {
+    #[arg(
+        long,
+        env = "OIDC_ISSUER",
+        name = "OIDC_ISSUER",
+        help = "The issuer URL of the OpenID Connect provider, e.g. a Keycloak realm"
+    )]
+    pub issuer: String,
+
+    #[arg(
+        long,
+        env = "OIDC_JWKS_URL",
+        name = "OIDC_JWKS_URL",
+        help = "The JWKS endpoint; defaults to the Keycloak certs endpoint of the issuer"
+    )]
+    pub jwks_url: Option<String>,
+
+    #[arg(
+        long,
+        env = "OIDC_AUDIENCE",
+        name = "OIDC_AUDIENCE",
+        help = "The audience that access tokens must be issued for"
+    )]
+    pub audience: Option<String>,
+
+    #[arg(
+        long,
+        env = "OIDC_ROLE_CLIENT",
+        name = "OIDC_ROLE_CLIENT",
+        help = "The client whose roles are read in addition to realm roles"
+    )]
+    pub role_client: Option<String>,
+
+    #[arg(
+        long,
+        env = "OIDC_REALM_ROLES_CLAIM",
+        name = "OIDC_REALM_ROLES_CLAIM",
+        default_value = "realm_access.roles",
+        help = "The dot-separated path of the claim holding realm roles; empty reads none"
+    )]
+    pub realm_roles_claim: String,
+
+    #[arg(
+        long,
+        env = "OIDC_CLIENT_ROLES_CLAIM",
+        name = "OIDC_CLIENT_ROLES_CLAIM",
+        default_value = "resource_access.{client}.roles",
+        help = "The dot-separated path of the claim holding the roles of the role client, which replaces {client}"
+    )]
+    pub client_roles_claim: String,
+}
+
+impl AuthArgs {
+    /// Realm roles, then the roles of the role client, if any. The defaults
+    /// read the Keycloak layout.
+    pub fn role_mapping(&self) -> RoleMapping {
+        let realm =
+            (!self.realm_roles_claim.is_empty()).then(|| ClaimPath::parse(&self.realm_roles_claim));
+        let client = self.role_client.as_ref().map(|client| {
+            ClaimPath::new(self.client_roles_claim.split('.').map(|segment| {
+                if segment == "{client}" {
+                    client
+                } else {
+                    segment
+                }
+            }))
+        });
+
+        RoleMapping::new(realm.into_iter().chain(client))
+    }
+
+    pub fn jwks_url(&self) -> String {
+        self.jwks_url.clone().unwrap_or_else(|| {
+            format!(
+                "{}/protocol/openid-connect/certs",
+                self.issuer.trim_end_matches('/')
+            )
+        })
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct HistoryArgs {
+    #[arg(
+        long,
+        env = "VAULT_HISTORY_MAX_REVISIONS",
+        name = "VAULT_HISTORY_MAX_REVISIONS",
+        default_value = "50",
+        help = "The number of past revisions kept per vault; 0 keeps all of them"
+    )]
+    pub max_revisions: usize,
+
+    #[arg(
+        long,
+        env = "VAULT_HISTORY_MAX_AGE_DAYS",
+        name = "VAULT_HISTORY_MAX_AGE_DAYS",
+        default_value = "90",
+        help = "The number of days a past revision is kept after being replaced; 0 keeps them forever"
+    )]
+    pub max_age_days: u32,
+
+    #[arg(
+        long,
+        env = "VAULT_HISTORY_PRUNE_INTERVAL_SECS",
+        name = "VAULT_HISTORY_PRUNE_INTERVAL_SECS",
+        default_value = "3600",
+        help = "How often, in seconds, past revisions older than the maximum age are dropped"
+    )]
+    pub prune_interval_secs: u64,
+}
+
+impl HistoryArgs {
+    pub fn retention(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_revisions: (self.max_revisions > 0).then_some(self.max_revisions),
+            max_age: (self.max_age_days > 0).then(|| Duration::days(self.max_age_days.into())),
+        }
+    }
+
+    pub fn prune_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.prune_interval_secs)
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct TrashArgs {
+    #[arg(
+        long,
+        env = "VAULT_TRASH_GRACE_DAYS",
+        name = "VAULT_TRASH_GRACE_DAYS",
+        default_value = "30",
+        help = "The number of days a deleted vault can still be restored before it is purged"
+    )]
+    pub grace_days: u32,
+
+    #[arg(
+        long,
+        env = "VAULT_PURGE_INTERVAL_SECS",
+        name = "VAULT_PURGE_INTERVAL_SECS",
+        default_value = "3600",
+        help = "How often, in seconds, vaults past their grace period are purged"
+    )]
+    pub purge_interval_secs: u64,
+}
+
+impl TrashArgs {
+    pub fn grace_period(&self) -> Duration {
+        Duration::days(self.grace_days.into())
+    }
+
+    pub fn purge_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.purge_interval_secs)
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct AttachmentArgs {
+    #[arg(
+        long,
+        env = "ATTACHMENT_GRACE_HOURS",
+        name = "ATTACHMENT_GRACE_HOURS",
+        default_value = "24",
+        help = "The number of hours an attachment no vault references is kept after its upload completed, or started if it never did, so that the vault can be saved in between"
+    )]
+    pub grace_hours: u32,
+
+    #[arg(
+        long,
+        env = "ATTACHMENT_COLLECT_INTERVAL_SECS",
+        name = "ATTACHMENT_COLLECT_INTERVAL_SECS",
+        default_value = "3600",
+        help = "How often, in seconds, orphaned attachments are collected"
+    )]
+    pub collect_interval_secs: u64,
+}
+
+impl AttachmentArgs {
+    pub fn grace_period(&self) -> Duration {
+        Duration::hours(self.grace_hours.into())
+    }
+
+    pub fn collect_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.collect_interval_secs)
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct QuotaArgs {
+    #[arg(
+        long,
+        env = "QUOTA_MAX_PACKAGE_BYTES",
+        name = "QUOTA_MAX_PACKAGE_BYTES",
+        default_value = "0",
+        help = "The largest encrypted vault package, in bytes, of owners on no plan; 0 means unlimited"
+    )]
+    pub max_package_bytes: u64,
+
+    #[arg(
+        long,
+        env = "QUOTA_MAX_TOTAL_BYTES",
+        name = "QUOTA_MAX_TOTAL_BYTES",
+        default_value = "0",
+        help = "The bytes of vaults, history and attachments together an owner on no plan may store; 0 means unlimited"
+    )]
+    pub max_total_bytes: u64,
+
+    #[arg(
+        long,
+        env = "QUOTA_MAX_VAULTS",
+        name = "QUOTA_MAX_VAULTS",
+        default_value = "0",
+        help = "The number of vaults, trashed ones included, an owner on no plan may hold; 0 means unlimited"
+    )]
+    pub max_vaults: u64,
+
+    #[arg(
+        long,
+        env = "QUOTA_PLANS",
+        name = "QUOTA_PLANS",
+        value_delimiter = ';',
+        value_parser = parse_plan,
+        help = "Plans granted through the role of the same name, by precedence, \
+                e.g. `pro:package=16777216,total=1073741824,vaults=20;free:vaults=1`; \
+                omitted or 0 limits are unlimited"
+    )]
+    pub plans: Vec<Plan>,
+}
+
+impl QuotaArgs {
+    pub fn policy(&self) -> Result<QuotaPolicy, DomainError> {
+        let default = Quota {
+            max_package_bytes: limit(self.max_package_bytes),
+            max_total_bytes: limit(self.max_total_bytes),
+            max_vaults: limit(self.max_vaults),
+        };
+
+        QuotaPolicy::new(default, self.plans.clone())
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct KdfArgs {
+    #[arg(
+        long,
+        env = "KDF_ALGORITHMS",
+        name = "KDF_ALGORITHMS",
+        default_value = "argon2id,scrypt,pbkdf2-hmac-sha256",
+        value_delimiter = ',',
+        value_parser = parse_kdf_alg,
+        help = "The password KDFs accepted on writes; vaults using any other are flagged for an upgrade"
+    )]
+    pub algorithms: Vec<KdfAlg>,
+
+    #[command(flatten)]
+    pub argon2id: Argon2idArgs,
+
+    #[command(flatten)]
+    pub scrypt: ScryptArgs,
+
+    #[command(flatten)]
+    pub pbkdf2: Pbkdf2Args,
+}
+
+impl KdfArgs {
+    pub fn policy(&self) -> Result<KdfPolicy, DomainError> {
+        let bounds = self
+            .algorithms
+            .iter()
+            .map(|alg| match alg {
+                KdfAlg::Argon2id => self.argon2id.bounds(),
+                KdfAlg::Scrypt => self.scrypt.bounds(),
+                KdfAlg::Pbkdf2HmacSha256 => self.pbkdf2.bounds(),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        KdfPolicy::new(bounds)
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Argon2idArgs {
+    #[arg(
+        long = "argon2id-min-m-kib",
+        alias = "min-m-kib",
+        env = "KDF_ARGON2ID_MIN_M_KIB",
+        name = "KDF_ARGON2ID_MIN_M_KIB",
+        default_value = "32768",
+        help = "The lowest Argon2id memory cost, in KiB, accepted on writes"
+    )]
+    pub min_m_kib: u32,
+
+    #[arg(
+        long = "argon2id-min-t",
+        alias = "min-t",
+        env = "KDF_ARGON2ID_MIN_T",
+        name = "KDF_ARGON2ID_MIN_T",
+        default_value = "1",
+        help = "The lowest Argon2id time cost accepted on writes"
+    )]
+    pub min_t: u32,
+
+    #[arg(
+        long = "argon2id-min-p",
+        alias = "min-p",
+        env = "KDF_ARGON2ID_MIN_P",
+        name = "KDF_ARGON2ID_MIN_P",
+        default_value = "1",
+        help = "The lowest Argon2id parallelism accepted on writes"
+    )]
+    pub min_p: u32,
+
+    #[arg(
+        long = "argon2id-recommended-m-kib",
+        alias = "recommended-m-kib",
+        env = "KDF_ARGON2ID_RECOMMENDED_M_KIB",
+        name = "KDF_ARGON2ID_RECOMMENDED_M_KIB",
+        default_value = "131072",
+        help = "The Argon2id memory cost, in KiB, below which clients are told to upgrade"
+    )]
+    pub recommended_m_kib: u32,
+
+    #[arg(
+        long = "argon2id-recommended-t",
+        alias = "recommended-t",
+        env = "KDF_ARGON2ID_RECOMMENDED_T",
+        name = "KDF_ARGON2ID_RECOMMENDED_T",
+        default_value = "3",
+        help = "The Argon2id time cost below which clients are told to upgrade"
+    )]
+    pub recommended_t: u32,
+
+    #[arg(
+        long = "argon2id-recommended-p",
+        alias = "recommended-p",
+        env = "KDF_ARGON2ID_RECOMMENDED_P",
+        name = "KDF_ARGON2ID_RECOMMENDED_P",
+        default_value = "1",
+        help = "The Argon2id parallelism below which clients are told to upgrade"
+    )]
+    pub recommended_p: u32,
+
+    #[arg(
+        long = "argon2id-max-m-kib",
+        alias = "max-m-kib",
+        env = "KDF_ARGON2ID_MAX_M_KIB",
+        name = "KDF_ARGON2ID_MAX_M_KIB",
+        default_value = "1048576",
+        help = "The highest Argon2id memory cost, in KiB, accepted on writes"
+    )]
+    pub max_m_kib: u32,
+
+    #[arg(
+        long = "argon2id-max-t",
+        alias = "max-t",
+        env = "KDF_ARGON2ID_MAX_T",
+        name = "KDF_ARGON2ID_MAX_T",
+        default_value = "10",
+        help = "The highest Argon2id time cost accepted on writes"
+    )]
+    pub max_t: u32,
+
+    #[arg(
+        long = "argon2id-max-p",
+        alias = "max-p",
+        env = "KDF_ARGON2ID_MAX_P",
+        name = "KDF_ARGON2ID_MAX_P",
+        default_value = "16",
+        help = "The highest Argon2id parallelism accepted on writes"
+    )]
+    pub max_p: u32,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ScryptArgs {
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_MIN_LOG_N",
+        name = "KDF_SCRYPT_MIN_LOG_N",
+        default_value = "15",
+        help = "The lowest scrypt cost, as log2 of N, accepted on writes"
+    )]
+    pub min_log_n: u8,
+
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_MIN_R",
+        name = "KDF_SCRYPT_MIN_R",
+        default_value = "8",
+        help = "The lowest scrypt block size accepted on writes"
+    )]
+    pub min_r: u32,
+
+    #[arg(
+        long = "scrypt-min-p",
+        env = "KDF_SCRYPT_MIN_P",
+        name = "KDF_SCRYPT_MIN_P",
+        default_value = "1",
+        help = "The lowest scrypt parallelism accepted on writes"
+    )]
+    pub min_p: u32,
+
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_RECOMMENDED_LOG_N",
+        name = "KDF_SCRYPT_RECOMMENDED_LOG_N",
+        default_value = "17",
+        help = "The scrypt cost, as log2 of N, below which clients are told to upgrade"
+    )]
+    pub recommended_log_n: u8,
+
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_RECOMMENDED_R",
+        name = "KDF_SCRYPT_RECOMMENDED_R",
+        default_value = "8",
+        help = "The scrypt block size below which clients are told to upgrade"
+    )]
+    pub recommended_r: u32,
+
+    #[arg(
+        long = "scrypt-recommended-p",
+        env = "KDF_SCRYPT_RECOMMENDED_P",
+        name = "KDF_SCRYPT_RECOMMENDED_P",
+        default_value = "1",
+        help = "The scrypt parallelism below which clients are told to upgrade"
+    )]
+    pub recommended_p: u32,
+
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_MAX_LOG_N",
+        name = "KDF_SCRYPT_MAX_LOG_N",
+        default_value = "20",
+        help = "The highest scrypt cost, as log2 of N, accepted on writes"
+    )]
+    pub max_log_n: u8,
+
+    #[arg(
+        long,
+        env = "KDF_SCRYPT_MAX_R",
+        name = "KDF_SCRYPT_MAX_R",
+        default_value = "16",
+        help = "The highest scrypt block size accepted on writes"
+    )]
+    pub max_r: u32,
+
+    #[arg(
+        long = "scrypt-max-p",
+        env = "KDF_SCRYPT_MAX_P",
+        name = "KDF_SCRYPT_MAX_P",
+        default_value = "16",
+        help = "The highest scrypt parallelism accepted on writes"
+    )]
+    pub max_p: u32,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Pbkdf2Args {
+    #[arg(
+        long,
+        env = "KDF_PBKDF2_MIN_ITERATIONS",
+        name = "KDF_PBKDF2_MIN_ITERATIONS",
+        default_value = "600000",
+        help = "The lowest PBKDF2 iteration count accepted on writes"
+    )]
+    pub min_iterations: u32,
+
+    #[arg(
+        long,
+        env = "KDF_PBKDF2_RECOMMENDED_ITERATIONS",
+        name = "KDF_PBKDF2_RECOMMENDED_ITERATIONS",
+        default_value = "600000",
+        help = "The PBKDF2 iteration count below which clients are told to upgrade"
+    )]
+    pub recommended_iterations: u32,
+
+    #[arg(
+        long,
+        env = "KDF_PBKDF2_MAX_ITERATIONS",
+        name = "KDF_PBKDF2_MAX_ITERATIONS",
+        default_value = "10000000",
+        help = "The highest PBKDF2 iteration count accepted on writes"
+    )]
+    pub max_iterations: u32,
+}
+
+impl Argon2idArgs {
+    fn bounds(&self) -> Result<KdfBounds, DomainError> {
+        KdfBounds::new(
+            KdfParams::Argon2id {
+                m_kib: self.min_m_kib,
+                t: self.min_t,
+                p: self.min_p,
+            },
+            KdfParams::Argon2id {
+                m_kib: self.recommended_m_kib,
+                t: self.recommended_t,
+                p: self.recommended_p,
+            },
+            KdfParams::Argon2id {
+                m_kib: self.max_m_kib,
+                t: self.max_t,
+                p: self.max_p,
+            },
+        )
+    }
+}
+
+impl ScryptArgs {
+    fn bounds(&self) -> Result<KdfBounds, DomainError> {
+        KdfBounds::new(
+            KdfParams::Scrypt {
+                log_n: self.min_log_n,
+                r: self.min_r,
+                p: self.min_p,
+            },
+            KdfParams::Scrypt {
+                log_n: self.recommended_log_n,
+                r: self.recommended_r,
+                p: self.recommended_p,
+            },
+            KdfParams::Scrypt {
+                log_n: self.max_log_n,
+                r: self.max_r,
+                p: self.max_p,
+            },
+        )
+    }
+}
+
+impl Pbkdf2Args {
+    fn bounds(&self) -> Result<KdfBounds, DomainError> {
+        KdfBounds::new(
+            KdfParams::Pbkdf2HmacSha256 {
+                iterations: self.min_iterations,
+            },
+            KdfParams::Pbkdf2HmacSha256 {
+                iterations: self.recommended_iterations,
+            },
+            KdfParams::Pbkdf2HmacSha256 {
+                iterations: self.max_iterations,
+            },
+        )
+    }
+}
+
+fn parse_kdf_alg(value: &str) -> Result<KdfAlg, String> {
+    match value.trim() {
+        "argon2id" => Ok(KdfAlg::Argon2id),
+        "scrypt" => Ok(KdfAlg::Scrypt),
+        "pbkdf2-hmac-sha256" => Ok(KdfAlg::Pbkdf2HmacSha256),
+        other => Err(format!(
+            "unknown KDF `{other}`, expected argon2id, scrypt or pbkdf2-hmac-sha256"
+        )),
+    }
+}
+
+fn limit(value: u64) -> Option<u64> {
+    (value > 0).then_some(value)
+}
+
+/// Parses `name:key=value,...` with keys `package`, `total` and `vaults`.
+fn parse_plan(value: &str) -> Result<Plan, String> {
+    let (name, limits) = value.split_once(':').unwrap_or((value, ""));
+    let mut quota = Quota::unlimited();
+
+    for entry in limits.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (key, amount) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value in plan limit `{entry}`"))?;
+        let amount: u64 = amount
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid plan limit `{entry}`: {e}"))?;
+
+        match key.trim() {
+            "package" => quota.max_package_bytes = limit(amount),
+            "total" => quota.max_total_bytes = limit(amount),
+            "vaults" => quota.max_vaults = limit(amount),
+            other => {
+                return Err(format!(
+                    "unknown plan limit `{other}`, expected package, total or vaults"
+                ));
+            }
+        }
+    }
+
+    Ok(Plan {
+        name: name.trim().to_string(),
+        quota,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::domain::models::{ClaimPath, RoleMapping};
+    use clap::{CommandFactory, Parser};
+
+    use crate::args::{Args, BlobStorage, VaultStore};
+
+    #[test]
+    fn arguments_are_consistent() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn stores_ask_for_their_settings() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                ["api", "--issuer", "http://localhost/realms/x"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+
+        let defaults = parse(&[]).unwrap().server;
+        assert_eq!(defaults.vault_store, VaultStore::Sqlite);
+        assert_eq!(defaults.blob_store, BlobStorage::Filesystem);
+
+        assert!(parse(&["--vault-store", "postgres"]).is_err());
+        assert!(
+            parse(&[
+                "--vault-store",
+                "postgres",
+                "--database-url",
+                "postgres://db"
+            ])
+            .is_ok()
+        );
+
+        assert!(parse(&["--blob-store", "s3", "--s3-bucket", "vaults"]).is_err());
+        let s3 = parse(&[
+            "--blob-store",
+            "s3",
+            "--s3-endpoint",
+            "http://localhost:9000",
+            "--s3-bucket",
+            "vaults",
+            "--s3-access-key-id",
+            "key",
+            "--s3-secret-access-key",
+            "secret",
+        ])
+        .unwrap();
+        assert_eq!(s3.server.s3_config().unwrap().bucket, "vaults");
+    }
+
+    #[test]
+    fn argon2id_settings_keep_their_old_flags() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                ["api", "--issuer", "http://localhost/realms/x"]
+                    .iter()
+                    .chain(args),
+            )
+            .unwrap()
+            .kdf
+        };
+
+        let kdf = parse(&["--argon2id-min-p", "2", "--scrypt-min-p", "3"]);
+        assert_eq!(kdf.argon2id.min_p, 2);
+        assert_eq!(kdf.scrypt.min_p, 3);
+
+        let kdf = parse(&["--min-p", "2", "--max-m-kib", "65536"]);
+        assert_eq!(kdf.argon2id.min_p, 2);
+        assert_eq!(kdf.argon2id.max_m_kib, 65536);
+    }
+
+    #[test]
+    fn role_claims_default_to_the_keycloak_layout() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                ["api", "--issuer", "http://localhost/realms/x"]
+                    .iter()
+                    .chain(args),
+            )
+            .unwrap()
+            .auth
+            .role_mapping()
+        };
+
+        assert_eq!(parse(&[]), RoleMapping::keycloak(None::<String>));
+        assert_eq!(
+            parse(&["--role-client", "ferrispass-api"]),
+            RoleMapping::keycloak(["ferrispass-api"])
+        );
+        assert_eq!(
+            parse(&[
+                "--role-client",
+                "ferrispass-api",
+                "--realm-roles-claim",
+                "",
+                "--client-roles-claim",
+                "apps.{client}.permissions",
+            ]),
+            RoleMapping::new([ClaimPath::new(["apps", "ferrispass-api", "permissions"])])
+        );
+        assert_eq!(
+            parse(&["--realm-roles-claim", "groups"]),
+            RoleMapping::new([ClaimPath::parse("groups")])
+        );
+    }
 }
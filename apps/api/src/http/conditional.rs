@@ -0,0 +1,217 @@
+//! Entity-tag handling for conditional requests (RFC 9110, section 13).
+
+use application::usecases::get_vault::KnownVersion;
+use axum::http::{
+    HeaderMap, HeaderName, HeaderValue,
+    header::{CACHE_CONTROL, ETAG, IF_MATCH, IF_NONE_MATCH},
+};
+use domain::vault::{Etag, Revision};
+
+use crate::http::errors::ApiError;
+
+pub const VAULT_REVISION: HeaderName = HeaderName::from_static("x-vault-revision");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityTag {
+    pub weak: bool,
+    pub value: String,
+}
+
+impl EntityTag {
+    /// Strong comparison: both tags must be strong and identical.
+    pub fn strong_eq(&self, etag: &Etag) -> bool {
+        !self.weak && self.value == etag.0
+    }
+
+    /// Weak comparison: opaque values match regardless of weakness.
+    pub fn weak_eq(&self, etag: &Etag) -> bool {
+        self.value == etag.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityTags {
+    Any,
+    List(Vec<EntityTag>),
+}
+
+impl EntityTags {
+    pub fn parse(raw: &str) -> Result<Self, ApiError> {
+        let raw = raw.trim();
+
+        if raw == "*" {
+            return Ok(EntityTags::Any);
+        }
+
+        let mut tags = Vec::new();
+
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (weak, quoted) = match part.strip_prefix("W/") {
+                Some(rest) => (true, rest),
+                None => (false, part),
+            };
+
+            let value = quoted
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .filter(|v| !v.contains('"'))
+                .ok_or_else(|| ApiError::bad_request(format!("malformed entity tag: {part}")))?;
+
+            tags.push(EntityTag {
+                weak,
+                value: value.to_string(),
+            });
+        }
+
+        if tags.is_empty() {
+            return Err(ApiError::bad_request("empty entity tag list"));
+        }
+
+        Ok(EntityTags::List(tags))
+    }
+
+    pub fn from_header(headers: &HeaderMap, name: HeaderName) -> Result<Option<Self>, ApiError> {
+        let Some(value) = headers.get(&name) else {
+            return Ok(None);
+        };
+
+        let raw = value
+            .to_str()
+            .map_err(|_| ApiError::bad_request(format!("{name} is not valid ASCII")))?;
+
+        Self::parse(raw).map(Some)
+    }
+
+    /// `If-None-Match` uses the weak comparison function.
+    pub fn matches_weak(&self, etag: &Etag) -> bool {
+        match self {
+            EntityTags::Any => true,
+            EntityTags::List(tags) => tags.iter().any(|t| t.weak_eq(etag)),
+        }
+    }
+
+    /// Versions a client holds, for the weak comparison of `If-None-Match`.
+    pub fn known_versions(&self) -> Vec<KnownVersion> {
+        match self {
+            EntityTags::Any => vec![KnownVersion::Any],
+            EntityTags::List(tags) => tags
+                .iter()
+                .map(|t| KnownVersion::Etag(Etag(t.value.clone())))
+                .collect(),
+        }
+    }
+}
+
+/// Extracts the single strong etag a write must be conditioned on.
+///
+/// Missing `If-Match` yields 428; wildcards and weak tags are refused
+/// because a vault write has to name the exact revision it replaces.
+pub fn required_if_match(headers: &HeaderMap) -> Result<Etag, ApiError> {
+    let tags = EntityTags::from_header(headers, IF_MATCH)?
+        .ok_or_else(|| ApiError::precondition_required("If-Match header is required"))?;
+
+    match tags {
+        EntityTags::List(tags) if tags.len() == 1 && !tags[0].weak => {
+            Ok(Etag(tags[0].value.clone()))
+        }
+        _ => Err(ApiError::bad_request(
+            "If-Match must carry exactly one strong entity tag",
+        )),
+    }
+}
+
+pub fn if_none_match(headers: &HeaderMap) -> Result<Option<EntityTags>, ApiError> {
+    EntityTags::from_header(headers, IF_NONE_MATCH)
+}
+
+/// Headers describing the current representation of a vault.
+pub fn version_headers(etag: &Etag, revision: Revision) -> Result<HeaderMap, ApiError> {
+    let mut headers = HeaderMap::new();
+
+    let etag = HeaderValue::from_str(&format!("\"{}\"", etag.0))
+        .map_err(|_| ApiError::internal("stored etag is not a valid header value"))?;
+
+    headers.insert(ETAG, etag);
+    headers.insert(VAULT_REVISION, HeaderValue::from(revision.0));
+    // Responses are per user: shared caches must not store them, and the
+    // client's own cache must revalidate before reuse.
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("private, no-cache"));
+
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use application::usecases::get_vault::KnownVersion;
+    use axum::http::{HeaderMap, HeaderValue, StatusCode, header::IF_MATCH};
+    use domain::vault::Etag;
+
+    use crate::http::conditional::{EntityTag, EntityTags, required_if_match};
+
+    #[test]
+    fn parses_wildcard() {
+        assert_eq!(EntityTags::parse("*").unwrap(), EntityTags::Any);
+    }
+
+    #[test]
+    fn parses_list_of_strong_and_weak_tags() {
+        let tags = EntityTags::parse(r#""a", W/"b""#).unwrap();
+
+        assert_eq!(
+            tags,
+            EntityTags::List(vec![
+                EntityTag {
+                    weak: false,
+                    value: "a".into()
+                },
+                EntityTag {
+                    weak: true,
+                    value: "b".into()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_unquoted_tags() {
+        assert!(EntityTags::parse("abc").is_err());
+    }
+
+    #[test]
+    fn weak_comparison_ignores_weakness() {
+        let tags = EntityTags::parse(r#"W/"etag-1""#).unwrap();
+
+        assert!(tags.matches_weak(&Etag::new("etag-1").unwrap()));
+        assert!(!tags.matches_weak(&Etag::new("etag-2").unwrap()));
+    }
+
+    #[test]
+    fn wildcard_means_any_known_version() {
+        let tags = EntityTags::parse("*").unwrap();
+
+        assert_eq!(tags.known_versions(), vec![KnownVersion::Any]);
+    }
+
+    #[test]
+    fn if_match_is_required() {
+        let err = required_if_match(&HeaderMap::new()).unwrap_err();
+
+        assert_eq!(err.status, StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[test]
+    fn if_match_refuses_weak_tags() {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, HeaderValue::from_static(r#"W/"etag-1""#));
+
+        assert!(required_if_match(&headers).is_err());
+    }
+
+    #[test]
+    fn if_match_returns_strong_tag() {
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, HeaderValue::from_static(r#""etag-1""#));
+
+        assert_eq!(required_if_match(&headers).unwrap().0, "etag-1");
+    }
+}
@@ -0,0 +1,300 @@
+use application::errors::{AppError, ConflictKind};
+use auth::domain::models::AuthError;
+use axum::{
+    Json,
+    http::{StatusCode, header::WWW_AUTHENTICATE},
+    response::{IntoResponse, Response},
+};
+use domain::quota::QuotaLimit;
+use serde::Serialize;
+use tracing::error;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<&'static str>,
+}
+
+impl ErrorBody {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            resource: None,
+            id: None,
+            kind: None,
+            field: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: Box<ErrorBody>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: Box::new(ErrorBody::new(code, message)),
+        }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn precondition_required(message: impl Into<String>) -> Self {
+        Self::new(
+            StatusCode::PRECONDITION_REQUIRED,
+            "precondition_required",
+            message,
+        )
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        error!("internal error: {}", message.into());
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "internal server error",
+        )
+    }
+
+    /// Maps errors of a write guarded by `If-Match`: a lost etag race is
+    /// reported as 412 Precondition Failed instead of 409 Conflict.
+    pub fn from_conditional_write(e: AppError) -> Self {
+        match e {
+            AppError::Conflict {
+                kind: ConflictKind::Concurrency,
+                resource,
+                id,
+            } => Self {
+                status: StatusCode::PRECONDITION_FAILED,
+                body: Box::new(ErrorBody {
+                    resource: Some(resource.to_string()),
+                    id,
+                    kind: Some(ConflictKind::Concurrency.to_string()),
+                    ..ErrorBody::new("precondition_failed", "etag does not match current vault")
+                }),
+            },
+            e => e.into(),
+        }
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(e: AppError) -> Self {
+        let message = e.to_string();
+
+        match e {
+            AppError::NotFound { resource, id } => Self {
+                status: StatusCode::NOT_FOUND,
+                body: Box::new(ErrorBody {
+                    resource: Some(resource.to_string()),
+                    id,
+                    ..ErrorBody::new("not_found", message)
+                }),
+            },
+
+            AppError::Conflict { kind, resource, id } => Self {
+                status: StatusCode::CONFLICT,
+                body: Box::new(ErrorBody {
+                    resource: Some(resource.to_string()),
+                    id,
+                    kind: Some(kind.to_string()),
+                    ..ErrorBody::new("conflict", message)
+                }),
+            },
+
+            AppError::Validation { field, .. } => Self {
+                status: StatusCode::UNPROCESSABLE_ENTITY,
+                body: Box::new(ErrorBody {
+                    field: Some(field),
+                    ..ErrorBody::new("validation", message)
+                }),
+            },
+
+            // An oversized package is the request's fault; a full account is
+            // the server refusing to store more for this owner.
+            AppError::QuotaExceeded { limit, .. } => Self {
+                status: match limit {
+                    QuotaLimit::PackageBytes => StatusCode::PAYLOAD_TOO_LARGE,
+                    QuotaLimit::TotalBytes | QuotaLimit::Vaults => StatusCode::INSUFFICIENT_STORAGE,
+                },
+                body: Box::new(ErrorBody {
+                    kind: Some(limit.to_string()),
+                    ..ErrorBody::new("quota_exceeded", message)
+                }),
+            },
+
+            AppError::Forbidden { .. } => Self::new(StatusCode::FORBIDDEN, "forbidden", message),
+
+            // Never leak storage details to clients.
+            AppError::Infrastructure { .. } => Self::internal(message),
+        }
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::Network { .. } => {
+                error!("authentication unavailable: {}", e);
+                Self::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "auth_unavailable",
+                    "identity provider unavailable",
+                )
+            }
+            AuthError::Internal { .. } => Self::internal(e.to_string()),
+            e => Self::unauthorized(e.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status == StatusCode::UNAUTHORIZED {
+            return (self.status, [(WWW_AUTHENTICATE, "Bearer")], Json(self.body)).into_response();
+        }
+
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use application::errors::{AppError, ConflictKind, Resource};
+    use auth::domain::models::AuthError;
+    use axum::{
+        http::{StatusCode, header::WWW_AUTHENTICATE},
+        response::IntoResponse,
+    };
+    use domain::quota::QuotaLimit;
+
+    use crate::http::errors::ApiError;
+
+    #[test]
+    fn not_found_maps_to_404() {
+        let err: ApiError = AppError::NotFound {
+            resource: Resource::Vault,
+            id: Some("user1".into()),
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::NOT_FOUND);
+        assert_eq!(err.body.code, "not_found");
+        assert_eq!(err.body.resource.as_deref(), Some("vault"));
+        assert_eq!(err.body.id.as_deref(), Some("user1"));
+    }
+
+    #[test]
+    fn conflict_carries_kind() {
+        let err: ApiError = AppError::Conflict {
+            kind: ConflictKind::Concurrency,
+            resource: Resource::Vault,
+            id: None,
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        assert_eq!(err.body.kind.as_deref(), Some("concurrency"));
+    }
+
+    #[test]
+    fn conditional_write_maps_concurrency_to_412() {
+        let err = ApiError::from_conditional_write(AppError::Conflict {
+            kind: ConflictKind::Concurrency,
+            resource: Resource::Vault,
+            id: None,
+        });
+
+        assert_eq!(err.status, StatusCode::PRECONDITION_FAILED);
+        assert_eq!(err.body.code, "precondition_failed");
+    }
+
+    #[test]
+    fn conditional_write_keeps_already_exists_as_409() {
+        let err = ApiError::from_conditional_write(AppError::Conflict {
+            kind: ConflictKind::AlreadyExists,
+            resource: Resource::Vault,
+            id: None,
+        });
+
+        assert_eq!(err.status, StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn oversized_package_maps_to_413() {
+        let err: ApiError = AppError::QuotaExceeded {
+            limit: QuotaLimit::PackageBytes,
+            allowed: 100,
+            requested: 101,
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(err.body.code, "quota_exceeded");
+        assert_eq!(err.body.kind.as_deref(), Some("package_bytes"));
+    }
+
+    #[test]
+    fn exhausted_storage_maps_to_507() {
+        let err: ApiError = AppError::QuotaExceeded {
+            limit: QuotaLimit::Vaults,
+            allowed: 3,
+            requested: 4,
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::INSUFFICIENT_STORAGE);
+        assert_eq!(err.body.kind.as_deref(), Some("vaults"));
+    }
+
+    #[test]
+    fn infrastructure_message_is_not_leaked() {
+        let err: ApiError = AppError::Infrastructure {
+            message: "connection refused on 10.0.0.3".into(),
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!err.body.message.contains("10.0.0.3"));
+    }
+
+    #[test]
+    fn token_errors_are_unauthorized_with_challenge() {
+        let err: ApiError = AuthError::Expired.into();
+
+        assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(err.body.code, "unauthorized");
+        assert_eq!(err.into_response().headers()[WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[test]
+    fn unreachable_identity_provider_is_service_unavailable() {
+        let err: ApiError = AuthError::Network {
+            message: "connection refused".into(),
+        }
+        .into();
+
+        assert_eq!(err.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!err.body.message.contains("refused"));
+    }
+}
@@ -0,0 +1,48 @@
+use auth::domain::{
+    models::{Identity, Token},
+    ports::Authenticator,
+};
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+
+use crate::http::{errors::ApiError, state::AppState};
+
+/// Caller identity, verified by the configured [`Authenticator`].
+pub struct Authenticated(pub Identity);
+
+impl<R, E, A, T, B, U> FromRequestParts<AppState<R, E, A, T, B, U>> for Authenticated
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState<R, E, A, T, B, U>,
+    ) -> Result<Self, Self::Rejection> {
+        let header = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| ApiError::unauthorized("missing authorization header"))?;
+
+        let token = header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| ApiError::unauthorized("expected a bearer token"))?;
+
+        let identity = state.authenticator.authenticate(&Token::new(token)).await?;
+
+        Ok(Authenticated(identity))
+    }
+}
@@ -0,0 +1,170 @@
+use application::usecases::get_attachment::AttachmentStatus;
+use auth::domain::ports::Authenticator;
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use domain::attachment::{Attachment, AttachmentId};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{errors::ApiError, extractors::Authenticated, state::AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartAttachmentUploadRequest {
+    /// Total encrypted bytes.
+    pub size: u64,
+    pub chunk_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttachmentResponse {
+    pub id: AttachmentId,
+    pub size: u64,
+    pub chunk_size: u32,
+    pub chunk_count: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Chunks still to upload; empty once the attachment is complete.
+    pub missing_chunks: Vec<u32>,
+}
+
+impl AttachmentResponse {
+    fn new(attachment: Attachment, missing_chunks: Vec<u32>) -> Self {
+        Self {
+            id: attachment.id,
+            size: attachment.size,
+            chunk_size: attachment.chunk_size,
+            chunk_count: attachment.chunk_count(),
+            created_at: attachment.created_at,
+            completed_at: attachment.completed_at,
+            missing_chunks,
+        }
+    }
+}
+
+impl From<AttachmentStatus> for AttachmentResponse {
+    fn from(status: AttachmentStatus) -> Self {
+        Self::new(status.attachment, status.missing_chunks)
+    }
+}
+
+pub async fn start_attachment_upload<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Json(request): Json<StartAttachmentUploadRequest>,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let attachment = state
+        .start_attachment_upload
+        .execute(&identity, request.size, request.chunk_size)
+        .await?;
+
+    let missing_chunks = (0..attachment.chunk_count()).collect();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AttachmentResponse::new(attachment, missing_chunks)),
+    )
+        .into_response())
+}
+
+pub async fn get_attachment<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(attachment_id): Path<AttachmentId>,
+) -> Result<Json<AttachmentResponse>, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let status = state
+        .get_attachment
+        .execute(&identity, &attachment_id)
+        .await?;
+
+    Ok(Json(status.into()))
+}
+
+pub async fn upload_attachment_chunk<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path((attachment_id, index)): Path<(AttachmentId, u32)>,
+    body: Bytes,
+) -> Result<StatusCode, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    state
+        .upload_attachment_chunk
+        .execute(&identity, &attachment_id, index, body.to_vec())
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn download_attachment_chunk<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path((attachment_id, index)): Path<(AttachmentId, u32)>,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let chunk = state
+        .download_attachment_chunk
+        .execute(&identity, &attachment_id, index)
+        .await?;
+
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], chunk).into_response())
+}
+
+pub async fn complete_attachment_upload<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(attachment_id): Path<AttachmentId>,
+) -> Result<Json<AttachmentResponse>, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let attachment = state
+        .complete_attachment_upload
+        .execute(&identity, &attachment_id)
+        .await?;
+
+    Ok(Json(AttachmentResponse::new(attachment, vec![])))
+}
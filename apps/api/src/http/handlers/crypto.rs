@@ -0,0 +1,45 @@
+use axum::Json;
+use domain::vault::{AeadAlg, ByteLen, CryptoSuite, CryptoVersion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CryptoSuiteResponse {
+    pub version: CryptoVersion,
+    pub aead: AeadAlg,
+    pub nonce_len: ByteLen,
+    pub tag_len: usize,
+    pub wrapped_key_len: ByteLen,
+}
+
+impl From<&CryptoSuite> for CryptoSuiteResponse {
+    fn from(suite: &CryptoSuite) -> Self {
+        Self {
+            version: suite.version,
+            aead: suite.aead,
+            nonce_len: suite.nonce_len,
+            tag_len: suite.tag_len,
+            wrapped_key_len: suite.wrapped_key_len,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CryptoVersionsResponse {
+    /// Versions clients may pick for new writes.
+    pub supported: Vec<CryptoSuiteResponse>,
+    /// Versions still accepted, which clients should move away from.
+    pub deprecated: Vec<CryptoSuiteResponse>,
+}
+
+/// Lets clients pick a crypto version before encrypting. Public: it only
+/// describes the server.
+pub async fn crypto_versions() -> Json<CryptoVersionsResponse> {
+    let (deprecated, supported): (Vec<&CryptoSuite>, Vec<_>) = CryptoSuite::all()
+        .iter()
+        .partition(|suite| suite.deprecated);
+
+    Json(CryptoVersionsResponse {
+        supported: supported.into_iter().map(Into::into).collect(),
+        deprecated: deprecated.into_iter().map(Into::into).collect(),
+    })
+}
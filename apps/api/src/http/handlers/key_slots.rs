@@ -0,0 +1,111 @@
+use application::usecases::add_key_slot::NewKeySlot;
+use auth::domain::ports::Authenticator;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::vault::{KeySlotId, KeySlotKind, Revision, VaultId};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{
+    conditional::{required_if_match, version_headers},
+    errors::ApiError,
+    extractors::Authenticated,
+    handlers::vault::VaultVersionResponse,
+    state::AppState,
+};
+
+/// A key slot without its id, which the server assigns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddKeySlotRequest {
+    #[serde(flatten)]
+    pub kind: KeySlotKind,
+    pub wrapped_vault_key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeySlotAddedResponse {
+    pub id: VaultId,
+    pub etag: String,
+    pub revision: u64,
+    pub key_slot_id: KeySlotId,
+}
+
+pub async fn add_key_slot<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+    headers: HeaderMap,
+    Json(request): Json<AddKeySlotRequest>,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+    let new_slot = NewKeySlot {
+        kind: request.kind,
+        wrapped_vault_key: request.wrapped_vault_key,
+    };
+
+    let (key_slot_id, etag, revision) = state
+        .add_key_slot
+        .execute(&identity, &vault_id, expected_etag, new_slot)
+        .await
+        .map_err(ApiError::from_conditional_write)?;
+
+    Ok((
+        StatusCode::CREATED,
+        version_headers(&etag, Revision(revision))?,
+        Json(KeySlotAddedResponse {
+            id: vault_id,
+            etag: etag.0,
+            revision,
+            key_slot_id,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn revoke_key_slot<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path((vault_id, key_slot_id)): Path<(VaultId, KeySlotId)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+
+    let (etag, revision) = state
+        .revoke_key_slot
+        .execute(&identity, &vault_id, expected_etag, key_slot_id)
+        .await
+        .map_err(ApiError::from_conditional_write)?;
+
+    Ok((
+        version_headers(&etag, Revision(revision))?,
+        Json(VaultVersionResponse {
+            id: vault_id,
+            etag: etag.0,
+            revision,
+        }),
+    )
+        .into_response())
+}
@@ -0,0 +1,6 @@
+pub mod attachments;
+pub mod crypto;
+pub mod key_slots;
+pub mod revisions;
+pub mod usage;
+pub mod vault;
@@ -0,0 +1,81 @@
+use auth::domain::ports::Authenticator;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
+use domain::vault::{Revision, RevisionSummary, VaultId};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{
+    conditional::{required_if_match, version_headers},
+    errors::ApiError,
+    extractors::Authenticated,
+    handlers::vault::VaultVersionResponse,
+    state::AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RevisionListResponse {
+    /// Past revisions, newest first.
+    pub revisions: Vec<RevisionSummary>,
+}
+
+pub async fn list_revisions<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+) -> Result<Json<RevisionListResponse>, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let revisions = state
+        .list_vault_revisions
+        .execute(&identity, &vault_id)
+        .await?;
+
+    Ok(Json(RevisionListResponse { revisions }))
+}
+
+pub async fn restore_revision<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path((vault_id, revision)): Path<(VaultId, u64)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+
+    let (etag, revision) = state
+        .restore_vault_revision
+        .execute(&identity, &vault_id, expected_etag, Revision(revision))
+        .await
+        .map_err(ApiError::from_conditional_write)?;
+
+    Ok((
+        version_headers(&etag, Revision(revision))?,
+        Json(VaultVersionResponse {
+            id: vault_id,
+            etag: etag.0,
+            revision,
+        }),
+    )
+        .into_response())
+}
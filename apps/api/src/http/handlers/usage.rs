@@ -0,0 +1,49 @@
+use application::usecases::get_storage_usage::UsageReport;
+use auth::domain::ports::Authenticator;
+use axum::{Json, extract::State};
+use domain::quota::{Quota, StorageUsage};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{errors::ApiError, extractors::Authenticated, state::AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UsageResponse {
+    /// Plan whose limits apply, absent for the default quota.
+    pub plan: Option<String>,
+    /// Limits of that plan; `null` means unlimited.
+    pub quota: Quota,
+    pub usage: StorageUsage,
+    pub total_bytes: u64,
+}
+
+impl From<UsageReport> for UsageResponse {
+    fn from(report: UsageReport) -> Self {
+        Self {
+            plan: report.plan,
+            quota: report.quota,
+            total_bytes: report.usage.total_bytes(),
+            usage: report.usage,
+        }
+    }
+}
+
+pub async fn get_usage<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+) -> Result<Json<UsageResponse>, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let report = state.get_storage_usage.execute(&identity).await?;
+
+    Ok(Json(report.into()))
+}
@@ -0,0 +1,350 @@
+use application::usecases::{
+    get_vault::ConditionalVault, store_vault_ciphertext::CiphertextTarget,
+};
+use auth::domain::{models::Identity, ports::Authenticator};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+        header::{CONTENT_TYPE, VARY},
+    },
+    response::{IntoResponse, Response},
+};
+use domain::{
+    attachment::AttachmentId,
+    vault::{Revision, Vault, VaultHeader, VaultId, VaultPackage, VaultSummary},
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{
+    conditional::{if_none_match, required_if_match, version_headers},
+    errors::ApiError,
+    extractors::Authenticated,
+    multipart::{MULTIPART_FORM_DATA, PackageBody, package_body, wants_multipart},
+    state::AppState,
+};
+
+/// Set on vault reads when the stored KDF parameters are below the level
+/// recommended by the server policy.
+pub const KDF_UPGRADE_RECOMMENDED: HeaderName =
+    HeaderName::from_static("x-kdf-upgrade-recommended");
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VaultVersionResponse {
+    pub id: VaultId,
+    pub etag: String,
+    pub revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VaultListResponse {
+    /// Live vaults of the caller, oldest first.
+    pub vaults: Vec<VaultSummary>,
+}
+
+pub async fn list_vaults<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+) -> Result<Json<VaultListResponse>, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let vaults = state.list_vaults.execute(&identity).await?;
+
+    Ok(Json(VaultListResponse { vaults }))
+}
+
+/// Sends the package as JSON, or as `multipart/form-data` when the client
+/// accepts it, which streams a large ciphertext instead of encoding it. A
+/// package whose ciphertext was streamed in is only sent as multipart: JSON
+/// has nowhere to put it.
+pub async fn get_vault<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone + 'static,
+    B: BlobStore + Clone + 'static,
+    U: UsageRepository + Clone,
+{
+    let multipart = wants_multipart(&headers);
+
+    if let Some(tags) = if_none_match(&headers)? {
+        let known = tags.known_versions();
+
+        return match state
+            .get_vault
+            .execute_if_modified(&identity, &vault_id, &known)
+            .await?
+        {
+            ConditionalVault::NotModified(version) => Ok((
+                StatusCode::NOT_MODIFIED,
+                version_headers(&version.etag, version.revision)?,
+            )
+                .into_response()),
+            ConditionalVault::Modified(vault) => {
+                vault_response(&state, identity, *vault, multipart).await
+            }
+        };
+    }
+
+    let vault = state.get_vault.execute(&identity, &vault_id).await?;
+
+    vault_response(&state, identity, vault, multipart).await
+}
+
+async fn vault_response<R, E, A, T, B, U>(
+    state: &AppState<R, E, A, T, B, U>,
+    identity: Identity,
+    vault: Vault,
+    multipart: bool,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone + 'static,
+    B: BlobStore + Clone + 'static,
+    U: UsageRepository + Clone,
+{
+    let mut headers = version_headers(&vault.etag, vault.revision)?;
+    if state.get_vault.kdf_upgrade_recommended(&vault) {
+        headers.insert(KDF_UPGRADE_RECOMMENDED, HeaderValue::from_static("true"));
+    }
+    headers.insert(VARY, HeaderValue::from_static("accept"));
+
+    if !multipart {
+        if vault.package.stored_ciphertext.is_some() {
+            return Err(ApiError::new(
+                StatusCode::NOT_ACCEPTABLE,
+                "not_acceptable",
+                format!("the ciphertext is streamed; accept {MULTIPART_FORM_DATA} to read it"),
+            ));
+        }
+        return Ok((headers, Json(vault)).into_response());
+    }
+
+    let (content_type, body) = package_body(state, identity, vault.package).await?;
+    headers.insert(CONTENT_TYPE, content_type);
+
+    Ok((headers, body).into_response())
+}
+
+/// Takes the package as JSON or, for large ciphertexts, as
+/// `multipart/form-data`; see [`multipart`](crate::http::multipart).
+pub async fn create_vault<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    body: PackageBody,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let package = body
+        .into_package(&state, &identity, CiphertextTarget::NewVault)
+        .await?;
+    let streamed = streamed_attachment(&package);
+
+    let vault = match state.create_vault.execute(&identity, package).await {
+        Ok(vault) => vault,
+        Err(e) => {
+            discard(&state, streamed).await;
+            return Err(e.into());
+        }
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        version_headers(&vault.etag, vault.revision)?,
+        Json(VaultVersionResponse {
+            id: vault.id,
+            etag: vault.etag.0,
+            revision: vault.revision.0,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn put_vault<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+    headers: HeaderMap,
+    body: PackageBody,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+    let target = CiphertextTarget::Vault {
+        vault_id: &vault_id,
+        expected_etag: &expected_etag,
+    };
+    let package = body.into_package(&state, &identity, target).await?;
+    let streamed = streamed_attachment(&package);
+
+    let (etag, revision) = match state
+        .put_vault
+        .execute(&identity, &vault_id, expected_etag.clone(), package)
+        .await
+    {
+        Ok(version) => version,
+        Err(e) => {
+            discard(&state, streamed).await;
+            return Err(ApiError::from_conditional_write(e));
+        }
+    };
+    // An unchanged package saves nothing, so its fresh upload is unused.
+    if etag == expected_etag {
+        discard(&state, streamed).await;
+    }
+
+    Ok((
+        version_headers(&etag, Revision(revision))?,
+        Json(VaultVersionResponse {
+            id: vault_id,
+            etag: etag.0,
+            revision,
+        }),
+    )
+        .into_response())
+}
+
+/// Master-password change: only the header is sent, the ciphertext stays.
+pub async fn rewrap_vault_key<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+    headers: HeaderMap,
+    Json(header): Json<VaultHeader>,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+
+    let (etag, revision) = state
+        .rewrap_vault_key
+        .execute(&identity, &vault_id, expected_etag, header)
+        .await
+        .map_err(ApiError::from_conditional_write)?;
+
+    Ok((
+        version_headers(&etag, Revision(revision))?,
+        Json(VaultVersionResponse {
+            id: vault_id,
+            etag: etag.0,
+            revision,
+        }),
+    )
+        .into_response())
+}
+
+pub async fn delete_vault<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let expected_etag = required_if_match(&headers)?;
+
+    state
+        .delete_vault
+        .execute(&identity, &vault_id, expected_etag)
+        .await
+        .map_err(ApiError::from_conditional_write)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn restore_deleted_vault<R, E, A, T, B, U>(
+    State(state): State<AppState<R, E, A, T, B, U>>,
+    Authenticated(identity): Authenticated,
+    Path(vault_id): Path<VaultId>,
+) -> Result<Response, ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    let vault = state
+        .restore_deleted_vault
+        .execute(&identity, &vault_id)
+        .await?;
+
+    Ok((
+        version_headers(&vault.etag, vault.revision)?,
+        Json(VaultVersionResponse {
+            id: vault.id,
+            etag: vault.etag.0,
+            revision: vault.revision.0,
+        }),
+    )
+        .into_response())
+}
+
+/// Attachment holding the ciphertext streamed in with `package`, if any.
+fn streamed_attachment(package: &VaultPackage) -> Option<AttachmentId> {
+    package
+        .stored_ciphertext
+        .as_ref()
+        .map(|stored| stored.attachment_id)
+}
+
+/// Drops a streamed ciphertext that no saved package ended up pointing at.
+async fn discard<R, E, A, T, B, U>(
+    state: &AppState<R, E, A, T, B, U>,
+    attachment_id: Option<AttachmentId>,
+) where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    if let Some(attachment_id) = attachment_id {
+        state.store_vault_ciphertext.discard(&attachment_id).await;
+    }
+}
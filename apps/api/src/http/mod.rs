@@ -0,0 +1,7 @@
+pub mod conditional;
+pub mod errors;
+pub mod extractors;
+pub mod handlers;
+pub mod multipart;
+pub mod router;
+pub mod state;
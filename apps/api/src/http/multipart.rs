@@ -0,0 +1,352 @@
+//! Vault packages as `multipart/form-data`, for ciphertexts too large to
+//! hold in memory.
+//!
+//! The first part, `header`, is a small JSON [`StreamedPackageHeader`]; the
+//! second, `ciphertext`, is the blob ciphertext as raw bytes. Uploads stream
+//! the second part into the blob store and downloads stream it back out, so
+//! neither holds more than one chunk of it at a time.
+
+use application::{errors::AppError, usecases::store_vault_ciphertext::CiphertextTarget};
+use auth::domain::{models::Identity, ports::Authenticator};
+use axum::{
+    Json,
+    body::Body,
+    extract::{FromRequest, Request},
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{ACCEPT, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Response},
+};
+use domain::{
+    attachment::AttachmentId,
+    vault::{CipherBlob, VaultHeader, VaultPackage},
+};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use multer::{Constraints, Multipart, SizeLimit};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::http::{errors::ApiError, state::AppState};
+
+pub const MULTIPART_FORM_DATA: &str = "multipart/form-data";
+
+pub const HEADER_PART: &str = "header";
+
+pub const CIPHERTEXT_PART: &str = "ciphertext";
+
+/// Largest `header` part accepted; key slots and metadata are small.
+pub const MAX_HEADER_PART_BYTES: u64 = 64 * 1024;
+
+/// Everything of a package but its ciphertext, which follows in its own
+/// part.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedPackageHeader {
+    pub header: VaultHeader,
+    pub nonce: Vec<u8>,
+    pub aad: Vec<u8>,
+    pub ciphertext_size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CipherBlob>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AttachmentId>,
+}
+
+impl StreamedPackageHeader {
+    pub fn of(package: &VaultPackage) -> Self {
+        Self {
+            header: package.header.clone(),
+            nonce: package.blob.nonce.clone(),
+            aad: package.blob.aad.clone(),
+            ciphertext_size: package
+                .stored_ciphertext
+                .as_ref()
+                .map_or(package.blob.ciphertext.len() as u64, |s| s.size),
+            metadata: package.metadata.clone(),
+            attachments: package.attachments.clone(),
+        }
+    }
+
+    /// The package with an empty ciphertext, and the size announced for it.
+    pub fn into_package(self) -> (VaultPackage, u64) {
+        let package = VaultPackage {
+            header: self.header,
+            blob: CipherBlob {
+                nonce: self.nonce,
+                aad: self.aad,
+                ciphertext: vec![],
+            },
+            metadata: self.metadata,
+            attachments: self.attachments,
+            stored_ciphertext: None,
+        };
+
+        (package, self.ciphertext_size)
+    }
+}
+
+/// Body of a vault write: a JSON package, or a multipart one whose parts are
+/// still to be read. Only JSON bodies are subject to the default body limit.
+pub enum PackageBody {
+    Json(Box<VaultPackage>),
+    Multipart(Multipart<'static>),
+}
+
+impl<S> FromRequest<S> for PackageBody
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+
+        if !is_multipart(content_type) {
+            let Json(package) = Json::<VaultPackage>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(Self::Json(Box::new(package)));
+        }
+
+        let boundary =
+            multer::parse_boundary(content_type).map_err(|e| multipart_error(e).into_response())?;
+        let constraints = Constraints::new()
+            .allowed_fields(vec![HEADER_PART, CIPHERTEXT_PART])
+            .size_limit(SizeLimit::new().for_field(HEADER_PART, MAX_HEADER_PART_BYTES));
+
+        Ok(Self::Multipart(Multipart::with_constraints(
+            req.into_body().into_data_stream(),
+            boundary,
+            constraints,
+        )))
+    }
+}
+
+impl PackageBody {
+    /// The package to save into `target`. A streamed ciphertext is stored
+    /// first, once the package and its target are checked, and the package
+    /// then points at it; an upload cut short is discarded.
+    pub async fn into_package<R, E, A, T, B, U>(
+        self,
+        state: &AppState<R, E, A, T, B, U>,
+        identity: &Identity,
+        target: CiphertextTarget<'_>,
+    ) -> Result<VaultPackage, ApiError>
+    where
+        R: VaultRepository + Clone,
+        E: EtagGenerator + Clone,
+        A: Authenticator,
+        T: AttachmentRepository + Clone,
+        B: BlobStore + Clone,
+        U: UsageRepository + Clone,
+    {
+        let mut multipart = match self {
+            Self::Json(package) => return Ok(*package),
+            Self::Multipart(multipart) => multipart,
+        };
+
+        let header = next_part(&mut multipart, HEADER_PART)
+            .await?
+            .bytes()
+            .await
+            .map_err(multipart_error)?;
+        let (package, size) = serde_json::from_slice::<StreamedPackageHeader>(&header)
+            .map_err(|e| ApiError::bad_request(format!("invalid {HEADER_PART} part: {e}")))?
+            .into_package();
+
+        let store = &state.store_vault_ciphertext;
+        let mut upload = store
+            .start(identity, target, &package, size)
+            .await
+            .map_err(ApiError::from_conditional_write)?;
+        let attachment_id = upload.attachment().id;
+
+        let stored = async {
+            let mut ciphertext = next_part(&mut multipart, CIPHERTEXT_PART).await?;
+            while let Some(bytes) = ciphertext.chunk().await.map_err(multipart_error)? {
+                store.write(&mut upload, &bytes).await?;
+            }
+            Ok::<_, ApiError>(store.finish(upload).await?)
+        }
+        .await;
+
+        match stored {
+            Ok(stored) => Ok(package.with_stored_ciphertext(stored)),
+            Err(e) => {
+                store.discard(&attachment_id).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Whether the client asked for packages as `multipart/form-data`. A range
+/// with `q=0` refuses it instead.
+pub fn wants_multipart(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|range| is_multipart(range) && quality(range) > 0.0)
+}
+
+/// `package` as a multipart body and its content type. A stored ciphertext
+/// is read from the blob store one chunk at a time as the body is sent.
+pub async fn package_body<R, E, A, T, B, U>(
+    state: &AppState<R, E, A, T, B, U>,
+    identity: Identity,
+    package: VaultPackage,
+) -> Result<(HeaderValue, Body), ApiError>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone + 'static,
+    B: BlobStore + Clone + 'static,
+    U: UsageRepository + Clone,
+{
+    let boundary = format!("ferrispass-{}", Uuid::new_v4().simple());
+    let content_type =
+        HeaderValue::from_str(&format!("{MULTIPART_FORM_DATA}; boundary={boundary}"))
+            .map_err(|e| ApiError::internal(format!("invalid content type: {e}")))?;
+
+    let header = serde_json::to_vec(&StreamedPackageHeader::of(&package))
+        .map_err(|e| ApiError::internal(format!("failed to encode the header part: {e}")))?;
+    let mut opening = part_opening(&boundary, HEADER_PART, "application/json");
+    opening.extend(header);
+    opening.extend(b"\r\n");
+    opening.extend(part_opening(
+        &boundary,
+        CIPHERTEXT_PART,
+        "application/octet-stream",
+    ));
+    let closing = format!("\r\n--{boundary}--\r\n").into_bytes();
+
+    let ciphertext = match package.stored_ciphertext {
+        None => stream::once(async move { Ok::<_, AppError>(package.blob.ciphertext) }).boxed(),
+        Some(stored) => {
+            let id = stored.attachment_id;
+            let chunk_count = state
+                .get_attachment
+                .execute(&identity, &id)
+                .await?
+                .attachment
+                .chunk_count();
+            let download = state.download_attachment_chunk.clone();
+
+            stream::iter(0..chunk_count)
+                .then(move |index| {
+                    let download = download.clone();
+                    let identity = identity.clone();
+                    async move { download.execute(&identity, &id, index).await }
+                })
+                .boxed()
+        }
+    };
+
+    let body = stream::once(async { Ok::<_, AppError>(opening) })
+        .chain(ciphertext)
+        .chain(stream::once(async { Ok(closing) }))
+        .inspect_err(|e| error!("failed to stream vault ciphertext: {}", e));
+
+    Ok((content_type, Body::from_stream(body)))
+}
+
+fn is_multipart(media_type: &str) -> bool {
+    media_type
+        .split(';')
+        .next()
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case(MULTIPART_FORM_DATA))
+}
+
+/// The `q` parameter of a media range; a missing or malformed one counts as
+/// `1`.
+fn quality(media_range: &str) -> f32 {
+    media_range
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(1.0)
+}
+
+fn part_opening(boundary: &str, name: &str, content_type: &str) -> Vec<u8> {
+    format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\
+         Content-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes()
+}
+
+async fn next_part(
+    multipart: &mut Multipart<'static>,
+    name: &'static str,
+) -> Result<multer::Field<'static>, ApiError> {
+    multipart
+        .next_field()
+        .await
+        .map_err(multipart_error)?
+        .filter(|field| field.name() == Some(name))
+        .ok_or_else(|| ApiError::bad_request(format!("expected the {name} part next")))
+}
+
+fn multipart_error(e: multer::Error) -> ApiError {
+    match e {
+        multer::Error::FieldSizeExceeded { .. } | multer::Error::StreamSizeExceeded { .. } => {
+            ApiError::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                e.to_string(),
+            )
+        }
+        _ => ApiError::bad_request(format!("malformed multipart body: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header::ACCEPT};
+
+    use crate::http::multipart::wants_multipart;
+
+    fn accepting(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(ACCEPT, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn multipart_is_chosen_when_accepted() {
+        assert!(wants_multipart(&accepting(&["multipart/form-data"])));
+        assert!(wants_multipart(&accepting(&[
+            "application/json;q=0.5, Multipart/Form-Data; q=0.8"
+        ])));
+        assert!(wants_multipart(&accepting(&[
+            "application/json",
+            "multipart/form-data;q=0.1"
+        ])));
+        assert!(!wants_multipart(&accepting(&["application/json, */*"])));
+        assert!(!wants_multipart(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn zero_quality_refuses_multipart() {
+        assert!(!wants_multipart(&accepting(&["multipart/form-data;q=0"])));
+        assert!(!wants_multipart(&accepting(&[
+            "application/json, multipart/form-data; q=0.000"
+        ])));
+    }
+}
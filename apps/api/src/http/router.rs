@@ -0,0 +1,1428 @@
+use auth::domain::ports::Authenticator;
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+
+use crate::http::{
+    handlers::{
+        attachments::{
+            complete_attachment_upload, download_attachment_chunk, get_attachment,
+            start_attachment_upload, upload_attachment_chunk,
+        },
+        crypto::crypto_versions,
+        key_slots::{add_key_slot, revoke_key_slot},
+        revisions::{list_revisions, restore_revision},
+        usage::get_usage,
+        vault::{
+            create_vault, delete_vault, get_vault, list_vaults, put_vault, restore_deleted_vault,
+            rewrap_vault_key,
+        },
+    },
+    state::AppState,
+};
+
+pub fn router<R, E, A, T, B, U>(state: AppState<R, E, A, T, B, U>) -> Router
+where
+    R: VaultRepository + Clone + 'static,
+    E: EtagGenerator + Clone + 'static,
+    A: Authenticator + 'static,
+    T: AttachmentRepository + Clone + 'static,
+    B: BlobStore + Clone + 'static,
+    U: UsageRepository + Clone + 'static,
+{
+    Router::new()
+        .route(
+            "/attachments",
+            post(start_attachment_upload::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/attachments/{attachment_id}",
+            get(get_attachment::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/attachments/{attachment_id}/chunks/{index}",
+            get(download_attachment_chunk::<R, E, A, T, B, U>)
+                .put(upload_attachment_chunk::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/attachments/{attachment_id}/complete",
+            post(complete_attachment_upload::<R, E, A, T, B, U>),
+        )
+        .route("/crypto-versions", get(crypto_versions))
+        .route("/usage", get(get_usage::<R, E, A, T, B, U>))
+        .route(
+            "/vaults",
+            get(list_vaults::<R, E, A, T, B, U>).post(create_vault::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}",
+            get(get_vault::<R, E, A, T, B, U>)
+                .put(put_vault::<R, E, A, T, B, U>)
+                .delete(delete_vault::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/header",
+            put(rewrap_vault_key::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/key-slots",
+            post(add_key_slot::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/key-slots/{key_slot_id}",
+            delete(revoke_key_slot::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/restore",
+            post(restore_deleted_vault::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/revisions",
+            get(list_revisions::<R, E, A, T, B, U>),
+        )
+        .route(
+            "/vaults/{vault_id}/revisions/{revision}/restore",
+            post(restore_revision::<R, E, A, T, B, U>),
+        )
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::domain::{
+        models::{AuthError, Client, Identity, User},
+        ports::MockAuthenticator,
+    };
+    use axum::{
+        Router,
+        body::Body,
+        http::{HeaderMap, HeaderName, Method, Request, StatusCode, header},
+    };
+    use chrono::Duration;
+    use domain::{
+        quota::{Plan, Quota, QuotaPolicy},
+        vault::{
+            CipherBlob, CryptoVersion, KdfAlg, KdfParams, KdfPolicy, KdfSpec, KeySlotKind,
+            RetentionPolicy, VaultPackage,
+        },
+    };
+    use etag::ContentHashEtagGenerator;
+    use http_body_util::BodyExt;
+    use memory_storage::{
+        InMemoryAttachmentRepository, InMemoryBlobStore, InMemoryUsageRepository,
+        InMemoryVaultRepository,
+    };
+    use ports::conformance::valid_package;
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::http::{
+        multipart::StreamedPackageHeader,
+        router::router,
+        state::{AppState, Policies},
+    };
+
+    const ISSUER: &str = "http://localhost:8000/realms/ferrispass";
+
+    /// Accepts any bearer token, using the token itself as the subject,
+    /// except `expired` which is rejected and `service` which is a client.
+    /// Subjects starting with `pro` hold the `pro` role.
+    fn authenticator() -> MockAuthenticator {
+        let mut authenticator = MockAuthenticator::new();
+
+        authenticator.expect_authenticate().returning(|token| {
+            let result = match token.as_str() {
+                "expired" => Err(AuthError::Expired),
+                "service" => Ok(Identity::Client(Client {
+                    id: "service".to_string(),
+                    issuer: ISSUER.to_string(),
+                    client_id: "ferrispass-sync".to_string(),
+                    roles: vec![],
+                    scopes: vec![],
+                })),
+                sub => Ok(Identity::User(User {
+                    id: sub.to_string(),
+                    issuer: ISSUER.to_string(),
+                    username: sub.to_string(),
+                    email: None,
+                    name: None,
+                    roles: if sub.starts_with("pro") {
+                        vec!["pro".to_string()]
+                    } else {
+                        vec![]
+                    },
+                })),
+            };
+            Box::pin(async move { result })
+        });
+
+        authenticator
+    }
+
+    fn app() -> Router {
+        app_with(KdfPolicy::default(), QuotaPolicy::unlimited())
+    }
+
+    fn app_with(kdf_policy: KdfPolicy, quota: QuotaPolicy) -> Router {
+        let vault_repository = InMemoryVaultRepository::new();
+        let attachment_repository = InMemoryAttachmentRepository::new();
+        let usage_repository =
+            InMemoryUsageRepository::new(vault_repository.clone(), attachment_repository.clone());
+
+        router(AppState::new(
+            vault_repository,
+            attachment_repository,
+            InMemoryBlobStore::new(),
+            usage_repository,
+            ContentHashEtagGenerator::sha256(),
+            authenticator(),
+            Policies {
+                retention: RetentionPolicy::keep_all(),
+                trash_grace_period: Duration::days(30),
+                kdf: kdf_policy,
+                quota,
+            },
+        ))
+    }
+
+    /// The shared package, without the attachments it references: the
+    /// router only accepts attachments that were uploaded.
+    fn plain_package() -> VaultPackage {
+        VaultPackage {
+            attachments: vec![],
+            ..valid_package()
+        }
+    }
+
+    struct Reply {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: Value,
+    }
+
+    async fn send(
+        app: &Router,
+        method: Method,
+        sub: Option<&str>,
+        headers: &[(HeaderName, &str)],
+        body: Option<Value>,
+    ) -> Reply {
+        send_to(app, "/vaults", method, sub, headers, body).await
+    }
+
+    async fn send_to(
+        app: &Router,
+        uri: &str,
+        method: Method,
+        sub: Option<&str>,
+        headers: &[(HeaderName, &str)],
+        body: Option<Value>,
+    ) -> Reply {
+        let mut request = Request::builder().method(method).uri(uri);
+
+        if let Some(sub) = sub {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {sub}"));
+        }
+
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+        Reply {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    fn package_json() -> Value {
+        serde_json::to_value(plain_package()).unwrap()
+    }
+
+    fn changed_package_json() -> Value {
+        let mut package = plain_package();
+        package.blob.ciphertext = vec![5; 48];
+
+        serde_json::to_value(package).unwrap()
+    }
+
+    async fn create(app: &Router, sub: &str) -> Reply {
+        send(app, Method::POST, Some(sub), &[], Some(package_json())).await
+    }
+
+    fn vault_uri(created: &Reply) -> String {
+        format!("/vaults/{}", created.body["id"].as_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_bearer_token() {
+        let reply = send(&app(), Method::GET, None, &[], None).await;
+
+        assert_eq!(reply.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(reply.body["code"], "unauthorized");
+        assert_eq!(reply.headers[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[tokio::test]
+    async fn rejects_token_refused_by_authenticator() {
+        let reply = send(&app(), Method::GET, Some("expired"), &[], None).await;
+
+        assert_eq!(reply.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(reply.body["message"], "token expired");
+    }
+
+    #[tokio::test]
+    async fn undelegated_service_account_is_forbidden() {
+        let reply = send(&app(), Method::GET, Some("service"), &[], None).await;
+
+        assert_eq!(reply.status, StatusCode::FORBIDDEN);
+        assert_eq!(reply.body["code"], "forbidden");
+    }
+
+    #[tokio::test]
+    async fn get_returns_not_found_before_creation() {
+        let reply = send_to(
+            &app(),
+            "/vaults/0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b",
+            Method::GET,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::NOT_FOUND);
+        assert_eq!(reply.body["code"], "not_found");
+        assert_eq!(reply.body["resource"], "vault");
+    }
+
+    #[tokio::test]
+    async fn create_get_and_put_roundtrip() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        assert_eq!(created.status, StatusCode::CREATED);
+        assert_eq!(created.body["revision"], 0);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(
+            etag,
+            format!("\"{}\"", created.body["etag"].as_str().unwrap())
+        );
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(fetched.status, StatusCode::OK);
+        assert_eq!(fetched.headers[header::ETAG], etag.as_str());
+        assert_eq!(fetched.headers["x-vault-revision"], "0");
+
+        let updated = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(changed_package_json()),
+        )
+        .await;
+        assert_eq!(updated.status, StatusCode::OK);
+        assert_eq!(updated.body["revision"], 1);
+        assert_eq!(updated.headers["x-vault-revision"], "1");
+        assert_ne!(updated.headers[header::ETAG], etag.as_str());
+    }
+
+    #[tokio::test]
+    async fn put_of_unchanged_content_keeps_version() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+        assert!(etag.starts_with("\"sha256-"));
+
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(package_json()),
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::OK);
+        assert_eq!(reply.headers[header::ETAG], etag.as_str());
+        assert_eq!(reply.body["revision"], 0);
+    }
+
+    #[tokio::test]
+    async fn get_answers_not_modified_when_etag_matches() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::GET,
+            Some("user1"),
+            &[(header::IF_NONE_MATCH, &format!("W/{etag}"))],
+            None,
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::NOT_MODIFIED);
+        assert_eq!(reply.headers[header::ETAG], etag.as_str());
+        assert_eq!(reply.body, Value::Null);
+
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::GET,
+            Some("user1"),
+            &[(header::IF_NONE_MATCH, "\"other\"")],
+            None,
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn owner_can_hold_several_vaults() {
+        let app = app();
+
+        let first = create(&app, "user1").await;
+        let second = create(&app, "user1").await;
+        assert_eq!(second.status, StatusCode::CREATED);
+        assert_ne!(first.body["id"], second.body["id"]);
+
+        let listed = send(&app, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(listed.status, StatusCode::OK);
+        let ids: Vec<_> = listed.body["vaults"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|vault| vault["id"].clone())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![first.body["id"].clone(), second.body["id"].clone()]
+        );
+
+        let others = send(&app, Method::GET, Some("user2"), &[], None).await;
+        assert_eq!(others.body["vaults"], Value::Array(vec![]));
+    }
+
+    #[tokio::test]
+    async fn vaults_of_other_owners_are_not_found() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user2"), &[], None).await;
+        assert_eq!(fetched.status, StatusCode::NOT_FOUND);
+
+        let updated = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user2"),
+            &[(header::IF_MATCH, &etag)],
+            Some(changed_package_json()),
+        )
+        .await;
+        assert_eq!(updated.status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn encrypted_metadata_is_listed() {
+        let app = app();
+
+        let mut package = plain_package();
+        package.metadata = Some(CipherBlob {
+            nonce: vec![6; 24],
+            aad: vec![],
+            ciphertext: vec![7; 24],
+        });
+        let created = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(&package).unwrap()),
+        )
+        .await;
+        assert_eq!(created.status, StatusCode::CREATED);
+
+        let listed = send(&app, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(
+            listed.body["vaults"][0]["metadata"],
+            serde_json::to_value(&package.metadata).unwrap()
+        );
+        assert!(listed.body["vaults"][0].get("package").is_none());
+    }
+
+    #[tokio::test]
+    async fn put_without_if_match_is_precondition_required() {
+        let app = app();
+
+        let uri = vault_uri(&create(&app, "user1").await);
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[],
+            Some(package_json()),
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[tokio::test]
+    async fn put_with_stale_etag_is_precondition_failed() {
+        let app = app();
+
+        let uri = vault_uri(&create(&app, "user1").await);
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, "\"stale\"")],
+            Some(package_json()),
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::PRECONDITION_FAILED);
+        assert_eq!(reply.body["kind"], "concurrency");
+    }
+
+    #[tokio::test]
+    async fn invalid_package_is_unprocessable() {
+        let mut package = plain_package();
+        package.header.key_slots[0].kind = KeySlotKind::RecoveryKey { salt: vec![1; 4] };
+
+        let reply = send(
+            &app(),
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(package).unwrap()),
+        )
+        .await;
+
+        assert_eq!(reply.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(reply.body["field"], "key_slots.salt");
+    }
+
+    #[tokio::test]
+    async fn put_archives_and_restore_brings_back_a_revision() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let first = created.headers[header::ETAG].to_str().unwrap().to_string();
+        let updated = send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &first)],
+            Some(changed_package_json()),
+        )
+        .await;
+        let second = updated.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let listed = send_to(
+            &app,
+            &format!("{uri}/revisions"),
+            Method::GET,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert_eq!(listed.status, StatusCode::OK);
+        assert_eq!(listed.body["revisions"].as_array().unwrap().len(), 1);
+        assert_eq!(listed.body["revisions"][0]["revision"], 0);
+        assert_eq!(
+            format!(
+                "\"{}\"",
+                listed.body["revisions"][0]["etag"].as_str().unwrap()
+            ),
+            first
+        );
+
+        let restored = send_to(
+            &app,
+            &format!("{uri}/revisions/0/restore"),
+            Method::POST,
+            Some("user1"),
+            &[(header::IF_MATCH, &second)],
+            None,
+        )
+        .await;
+        assert_eq!(restored.status, StatusCode::OK);
+        assert_eq!(restored.body["revision"], 2);
+        assert_eq!(restored.headers["x-vault-revision"], "2");
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(fetched.body["package"], package_json());
+    }
+
+    #[tokio::test]
+    async fn restore_requires_current_etag() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+        let restore = format!("{uri}/revisions/0/restore");
+
+        let reply = send_to(
+            &app,
+            &restore,
+            Method::POST,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(reply.status, StatusCode::NOT_FOUND);
+        assert_eq!(reply.body["resource"], "vault_revision");
+
+        send_to(
+            &app,
+            &uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(changed_package_json()),
+        )
+        .await;
+
+        let reply = send_to(&app, &restore, Method::POST, Some("user1"), &[], None).await;
+        assert_eq!(reply.status, StatusCode::PRECONDITION_REQUIRED);
+
+        let reply = send_to(
+            &app,
+            &restore,
+            Method::POST,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(reply.status, StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn deleted_vault_is_hidden_until_restored() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let deleted = send_to(
+            &app,
+            &uri,
+            Method::DELETE,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(fetched.status, StatusCode::NOT_FOUND);
+        let listed = send(&app, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(listed.body["vaults"], Value::Array(vec![]));
+
+        let restored = send_to(
+            &app,
+            &format!("{uri}/restore"),
+            Method::POST,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert_eq!(restored.status, StatusCode::OK);
+        assert_eq!(restored.headers[header::ETAG], etag.as_str());
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(fetched.status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn delete_requires_current_etag() {
+        let app = app();
+
+        let uri = vault_uri(&create(&app, "user1").await);
+
+        let reply = send_to(&app, &uri, Method::DELETE, Some("user1"), &[], None).await;
+        assert_eq!(reply.status, StatusCode::PRECONDITION_REQUIRED);
+
+        let reply = send_to(
+            &app,
+            &uri,
+            Method::DELETE,
+            Some("user1"),
+            &[(header::IF_MATCH, "\"stale\"")],
+            None,
+        )
+        .await;
+        assert_eq!(reply.status, StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn weak_kdf_is_refused_and_outdated_kdf_is_flagged() {
+        let app = app();
+
+        let mut package = plain_package();
+        package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 16 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+        let refused = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(&package).unwrap()),
+        )
+        .await;
+        assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(refused.body["field"], "kdf.params.m_kib");
+
+        package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 64 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+        let created = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(&package).unwrap()),
+        )
+        .await;
+        let outdated = send_to(
+            &app,
+            &vault_uri(&created),
+            Method::GET,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert_eq!(outdated.headers["x-kdf-upgrade-recommended"], "true");
+
+        let created = create(&app, "user1").await;
+        let current = send_to(
+            &app,
+            &vault_uri(&created),
+            Method::GET,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert!(!current.headers.contains_key("x-kdf-upgrade-recommended"));
+    }
+
+    #[tokio::test]
+    async fn kdf_algorithms_follow_the_policy() {
+        let mut package = plain_package();
+        package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Scrypt {
+                    log_n: 17,
+                    r: 8,
+                    p: 1,
+                },
+            },
+        };
+        let body = serde_json::to_value(&package).unwrap();
+        assert_eq!(body["header"]["key_slots"][0]["kdf"]["alg"], "Scrypt");
+
+        let created = send(&app(), Method::POST, Some("user1"), &[], Some(body.clone())).await;
+        assert_eq!(created.status, StatusCode::CREATED);
+
+        let argon2id_only =
+            KdfPolicy::new(KdfPolicy::default().bounds(KdfAlg::Argon2id).cloned()).unwrap();
+        let refused = send(
+            &app_with(argon2id_only, QuotaPolicy::unlimited()),
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(body),
+        )
+        .await;
+        assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(refused.body["field"], "kdf.alg");
+    }
+
+    #[tokio::test]
+    async fn crypto_versions_are_advertised_and_unknown_ones_refused() {
+        let app = app();
+
+        let reply = send_to(&app, "/crypto-versions", Method::GET, None, &[], None).await;
+        assert_eq!(reply.status, StatusCode::OK);
+        assert_eq!(reply.body["deprecated"][0]["version"], 1);
+        assert_eq!(reply.body["deprecated"][0]["nonce_len"]["at_least"], 12);
+        assert_eq!(reply.body["supported"][0]["version"], 2);
+        assert_eq!(reply.body["supported"][0]["aead"], "XChaCha20Poly1305");
+        assert_eq!(reply.body["supported"][1]["aead"], "Aes256Gcm");
+        assert_eq!(reply.body["supported"][1]["nonce_len"]["exact"], 12);
+
+        let mut package = plain_package();
+        package.header.crypto_version = CryptoVersion(99);
+        let refused = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(&package).unwrap()),
+        )
+        .await;
+        assert_eq!(refused.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(refused.body["field"], "crypto_version");
+
+        package.header.crypto_version = CryptoVersion::V3;
+        let mismatched = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(&package).unwrap()),
+        )
+        .await;
+        assert_eq!(mismatched.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(mismatched.body["field"], "key_slots.wrapped_vault_key");
+    }
+
+    #[tokio::test]
+    async fn legacy_single_slot_package_is_accepted() {
+        let app = app();
+        let legacy = serde_json::json!({
+            "header": {
+                "crypto_version": 1,
+                "kdf": {
+                    "alg": "Argon2id",
+                    "salt": vec![1; 16],
+                    "params": { "m_kib": 131_072, "t": 3, "p": 1 },
+                },
+                "wrapped_vault_key": vec![2; 48],
+            },
+            "blob": {
+                "nonce": vec![3; 12],
+                "aad": [],
+                "ciphertext": vec![4; 16],
+            },
+        });
+
+        let created = send(&app, Method::POST, Some("user1"), &[], Some(legacy)).await;
+        assert_eq!(created.status, StatusCode::CREATED);
+
+        let uri = vault_uri(&created);
+        let read = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(
+            read.body["package"]["header"]["key_slots"][0]["kind"],
+            "MasterPassword"
+        );
+        assert_eq!(
+            read.body["package"]["header"]["key_slots"][0]["id"],
+            Uuid::nil().to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn header_can_be_rewrapped_without_the_ciphertext() {
+        let app = app();
+        let created = create(&app, "user1").await;
+        let uri = vault_uri(&created);
+        let header_uri = format!("{uri}/header");
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let mut header = plain_package().header;
+        header.key_slots[0].kind = KeySlotKind::RecoveryKey { salt: vec![8; 16] };
+        header.key_slots[0].wrapped_vault_key = vec![9; 72];
+        let header = serde_json::to_value(&header).unwrap();
+
+        let stale = send_to(
+            &app,
+            &header_uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, "\"stale\"")],
+            Some(header.clone()),
+        )
+        .await;
+        assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+
+        let rewrapped = send_to(
+            &app,
+            &header_uri,
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(header),
+        )
+        .await;
+        assert_eq!(rewrapped.status, StatusCode::OK);
+        assert_eq!(rewrapped.body["revision"], 1);
+        assert_ne!(rewrapped.headers[header::ETAG], etag.as_str());
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        let slot = &fetched.body["package"]["header"]["key_slots"][0];
+        assert_eq!(slot["kind"], "RecoveryKey");
+        assert_eq!(slot["salt"][0], 8);
+        assert_eq!(fetched.body["package"]["blob"], package_json()["blob"]);
+    }
+
+    #[tokio::test]
+    async fn key_slots_can_be_added_and_revoked() {
+        let app = app();
+        let mut package = plain_package();
+        package.header.key_slots.truncate(1);
+        let created = send(
+            &app,
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::to_value(package).unwrap()),
+        )
+        .await;
+        let uri = vault_uri(&created);
+        let slots_uri = format!("{uri}/key-slots");
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let added = send_to(
+            &app,
+            &slots_uri,
+            Method::POST,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(serde_json::json!({
+                "kind": "Device",
+                "device_id": "phone",
+                "wrapped_vault_key": vec![7; 72],
+            })),
+        )
+        .await;
+        assert_eq!(added.status, StatusCode::CREATED);
+        assert_eq!(added.body["revision"], 1);
+        let slot_id = added.body["key_slot_id"].as_str().unwrap().to_string();
+        let etag = added.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        let slots = &fetched.body["package"]["header"]["key_slots"];
+        assert_eq!(slots.as_array().unwrap().len(), 2);
+        assert_eq!(slots[1]["id"], slot_id.as_str());
+        assert_eq!(slots[1]["device_id"], "phone");
+
+        let revoked = send_to(
+            &app,
+            &format!("{slots_uri}/{slot_id}"),
+            Method::DELETE,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(revoked.status, StatusCode::OK);
+        assert_eq!(revoked.body["revision"], 2);
+        let etag = revoked.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let gone = send_to(
+            &app,
+            &format!("{slots_uri}/{slot_id}"),
+            Method::DELETE,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(gone.status, StatusCode::NOT_FOUND);
+
+        let last = send_to(
+            &app,
+            &format!("{slots_uri}/{}", Uuid::from_u128(1)),
+            Method::DELETE,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+        assert_eq!(last.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(last.body["field"], "key_slots");
+    }
+
+    /// Sends raw bytes and returns the raw response body, for attachment
+    /// chunks.
+    async fn send_bytes(
+        app: &Router,
+        uri: &str,
+        method: Method,
+        sub: &str,
+        body: Vec<u8>,
+    ) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {sub}"))
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+        (status, bytes.to_vec())
+    }
+
+    #[tokio::test]
+    async fn attachments_upload_in_chunks_and_resume() {
+        let app = app();
+
+        let started = send_to(
+            &app,
+            "/attachments",
+            Method::POST,
+            Some("user1"),
+            &[],
+            Some(serde_json::json!({ "size": 1500, "chunk_size": 1024 })),
+        )
+        .await;
+        assert_eq!(started.status, StatusCode::CREATED);
+        assert_eq!(started.body["chunk_count"], 2);
+        assert_eq!(started.body["missing_chunks"], serde_json::json!([0, 1]));
+        let uri = format!("/attachments/{}", started.body["id"].as_str().unwrap());
+
+        let (status, _) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/1"),
+            Method::PUT,
+            "user1",
+            vec![2; 476],
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let status = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(status.body["missing_chunks"], serde_json::json!([0]));
+
+        let early = send_to(
+            &app,
+            &format!("{uri}/complete"),
+            Method::POST,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert_eq!(early.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(early.body["field"], "chunks");
+
+        let (status, _) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/0"),
+            Method::GET,
+            "user1",
+            vec![],
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/0"),
+            Method::PUT,
+            "user1",
+            vec![1; 1000],
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, _) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/0"),
+            Method::PUT,
+            "user1",
+            vec![1; 1024],
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let completed = send_to(
+            &app,
+            &format!("{uri}/complete"),
+            Method::POST,
+            Some("user1"),
+            &[],
+            None,
+        )
+        .await;
+        assert_eq!(completed.status, StatusCode::OK);
+        assert!(completed.body["completed_at"].is_string());
+
+        let (status, chunk) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/0"),
+            Method::GET,
+            "user1",
+            vec![],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(chunk, vec![1; 1024]);
+
+        let (status, _) = send_bytes(
+            &app,
+            &format!("{uri}/chunks/0"),
+            Method::PUT,
+            "user1",
+            vec![1; 1024],
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let other = send_to(&app, &uri, Method::GET, Some("user2"), &[], None).await;
+        assert_eq!(other.status, StatusCode::NOT_FOUND);
+        assert_eq!(other.body["resource"], "attachment");
+    }
+
+    #[tokio::test]
+    async fn quotas_are_enforced_per_plan_and_reported() {
+        let quota = QuotaPolicy::new(
+            Quota {
+                max_package_bytes: Some(150),
+                max_total_bytes: None,
+                max_vaults: Some(1),
+            },
+            [Plan {
+                name: "pro".into(),
+                quota: Quota::unlimited(),
+            }],
+        )
+        .unwrap();
+        let app = app_with(KdfPolicy::default(), quota);
+
+        let created = create(&app, "user1").await;
+        assert_eq!(created.status, StatusCode::CREATED);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let second = create(&app, "user1").await;
+        assert_eq!(second.status, StatusCode::INSUFFICIENT_STORAGE);
+        assert_eq!(second.body["code"], "quota_exceeded");
+        assert_eq!(second.body["kind"], "vaults");
+
+        let mut package = plain_package();
+        package.blob.ciphertext = vec![5; 200];
+        let oversized = send_to(
+            &app,
+            &vault_uri(&created),
+            Method::PUT,
+            Some("user1"),
+            &[(header::IF_MATCH, &etag)],
+            Some(serde_json::to_value(package).unwrap()),
+        )
+        .await;
+        assert_eq!(oversized.status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(oversized.body["kind"], "package_bytes");
+
+        let usage = send_to(&app, "/usage", Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(usage.status, StatusCode::OK);
+        assert_eq!(usage.body["plan"], Value::Null);
+        assert_eq!(usage.body["quota"]["max_vaults"], 1);
+        assert_eq!(usage.body["usage"]["vaults"], 1);
+        assert_eq!(usage.body["usage"]["vault_bytes"], 112);
+        assert_eq!(usage.body["total_bytes"], 112);
+
+        assert_eq!(create(&app, "pro1").await.status, StatusCode::CREATED);
+        assert_eq!(create(&app, "pro1").await.status, StatusCode::CREATED);
+        let usage = send_to(&app, "/usage", Method::GET, Some("pro1"), &[], None).await;
+        assert_eq!(usage.body["plan"], "pro");
+        assert_eq!(usage.body["usage"]["vaults"], 2);
+    }
+
+    const BOUNDARY: &str = "test-boundary";
+
+    fn multipart_body(header: &StreamedPackageHeader, ciphertext: &[u8]) -> Vec<u8> {
+        let mut body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"header\"\r\n\
+             Content-Type: application/json\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend(serde_json::to_vec(header).unwrap());
+        body.extend(
+            format!(
+                "\r\n--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"ciphertext\"\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend(ciphertext);
+        body.extend(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+        body
+    }
+
+    async fn attachment_bytes(app: &Router) -> u64 {
+        let usage = send_to(app, "/usage", Method::GET, Some("user1"), &[], None).await;
+        usage.body["usage"]["attachment_bytes"].as_u64().unwrap()
+    }
+
+    async fn send_multipart(
+        app: &Router,
+        uri: &str,
+        method: Method,
+        headers: &[(HeaderName, &str)],
+        body: Vec<u8>,
+    ) -> Reply {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, "Bearer user1")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            );
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+        Reply {
+            status,
+            headers,
+            body: serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        }
+    }
+
+    /// Reads a vault as `multipart/form-data`: its header part and its
+    /// ciphertext.
+    async fn get_multipart(app: &Router, uri: &str) -> (StreamedPackageHeader, Vec<u8>) {
+        let request = Request::builder()
+            .uri(uri)
+            .header(header::AUTHORIZATION, "Bearer user1")
+            .header(header::ACCEPT, "multipart/form-data")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        let boundary = multer::parse_boundary(content_type).unwrap();
+        let mut multipart =
+            multer::Multipart::new(response.into_body().into_data_stream(), boundary);
+
+        let header = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(header.name(), Some("header"));
+        let header = serde_json::from_slice(&header.bytes().await.unwrap()).unwrap();
+        let ciphertext = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(ciphertext.name(), Some("ciphertext"));
+        let ciphertext = ciphertext.bytes().await.unwrap().to_vec();
+        assert!(multipart.next_field().await.unwrap().is_none());
+
+        (header, ciphertext)
+    }
+
+    #[tokio::test]
+    async fn streamed_ciphertext_is_not_sent_as_json() {
+        let app = app();
+        let ciphertext = vec![4; 100];
+        let header = StreamedPackageHeader {
+            ciphertext_size: ciphertext.len() as u64,
+            ..StreamedPackageHeader::of(&plain_package())
+        };
+        let created = send_multipart(
+            &app,
+            "/vaults",
+            Method::POST,
+            &[],
+            multipart_body(&header, &ciphertext),
+        )
+        .await;
+        let uri = vault_uri(&created);
+
+        let fetched = send_to(&app, &uri, Method::GET, Some("user1"), &[], None).await;
+        assert_eq!(fetched.status, StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(fetched.body["code"], "not_acceptable");
+
+        let (_, bytes) = get_multipart(&app, &uri).await;
+        assert_eq!(bytes, ciphertext);
+    }
+
+    #[tokio::test]
+    async fn large_ciphertext_is_streamed_in_and_out() {
+        let app = app();
+        // Over the default body limit, and over two blob store chunks.
+        let ciphertext: Vec<u8> = (0..5 * 512 * 1024).map(|i| (i % 251) as u8).collect();
+        let header = StreamedPackageHeader {
+            ciphertext_size: ciphertext.len() as u64,
+            ..StreamedPackageHeader::of(&plain_package())
+        };
+
+        let created = send_multipart(
+            &app,
+            "/vaults",
+            Method::POST,
+            &[],
+            multipart_body(&header, &ciphertext),
+        )
+        .await;
+        assert_eq!(created.status, StatusCode::CREATED);
+        let uri = vault_uri(&created);
+        let etag = created.headers[header::ETAG].to_str().unwrap().to_string();
+
+        let (streamed, bytes) = get_multipart(&app, &uri).await;
+        assert_eq!(streamed.ciphertext_size, ciphertext.len() as u64);
+        assert_eq!(streamed.nonce, plain_package().blob.nonce);
+        assert!(bytes == ciphertext);
+
+        // The same content sent again hashes alike: nothing is written.
+        let unchanged = send_multipart(
+            &app,
+            &uri,
+            Method::PUT,
+            &[(header::IF_MATCH, &etag)],
+            multipart_body(&header, &ciphertext),
+        )
+        .await;
+        assert_eq!(unchanged.status, StatusCode::OK);
+        assert_eq!(unchanged.headers[header::ETAG], etag.as_str());
+        assert_eq!(unchanged.body["revision"], 0);
+
+        let short = send_multipart(
+            &app,
+            &uri,
+            Method::PUT,
+            &[(header::IF_MATCH, &etag)],
+            multipart_body(&header, &ciphertext[1..]),
+        )
+        .await;
+        assert_eq!(short.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(short.body["field"], "ciphertext");
+
+        let stale = send_multipart(
+            &app,
+            &uri,
+            Method::PUT,
+            &[(header::IF_MATCH, "\"stale\"")],
+            multipart_body(&header, &ciphertext),
+        )
+        .await;
+        assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+
+        // Only the ciphertext of the saved package is left stored.
+        assert_eq!(
+            attachment_bytes(&app).await,
+            ciphertext.len() as u64,
+            "unused uploads are dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn refused_streamed_save_drops_its_ciphertext() {
+        let quota = QuotaPolicy::new(
+            Quota {
+                max_package_bytes: None,
+                max_total_bytes: None,
+                max_vaults: Some(1),
+            },
+            [],
+        )
+        .unwrap();
+        let app = app_with(KdfPolicy::default(), quota);
+        assert_eq!(create(&app, "user1").await.status, StatusCode::CREATED);
+        let header = StreamedPackageHeader {
+            ciphertext_size: 64,
+            ..StreamedPackageHeader::of(&plain_package())
+        };
+
+        let refused = send_multipart(
+            &app,
+            "/vaults",
+            Method::POST,
+            &[],
+            multipart_body(&header, &[4; 64]),
+        )
+        .await;
+
+        assert_eq!(refused.status, StatusCode::INSUFFICIENT_STORAGE);
+        assert_eq!(attachment_bytes(&app).await, 0);
+    }
+
+    #[tokio::test]
+    async fn inline_ciphertext_can_be_read_as_multipart() {
+        let app = app();
+
+        let created = create(&app, "user1").await;
+        let (header, ciphertext) = get_multipart(&app, &vault_uri(&created)).await;
+
+        assert_eq!(header.ciphertext_size, 32);
+        assert_eq!(ciphertext, plain_package().blob.ciphertext);
+    }
+
+    #[tokio::test]
+    async fn multipart_parts_must_come_in_order() {
+        let app = app();
+        let body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"ciphertext\"\r\n\r\n\
+             abc\r\n--{BOUNDARY}--\r\n"
+        );
+
+        let reply = send_multipart(&app, "/vaults", Method::POST, &[], body.into_bytes()).await;
+
+        assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    }
+}
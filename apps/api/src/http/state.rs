@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use application::quota::Quotas;
+use application::usecases::{
+    add_key_slot::AddKeySlot, complete_attachment_upload::CompleteAttachmentUpload,
+    create_vault::CreateVault, delete_vault::DeleteVault,
+    download_attachment_chunk::DownloadAttachmentChunk, get_attachment::GetAttachment,
+    get_storage_usage::GetStorageUsage, get_vault::GetVault,
+    list_vault_revisions::ListVaultRevisions, list_vaults::ListVaults, put_vault::PutVault,
+    restore_deleted_vault::RestoreDeletedVault, restore_vault_revision::RestoreVaultRevision,
+    revoke_key_slot::RevokeKeySlot, rewrap_vault_key::RewrapVaultKey,
+    start_attachment_upload::StartAttachmentUpload, store_vault_ciphertext::StoreVaultCiphertext,
+    upload_attachment_chunk::UploadAttachmentChunk,
+};
+use auth::domain::ports::Authenticator;
+use chrono::Duration;
+use domain::{
+    quota::QuotaPolicy,
+    vault::{KdfPolicy, RetentionPolicy},
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::SystemClock,
+    etag::EtagGenerator, id::UuidV7Generator, usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+/// Server-wide rules the use cases enforce.
+#[derive(Debug, Clone)]
+pub struct Policies {
+    pub retention: RetentionPolicy,
+    pub trash_grace_period: Duration,
+    pub kdf: KdfPolicy,
+    pub quota: QuotaPolicy,
+}
+
+pub struct AppState<R, E, A, T, B, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    A: Authenticator,
+    T: AttachmentRepository,
+    B: BlobStore,
+    U: UsageRepository,
+{
+    pub authenticator: Arc<A>,
+    pub list_vaults: Arc<ListVaults<R>>,
+    pub get_vault: Arc<GetVault<R>>,
+    pub create_vault: Arc<CreateVault<R, T, E, SystemClock, UuidV7Generator, U>>,
+    pub put_vault: Arc<PutVault<R, T, E, SystemClock, U>>,
+    pub store_vault_ciphertext: Arc<StoreVaultCiphertext<R, T, B, SystemClock, UuidV7Generator, U>>,
+    pub rewrap_vault_key: Arc<RewrapVaultKey<R, E, SystemClock, U>>,
+    pub add_key_slot: Arc<AddKeySlot<R, E, SystemClock, UuidV7Generator, U>>,
+    pub revoke_key_slot: Arc<RevokeKeySlot<R, E, SystemClock, U>>,
+    pub list_vault_revisions: Arc<ListVaultRevisions<R>>,
+    pub restore_vault_revision: Arc<RestoreVaultRevision<R, E, SystemClock, U>>,
+    pub delete_vault: Arc<DeleteVault<R, SystemClock>>,
+    pub restore_deleted_vault: Arc<RestoreDeletedVault<R, SystemClock>>,
+    pub start_attachment_upload: Arc<StartAttachmentUpload<T, SystemClock, UuidV7Generator, U>>,
+    pub upload_attachment_chunk: Arc<UploadAttachmentChunk<T, B>>,
+    pub get_attachment: Arc<GetAttachment<T, B>>,
+    pub complete_attachment_upload: Arc<CompleteAttachmentUpload<T, B, SystemClock>>,
+    pub download_attachment_chunk: Arc<DownloadAttachmentChunk<T, B>>,
+    pub get_storage_usage: Arc<GetStorageUsage<U>>,
+}
+
+impl<R, E, A, T, B, U> AppState<R, E, A, T, B, U>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    pub fn new(
+        vault_repository: R,
+        attachment_repository: T,
+        blob_store: B,
+        usage_repository: U,
+        etag_generator: E,
+        authenticator: A,
+        policies: Policies,
+    ) -> Self {
+        let Policies {
+            retention,
+            trash_grace_period,
+            kdf: kdf_policy,
+            quota,
+        } = policies;
+        let quotas = Quotas::new(usage_repository, quota);
+
+        Self {
+            authenticator: Arc::new(authenticator),
+            create_vault: Arc::new(CreateVault::new(
+                vault_repository.clone(),
+                attachment_repository.clone(),
+                etag_generator.clone(),
+                SystemClock,
+                UuidV7Generator,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            put_vault: Arc::new(PutVault::new(
+                vault_repository.clone(),
+                attachment_repository.clone(),
+                etag_generator.clone(),
+                SystemClock,
+                retention,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            store_vault_ciphertext: Arc::new(StoreVaultCiphertext::new(
+                vault_repository.clone(),
+                attachment_repository.clone(),
+                blob_store.clone(),
+                SystemClock,
+                UuidV7Generator,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            rewrap_vault_key: Arc::new(RewrapVaultKey::new(
+                vault_repository.clone(),
+                etag_generator.clone(),
+                SystemClock,
+                retention,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            add_key_slot: Arc::new(AddKeySlot::new(
+                vault_repository.clone(),
+                etag_generator.clone(),
+                SystemClock,
+                UuidV7Generator,
+                retention,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            revoke_key_slot: Arc::new(RevokeKeySlot::new(
+                vault_repository.clone(),
+                etag_generator.clone(),
+                SystemClock,
+                retention,
+                quotas.clone(),
+            )),
+            restore_vault_revision: Arc::new(RestoreVaultRevision::new(
+                vault_repository.clone(),
+                etag_generator,
+                SystemClock,
+                retention,
+                kdf_policy.clone(),
+                quotas.clone(),
+            )),
+            list_vaults: Arc::new(ListVaults::new(vault_repository.clone())),
+            list_vault_revisions: Arc::new(ListVaultRevisions::new(vault_repository.clone())),
+            delete_vault: Arc::new(DeleteVault::new(vault_repository.clone(), SystemClock)),
+            restore_deleted_vault: Arc::new(RestoreDeletedVault::new(
+                vault_repository.clone(),
+                SystemClock,
+                trash_grace_period,
+            )),
+            get_vault: Arc::new(GetVault::new(vault_repository, kdf_policy)),
+            start_attachment_upload: Arc::new(StartAttachmentUpload::new(
+                attachment_repository.clone(),
+                SystemClock,
+                UuidV7Generator,
+                quotas.clone(),
+            )),
+            upload_attachment_chunk: Arc::new(UploadAttachmentChunk::new(
+                attachment_repository.clone(),
+                blob_store.clone(),
+            )),
+            get_attachment: Arc::new(GetAttachment::new(
+                attachment_repository.clone(),
+                blob_store.clone(),
+            )),
+            complete_attachment_upload: Arc::new(CompleteAttachmentUpload::new(
+                attachment_repository.clone(),
+                blob_store.clone(),
+                SystemClock,
+            )),
+            download_attachment_chunk: Arc::new(DownloadAttachmentChunk::new(
+                attachment_repository,
+                blob_store,
+            )),
+            get_storage_usage: Arc::new(GetStorageUsage::new(quotas)),
+        }
+    }
+}
+
+impl<R, E, A, T, B, U> Clone for AppState<R, E, A, T, B, U>
+where
+    R: VaultRepository + Clone,
+    E: EtagGenerator + Clone,
+    A: Authenticator,
+    T: AttachmentRepository + Clone,
+    B: BlobStore + Clone,
+    U: UsageRepository + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            authenticator: self.authenticator.clone(),
+            list_vaults: self.list_vaults.clone(),
+            get_vault: self.get_vault.clone(),
+            create_vault: self.create_vault.clone(),
+            put_vault: self.put_vault.clone(),
+            store_vault_ciphertext: self.store_vault_ciphertext.clone(),
+            rewrap_vault_key: self.rewrap_vault_key.clone(),
+            add_key_slot: self.add_key_slot.clone(),
+            revoke_key_slot: self.revoke_key_slot.clone(),
+            list_vault_revisions: self.list_vault_revisions.clone(),
+            restore_vault_revision: self.restore_vault_revision.clone(),
+            delete_vault: self.delete_vault.clone(),
+            restore_deleted_vault: self.restore_deleted_vault.clone(),
+            start_attachment_upload: self.start_attachment_upload.clone(),
+            upload_attachment_chunk: self.upload_attachment_chunk.clone(),
+            get_attachment: self.get_attachment.clone(),
+            complete_attachment_upload: self.complete_attachment_upload.clone(),
+            download_attachment_chunk: self.download_attachment_chunk.clone(),
+            get_storage_usage: self.get_storage_usage.clone(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use application::usecases::{
+    collect_orphaned_attachments::CollectOrphanedAttachments,
+    prune_expired_history::PruneExpiredHistory, purge_deleted_vaults::PurgeDeletedVaults,
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::Clock,
+    vault_repository::VaultRepository,
+};
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+use tracing::{error, info};
+
+/// Runs the trash purge every `period` until the runtime shuts down.
+pub fn spawn_purge<R, C>(purge: PurgeDeletedVaults<R, C>, period: Duration) -> JoinHandle<()>
+where
+    R: VaultRepository + 'static,
+    C: Clock + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(period);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticks.tick().await;
+
+            match purge.execute().await {
+                Ok(0) => {}
+                Ok(purged) => info!("purged {} deleted vaults", purged),
+                Err(e) => error!("failed to purge deleted vaults: {}", e),
+            }
+        }
+    })
+}
+
+/// Runs the history pruning every `period` until the runtime shuts down.
+pub fn spawn_history_pruning<R, C>(
+    prune: PruneExpiredHistory<R, C>,
+    period: Duration,
+) -> JoinHandle<()>
+where
+    R: VaultRepository + 'static,
+    C: Clock + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(period);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticks.tick().await;
+
+            match prune.execute().await {
+                Ok(0) => {}
+                Ok(pruned) => info!("pruned {} expired vault revisions", pruned),
+                Err(e) => error!("failed to prune vault history: {}", e),
+            }
+        }
+    })
+}
+
+/// Runs the orphaned attachment collection every `period` until the runtime
+/// shuts down.
+pub fn spawn_attachment_collection<R, T, B, C>(
+    collect: CollectOrphanedAttachments<R, T, B, C>,
+    period: Duration,
+) -> JoinHandle<()>
+where
+    R: VaultRepository + 'static,
+    T: AttachmentRepository + 'static,
+    B: BlobStore + 'static,
+    C: Clock + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(period);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticks.tick().await;
+
+            // The use case logs what it collected.
+            if let Err(e) = collect.execute().await {
+                error!("failed to collect orphaned attachments: {}", e);
+            }
+        }
+    })
+}
@@ -1,15 +1,257 @@
 use std::error::Error;
 
-use clap::Parser;
+use application::usecases::{
+    collect_orphaned_attachments::CollectOrphanedAttachments,
+    prune_expired_history::PruneExpiredHistory, purge_deleted_vaults::PurgeDeletedVaults,
+};
+use auth::{
+    domain::{authenticator::TokenAuthenticator, models::ClaimsPolicy, ports::Authenticator},
+    infrastructure::jwks::{HttpJwksSource, JwksCache, JwtVerifier},
+};
+use etag::ContentHashEtagGenerator;
+use filesystem_storage::FilesystemBlobStore;
+use memory_storage::{
+    InMemoryAttachmentRepository, InMemoryBlobStore, InMemoryUsageRepository,
+    InMemoryVaultRepository,
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::SystemClock,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use postgres_storage::PostgresVaultRepository;
+use s3_storage::S3BlobStore;
+use sqlite_storage::SqliteVaultRepository;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
-use crate::args::Args;
+use crate::{
+    args::{Args, BlobStorage, VaultStore},
+    http::{
+        router::router,
+        state::{AppState, Policies},
+    },
+    jobs::{spawn_attachment_collection, spawn_history_pruning, spawn_purge},
+};
 
 pub mod args;
+pub mod http;
+pub mod jobs;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    dotenvy::dotenv()?;
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    // A missing .env file is fine: configuration can come from the environment.
+    let _ = dotenvy::dotenv();
 
-    let _args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let args = Args::load();
+
+    let mut policy = ClaimsPolicy::new([args.auth.issuer.clone()]);
+    if let Some(audience) = &args.auth.audience {
+        policy = policy.with_audience(audience);
+    }
+
+    let keys = JwksCache::new(HttpJwksSource::new(args.auth.jwks_url())?);
+    let authenticator =
+        TokenAuthenticator::new(JwtVerifier::new(keys, policy), args.auth.role_mapping());
+
+    let policies = Policies {
+        retention: args.history.retention(),
+        trash_grace_period: args.trash.grace_period(),
+        kdf: args.kdf.policy()?,
+        quota: args.quota.policy()?,
+    };
+
+    match args.server.vault_store {
+        VaultStore::Postgres => {
+            let url = args
+                .server
+                .database_url
+                .as_deref()
+                .ok_or("DATABASE_URL is required by the postgres vault store")?;
+            let vault_repository = PostgresVaultRepository::connect(url).await?;
+            vault_repository.migrate().await?;
+            info!("vaults are kept in postgres");
+
+            with_blob_store(
+                &args,
+                authenticator,
+                policies,
+                vault_repository.clone(),
+                vault_repository.attachments(),
+                vault_repository,
+            )
+            .await
+        }
+        VaultStore::Sqlite => {
+            let vault_repository = SqliteVaultRepository::open(&args.server.sqlite_path).await?;
+            vault_repository.migrate().await?;
+            info!("vaults are kept in {}", args.server.sqlite_path.display());
+
+            with_blob_store(
+                &args,
+                authenticator,
+                policies,
+                vault_repository.clone(),
+                vault_repository.attachments(),
+                vault_repository,
+            )
+            .await
+        }
+        VaultStore::Memory => {
+            let vault_repository = InMemoryVaultRepository::new();
+            let attachment_repository = InMemoryAttachmentRepository::new();
+            let usage_repository = InMemoryUsageRepository::new(
+                vault_repository.clone(),
+                attachment_repository.clone(),
+            );
+            warn!("vaults are kept in memory and lost on restart");
+
+            with_blob_store(
+                &args,
+                authenticator,
+                policies,
+                vault_repository,
+                attachment_repository,
+                usage_repository,
+            )
+            .await
+        }
+    }
+}
+
+/// Picks the blob store, then serves with it and the given repositories.
+async fn with_blob_store<A, R, T, U>(
+    args: &Args,
+    authenticator: A,
+    policies: Policies,
+    vault_repository: R,
+    attachment_repository: T,
+    usage_repository: U,
+) -> Result<(), Box<dyn Error>>
+where
+    A: Authenticator + 'static,
+    R: VaultRepository + Clone + 'static,
+    T: AttachmentRepository + Clone + 'static,
+    U: UsageRepository + Clone + 'static,
+{
+    let repositories = (vault_repository, attachment_repository, usage_repository);
+
+    match args.server.blob_store {
+        BlobStorage::Filesystem => {
+            info!("blobs are kept in {}", args.server.blob_dir.display());
+            let blob_store = FilesystemBlobStore::new(&args.server.blob_dir);
+            serve(args, authenticator, policies, repositories, blob_store).await
+        }
+        BlobStorage::S3 => {
+            let config = args.server.s3_config().ok_or(
+                "S3_ENDPOINT, S3_BUCKET and the S3 credentials are required by the s3 blob store",
+            )?;
+            info!("blobs are kept in the {} bucket", config.bucket);
+            serve(
+                args,
+                authenticator,
+                policies,
+                repositories,
+                S3BlobStore::new(config),
+            )
+            .await
+        }
+        BlobStorage::Memory => {
+            warn!("blobs are kept in memory and lost on restart");
+            let blob_store = InMemoryBlobStore::new();
+            serve(args, authenticator, policies, repositories, blob_store).await
+        }
+    }
+}
+
+async fn serve<A, R, T, U, B>(
+    args: &Args,
+    authenticator: A,
+    policies: Policies,
+    (vault_repository, attachment_repository, usage_repository): (R, T, U),
+    blob_store: B,
+) -> Result<(), Box<dyn Error>>
+where
+    A: Authenticator + 'static,
+    R: VaultRepository + Clone + 'static,
+    T: AttachmentRepository + Clone + 'static,
+    U: UsageRepository + Clone + 'static,
+    B: BlobStore + Clone + 'static,
+{
+    spawn_purge(
+        PurgeDeletedVaults::new(
+            vault_repository.clone(),
+            SystemClock,
+            args.trash.grace_period(),
+        ),
+        args.trash.purge_interval(),
+    );
+
+    spawn_history_pruning(
+        PruneExpiredHistory::new(vault_repository.clone(), SystemClock, policies.retention),
+        args.history.prune_interval(),
+    );
+
+    spawn_attachment_collection(
+        CollectOrphanedAttachments::new(
+            vault_repository.clone(),
+            attachment_repository.clone(),
+            blob_store.clone(),
+            SystemClock,
+            args.attachments.grace_period(),
+        ),
+        args.attachments.collect_interval(),
+    );
+
+    let state = AppState::new(
+        vault_repository,
+        attachment_repository,
+        blob_store,
+        usage_repository,
+        ContentHashEtagGenerator::sha256(),
+        authenticator,
+        policies,
+    );
+    let app = router(state);
+
+    let listener = TcpListener::bind((args.server.host.as_str(), args.server.port)).await?;
+    info!("listening on {}", listener.local_addr()?);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    info!("server stopped");
 
     Ok(())
 }
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("shutdown signal received, draining connections");
+}
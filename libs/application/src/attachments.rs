@@ -0,0 +1,207 @@
+use domain::{
+    DomainError,
+    attachment::AttachmentId,
+    vault::{OwnerSub, VaultPackage},
+};
+use ports::attachment_repository::AttachmentRepository;
+
+use crate::errors::AppError;
+
+/// Checks that every attachment `package` references, its streamed
+/// ciphertext included, is a complete upload of `owner_id`.
+///
+/// An attachment of another owner is reported as unknown, like in every
+/// other attachment lookup.
+pub(crate) async fn check_references<T: AttachmentRepository>(
+    attachment_repository: &T,
+    owner_id: &OwnerSub,
+    package: &VaultPackage,
+) -> Result<(), AppError> {
+    for attachment_id in &package.attachments {
+        check_reference(
+            attachment_repository,
+            owner_id,
+            attachment_id,
+            "attachments",
+        )
+        .await?;
+    }
+
+    if let Some(stored) = &package.stored_ciphertext {
+        let size = check_reference(
+            attachment_repository,
+            owner_id,
+            &stored.attachment_id,
+            "stored_ciphertext.attachment_id",
+        )
+        .await?;
+
+        if size != stored.size {
+            return Err(invalid(
+                "stored_ciphertext.size",
+                format!("is {} but the attachment holds {size} bytes", stored.size),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Size of the referenced attachment, once found complete.
+async fn check_reference<T: AttachmentRepository>(
+    attachment_repository: &T,
+    owner_id: &OwnerSub,
+    attachment_id: &AttachmentId,
+    field: &'static str,
+) -> Result<u64, AppError> {
+    let attachment = attachment_repository
+        .find(owner_id, attachment_id)
+        .await?
+        .ok_or_else(|| {
+            invalid(
+                field,
+                format!("attachment {} does not exist", attachment_id.0),
+            )
+        })?;
+
+    if !attachment.is_complete() {
+        return Err(invalid(
+            field,
+            format!("attachment {} is not completely uploaded", attachment_id.0),
+        ));
+    }
+
+    Ok(attachment.size)
+}
+
+fn invalid(field: &'static str, message: String) -> AppError {
+    DomainError::Validation { field, message }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::{Attachment, AttachmentId},
+        vault::{OwnerSub, StoredCiphertext, VaultPackage},
+    };
+    use memory_storage::InMemoryAttachmentRepository;
+    use ports::attachment_repository::AttachmentRepository;
+    use uuid::Uuid;
+
+    use crate::{
+        attachments::check_references,
+        errors::AppError,
+        fixtures::{now, owner, valid_package},
+    };
+
+    fn package(attachments: Vec<AttachmentId>) -> VaultPackage {
+        VaultPackage {
+            attachments,
+            ..valid_package()
+        }
+    }
+
+    /// An attachment of `size` bytes in one chunk, completed when asked.
+    async fn stored(
+        repo: &InMemoryAttachmentRepository,
+        owner_id: OwnerSub,
+        size: u32,
+        complete: bool,
+    ) -> AttachmentId {
+        let mut attachment = Attachment::new(
+            AttachmentId(Uuid::new_v4()),
+            owner_id,
+            size.into(),
+            size,
+            now(),
+        )
+        .unwrap();
+        repo.create(&attachment).await.unwrap();
+
+        if complete {
+            attachment = attachment.complete(&[0], now()).unwrap();
+            repo.mark_completed(&attachment).await.unwrap();
+        }
+        attachment.id
+    }
+
+    fn refused(result: Result<(), AppError>, expected: &str) -> bool {
+        matches!(result, Err(AppError::Validation { field, .. }) if field == expected)
+    }
+
+    #[tokio::test]
+    async fn complete_attachments_of_the_owner_are_accepted() {
+        let repo = InMemoryAttachmentRepository::new();
+        let first = stored(&repo, owner(), 10, true).await;
+        let second = stored(&repo, owner(), 20, true).await;
+
+        let result = check_references(&repo, &owner(), &package(vec![first, second])).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn unknown_attachment_is_refused() {
+        let repo = InMemoryAttachmentRepository::new();
+        let unknown = AttachmentId(Uuid::new_v4());
+
+        let result = check_references(&repo, &owner(), &package(vec![unknown])).await;
+
+        assert!(refused(result, "attachments"));
+    }
+
+    #[tokio::test]
+    async fn incomplete_attachment_is_refused() {
+        let repo = InMemoryAttachmentRepository::new();
+        let pending = stored(&repo, owner(), 10, false).await;
+
+        let result = check_references(&repo, &owner(), &package(vec![pending])).await;
+
+        assert!(refused(result, "attachments"));
+    }
+
+    #[tokio::test]
+    async fn attachment_of_another_owner_is_refused() {
+        let repo = InMemoryAttachmentRepository::new();
+        let other = OwnerSub::new("http://localhost:8000/realms/ferrispass|user2").unwrap();
+        let theirs = stored(&repo, other, 10, true).await;
+
+        let result = check_references(&repo, &owner(), &package(vec![theirs])).await;
+
+        assert!(refused(result, "attachments"));
+    }
+
+    #[tokio::test]
+    async fn stored_ciphertext_must_match_its_attachment() {
+        let repo = InMemoryAttachmentRepository::new();
+        let ciphertext = stored(&repo, owner(), 64, true).await;
+        let with_size = |size| {
+            package(vec![]).with_stored_ciphertext(StoredCiphertext {
+                attachment_id: ciphertext,
+                size,
+                sha256: vec![0; 32],
+            })
+        };
+
+        assert!(
+            check_references(&repo, &owner(), &with_size(64))
+                .await
+                .is_ok()
+        );
+        assert!(refused(
+            check_references(&repo, &owner(), &with_size(65)).await,
+            "stored_ciphertext.size"
+        ));
+
+        let pending = stored(&repo, owner(), 64, false).await;
+        let unfinished = package(vec![]).with_stored_ciphertext(StoredCiphertext {
+            attachment_id: pending,
+            size: 64,
+            sha256: vec![0; 32],
+        });
+        assert!(refused(
+            check_references(&repo, &owner(), &unfinished).await,
+            "stored_ciphertext.attachment_id"
+        ));
+    }
+}
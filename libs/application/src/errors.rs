@@ -1,18 +1,24 @@
 use std::fmt::Display;
 
-use domain::DomainError;
-use ports::RepositoryError;
+use domain::{DomainError, quota::QuotaLimit};
+use ports::{BlobStoreError, RepositoryError};
 use thiserror::Error;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Resource {
     Vault,
+    VaultRevision,
+    KeySlot,
+    Attachment,
 }
 
 impl Display for Resource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Resource::Vault => write!(f, "vault"),
+            Resource::VaultRevision => write!(f, "vault_revision"),
+            Resource::KeySlot => write!(f, "key_slot"),
+            Resource::Attachment => write!(f, "attachment"),
         }
     }
 }
@@ -21,6 +27,7 @@ impl Display for Resource {
 pub enum ConflictKind {
     Concurrency, // ETag / revision mismatch
     AlreadyExists,
+    Deleted, // write to a vault in the trash
 }
 
 impl Display for ConflictKind {
@@ -28,6 +35,7 @@ impl Display for ConflictKind {
         match self {
             ConflictKind::Concurrency => write!(f, "concurrency"),
             ConflictKind::AlreadyExists => write!(f, "already_exists"),
+            ConflictKind::Deleted => write!(f, "deleted"),
         }
     }
 }
@@ -53,6 +61,16 @@ pub enum AppError {
         message: String,
     },
 
+    #[error("{limit} quota exceeded: {requested} over {allowed}")]
+    QuotaExceeded {
+        limit: QuotaLimit,
+        allowed: u64,
+        requested: u64,
+    },
+
+    #[error("forbidden: {message}")]
+    Forbidden { message: String },
+
     #[error("infrastructure error: {message}")]
     Infrastructure { message: String },
 }
@@ -75,7 +93,23 @@ impl From<DomainError> for AppError {
                 id: Some(vault_id),
             },
 
+            DomainError::VaultDeleted { vault_id } => AppError::Conflict {
+                kind: ConflictKind::Deleted,
+                resource: Resource::Vault,
+                id: Some(vault_id),
+            },
+
             DomainError::Validation { field, message } => AppError::Validation { field, message },
+
+            DomainError::QuotaExceeded {
+                limit,
+                allowed,
+                requested,
+            } => AppError::QuotaExceeded {
+                limit,
+                allowed,
+                requested,
+            },
         }
     }
 }
@@ -83,9 +117,15 @@ impl From<DomainError> for AppError {
 impl From<RepositoryError> for AppError {
     fn from(e: RepositoryError) -> Self {
         match e {
-            RepositoryError::VaultNotFound { owner } => AppError::NotFound {
+            RepositoryError::VaultNotFound { vault_id } => AppError::NotFound {
                 resource: Resource::Vault,
-                id: Some(owner),
+                id: Some(vault_id),
+            },
+
+            RepositoryError::AlreadyExists { vault_id } => AppError::Conflict {
+                kind: ConflictKind::AlreadyExists,
+                resource: Resource::Vault,
+                id: Some(vault_id),
             },
 
             RepositoryError::ConcurrencyConflict { vault_id } => AppError::Conflict {
@@ -94,7 +134,20 @@ impl From<RepositoryError> for AppError {
                 id: Some(vault_id),
             },
 
+            RepositoryError::AttachmentNotFound { attachment_id } => AppError::NotFound {
+                resource: Resource::Attachment,
+                id: Some(attachment_id),
+            },
+
             RepositoryError::Database { message } => AppError::Infrastructure { message },
         }
     }
 }
+
+impl From<BlobStoreError> for AppError {
+    fn from(e: BlobStoreError) -> Self {
+        match e {
+            BlobStoreError::Unavailable { message } => AppError::Infrastructure { message },
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Values the tests of this crate start from: one user, one instant and one
+//! valid package. Tests build their variants from these.
+
+use auth::domain::models::{Identity, User};
+use chrono::{DateTime, Utc};
+use domain::vault::{
+    CipherBlob, CryptoVersion, KdfParams, KdfSpec, KeySlot, KeySlotId, KeySlotKind, OwnerSub,
+    VaultHeader, VaultPackage,
+};
+use uuid::Uuid;
+
+pub(crate) fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_770_000_000, 0).unwrap()
+}
+
+/// `user1` of the ferrispass realm, on no plan.
+pub(crate) fn user() -> Identity {
+    user_with_roles(&[])
+}
+
+pub(crate) fn user_with_roles(roles: &[&str]) -> Identity {
+    Identity::User(User {
+        id: "user1".into(),
+        issuer: "http://localhost:8000/realms/ferrispass".into(),
+        username: "johndoe".into(),
+        email: None,
+        name: None,
+        roles: roles.iter().map(|r| r.to_string()).collect(),
+    })
+}
+
+/// Owner id of [`user`].
+pub(crate) fn owner() -> OwnerSub {
+    OwnerSub::new("http://localhost:8000/realms/ferrispass|user1").unwrap()
+}
+
+/// One master password slot, at the recommended Argon2id cost.
+pub(crate) fn header() -> VaultHeader {
+    VaultHeader {
+        crypto_version: CryptoVersion::V1,
+        key_slots: vec![KeySlot {
+            id: KeySlotId(Uuid::nil()),
+            kind: KeySlotKind::MasterPassword {
+                kdf: KdfSpec {
+                    salt: vec![1; 16],
+                    params: KdfParams::Argon2id {
+                        m_kib: 131_072,
+                        t: 3,
+                        p: 1,
+                    },
+                },
+            },
+            wrapped_vault_key: vec![2; 72],
+        }],
+    }
+}
+
+pub(crate) fn valid_package() -> VaultPackage {
+    VaultPackage {
+        header: header(),
+        blob: CipherBlob {
+            nonce: vec![3; 24],
+            aad: vec![],
+            ciphertext: vec![4; 32],
+        },
+        metadata: None,
+        attachments: vec![],
+        stored_ciphertext: None,
+    }
+}
+
+/// [`valid_package`] without its ciphertext, which is streamed after it.
+pub(crate) fn skeleton() -> VaultPackage {
+    let mut package = valid_package();
+    package.blob.ciphertext = vec![];
+    package
+}
@@ -1,2 +1,8 @@
+pub mod attachments;
 pub mod errors;
+#[cfg(test)]
+mod fixtures;
+pub mod ownership;
+pub mod quota;
+pub mod retention;
 pub mod usecases;
@@ -0,0 +1,108 @@
+use auth::domain::models::Identity;
+use domain::vault::OwnerSub;
+
+use crate::errors::AppError;
+
+/// Role a service account must hold to own a vault of its own.
+pub const DELEGATED_VAULT_ROLE: &str = "vault-delegate";
+
+/// Derives the vault owner from an authenticated identity.
+///
+/// The owner is never taken from the request: it is the identity's subject,
+/// namespaced by its issuer. Service accounts are refused unless they were
+/// explicitly granted [`DELEGATED_VAULT_ROLE`].
+pub fn owner_of(identity: &Identity) -> Result<OwnerSub, AppError> {
+    if identity.is_client() && !identity.has_role(DELEGATED_VAULT_ROLE) {
+        return Err(AppError::Forbidden {
+            message: format!(
+                "client {} is not delegated vault access",
+                identity.username()
+            ),
+        });
+    }
+
+    Ok(OwnerSub::namespaced(identity.issuer(), identity.id())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::domain::models::{Client, Identity, User};
+
+    use crate::{
+        errors::AppError,
+        ownership::{DELEGATED_VAULT_ROLE, owner_of},
+    };
+
+    const ISSUER: &str = "http://localhost:8000/realms/ferrispass";
+
+    fn client(roles: &[&str]) -> Identity {
+        Identity::Client(Client {
+            id: "service-123".into(),
+            issuer: ISSUER.into(),
+            client_id: "ferrispass-sync".into(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            scopes: vec![],
+        })
+    }
+
+    fn user(issuer: &str) -> Identity {
+        Identity::User(User {
+            id: "user1".into(),
+            issuer: issuer.into(),
+            username: "johndoe".into(),
+            email: None,
+            name: None,
+            roles: vec![],
+        })
+    }
+
+    #[test]
+    fn user_owner_is_namespaced_by_issuer() {
+        let owner = owner_of(&user(ISSUER)).unwrap();
+
+        assert_eq!(owner.0, format!("{ISSUER}|user1"));
+    }
+
+    #[test]
+    fn same_subject_in_another_realm_is_another_owner() {
+        let other = user("http://localhost:8000/realms/other");
+
+        assert_ne!(owner_of(&user(ISSUER)).unwrap(), owner_of(&other).unwrap());
+    }
+
+    #[test]
+    fn issuer_holding_the_separator_is_refused() {
+        // ("a|b", "c") would otherwise collide with ("a", "b|c").
+        let mut subject_with_bar = user("https://idp.example");
+        if let Identity::User(u) = &mut subject_with_bar {
+            u.id = "realms|user1".into();
+        }
+
+        assert!(matches!(
+            owner_of(&user("https://idp.example|realms")),
+            Err(AppError::Validation {
+                field: "owner_sub",
+                ..
+            })
+        ));
+        assert_eq!(
+            owner_of(&subject_with_bar).unwrap().0,
+            "https://idp.example|realms|user1"
+        );
+    }
+
+    #[test]
+    fn clients_without_delegation_are_forbidden() {
+        assert!(matches!(
+            owner_of(&client(&["service"])),
+            Err(AppError::Forbidden { .. })
+        ));
+    }
+
+    #[test]
+    fn delegated_clients_own_their_own_vault() {
+        let owner = owner_of(&client(&[DELEGATED_VAULT_ROLE])).unwrap();
+
+        assert_eq!(owner.0, format!("{ISSUER}|service-123"));
+    }
+}
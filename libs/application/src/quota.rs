@@ -0,0 +1,264 @@
+use auth::domain::models::Identity;
+use domain::{
+    quota::{Plan, Quota, QuotaPolicy},
+    vault::{OwnerSub, VaultPackage},
+};
+use ports::usage_repository::UsageRepository;
+
+use crate::errors::AppError;
+
+/// Enforces the [`QuotaPolicy`] on the writes that make an owner's storage
+/// grow.
+///
+/// The plan of an identity is the first configured plan among its roles.
+/// Restoring from the trash is never refused. Key slot changes and rewraps
+/// keep the ciphertext, but the revision they replace is still archived and
+/// so counted.
+#[derive(Debug, Clone)]
+pub struct Quotas<U> {
+    usage_repository: U,
+    policy: QuotaPolicy,
+}
+
+/// Sizes of a package, as [`Quotas::check_package`] holds them to the quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PackageSize {
+    /// Everything the package holds, a streamed ciphertext included: what
+    /// the package size limit applies to.
+    pub full: u64,
+    /// What the write adds to the owner's total. A streamed ciphertext is
+    /// part of the package but not inline: it was counted when it was
+    /// stored.
+    pub inline: u64,
+}
+
+impl PackageSize {
+    pub(crate) fn of(package: &VaultPackage) -> Self {
+        Self {
+            full: package.full_byte_size(),
+            inline: package.byte_size(),
+        }
+    }
+}
+
+/// What a write does to the owner's vault count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Growth {
+    /// One more vault.
+    NewVault,
+    /// An existing vault gets a new revision.
+    Update,
+}
+
+impl<U> Quotas<U>
+where
+    U: UsageRepository,
+{
+    pub fn new(usage_repository: U, policy: QuotaPolicy) -> Self {
+        Self {
+            usage_repository,
+            policy,
+        }
+    }
+
+    pub fn usage_repository(&self) -> &U {
+        &self.usage_repository
+    }
+
+    pub fn plan_of(&self, identity: &Identity) -> Option<&Plan> {
+        self.policy
+            .plan_for(identity.roles().iter().map(String::as_str))
+    }
+
+    pub fn quota_of(&self, identity: &Identity) -> &Quota {
+        self.policy
+            .quota_for(identity.roles().iter().map(String::as_str))
+    }
+
+    /// Checks the full `size` of a package against the package size limit,
+    /// then the owner's total once its inline bytes are stored, and their
+    /// vault count after `growth`.
+    ///
+    /// The package is counted in full even when it replaces another: the
+    /// replaced one moves to history and keeps counting until pruned.
+    pub(crate) async fn check_package(
+        &self,
+        identity: &Identity,
+        owner_id: &OwnerSub,
+        size: PackageSize,
+        growth: Growth,
+    ) -> Result<(), AppError> {
+        let quota = self.quota_of(identity);
+        quota.check_package(size.full)?;
+
+        let added_vaults = match growth {
+            Growth::NewVault => 1,
+            Growth::Update => 0,
+        };
+        self.check_growth(quota, owner_id, size.inline, added_vaults)
+            .await
+    }
+
+    /// Checks the owner's total once `bytes` more are stored.
+    pub(crate) async fn check_bytes(
+        &self,
+        identity: &Identity,
+        owner_id: &OwnerSub,
+        bytes: u64,
+    ) -> Result<(), AppError> {
+        self.check_growth(self.quota_of(identity), owner_id, bytes, 0)
+            .await
+    }
+
+    async fn check_growth(
+        &self,
+        quota: &Quota,
+        owner_id: &OwnerSub,
+        added_bytes: u64,
+        added_vaults: u64,
+    ) -> Result<(), AppError> {
+        if !quota.needs_usage() {
+            return Ok(());
+        }
+
+        let usage = self.usage_repository.usage(owner_id).await?;
+        quota.check_growth(&usage, added_bytes, added_vaults)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::quota::{Plan, Quota, QuotaLimit, QuotaPolicy, StorageUsage};
+    use ports::usage_repository::MockUsageRepository;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{owner, user, user_with_roles},
+        quota::{Growth, PackageSize, Quotas},
+    };
+
+    fn policy() -> QuotaPolicy {
+        QuotaPolicy::new(
+            Quota {
+                max_package_bytes: Some(100),
+                max_total_bytes: Some(1000),
+                max_vaults: Some(1),
+            },
+            [Plan {
+                name: "gold".into(),
+                quota: Quota {
+                    max_package_bytes: Some(1000),
+                    max_total_bytes: None,
+                    max_vaults: None,
+                },
+            }],
+        )
+        .unwrap()
+    }
+
+    fn usage() -> StorageUsage {
+        StorageUsage {
+            vaults: 1,
+            vault_bytes: 900,
+            history_bytes: 0,
+            attachment_bytes: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_package_is_refused_before_reading_usage() {
+        let quotas = Quotas::new(MockUsageRepository::new(), policy());
+
+        let result = quotas
+            .check_package(
+                &user(),
+                &owner(),
+                PackageSize {
+                    full: 101,
+                    inline: 101,
+                },
+                Growth::Update,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::PackageBytes,
+                allowed: 100,
+                requested: 101,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn new_vault_over_the_count_is_refused() {
+        let mut repo = MockUsageRepository::new();
+        repo.expect_usage()
+            .returning(|_| Box::pin(async { Ok(usage()) }));
+        let quotas = Quotas::new(repo, policy());
+
+        let result = quotas
+            .check_package(
+                &user(),
+                &owner(),
+                PackageSize {
+                    full: 10,
+                    inline: 10,
+                },
+                Growth::NewVault,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::Vaults,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn growth_over_the_total_is_refused() {
+        let mut repo = MockUsageRepository::new();
+        repo.expect_usage()
+            .returning(|_| Box::pin(async { Ok(usage()) }));
+        let quotas = Quotas::new(repo, policy());
+
+        assert!(quotas.check_bytes(&user(), &owner(), 100).await.is_ok());
+        assert!(matches!(
+            quotas.check_bytes(&user(), &owner(), 101).await,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::TotalBytes,
+                allowed: 1000,
+                requested: 1001,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn plan_from_roles_replaces_the_default() {
+        // No expectation: an unlimited total must not read usage.
+        let quotas = Quotas::new(MockUsageRepository::new(), policy());
+        let gold = user_with_roles(&["gold"]);
+
+        assert_eq!(quotas.plan_of(&gold).map(|p| p.name.as_str()), Some("gold"));
+        assert!(
+            quotas
+                .check_package(
+                    &gold,
+                    &owner(),
+                    PackageSize {
+                        full: 500,
+                        inline: 500,
+                    },
+                    Growth::NewVault,
+                )
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use domain::vault::{RetentionPolicy, VaultId};
+use ports::vault_repository::VaultRepository;
+use tracing::warn;
+
+/// Drops the archived revisions of `vault_id` that `policy` no longer keeps.
+///
+/// Runs after a write has been committed, so a failure here must not fail the
+/// request: it is logged and the next write retries.
+pub(crate) async fn prune_history<R: VaultRepository>(
+    vault_repository: &R,
+    policy: &RetentionPolicy,
+    vault_id: &VaultId,
+    now: DateTime<Utc>,
+) {
+    if policy.is_unbounded() {
+        return;
+    }
+
+    let result = async {
+        let history = vault_repository.list_revisions(vault_id).await?;
+        let expired = policy.expired(&history, now);
+
+        if expired.is_empty() {
+            return Ok(());
+        }
+        vault_repository.delete_revisions(vault_id, &expired).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        warn!("failed to prune history of vault {}: {}", vault_id.0, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::vault::{Etag, RetentionPolicy, Revision, RevisionSummary, VaultId};
+    use ports::{RepositoryError, vault_repository::MockVaultRepository};
+    use uuid::Uuid;
+
+    use crate::{fixtures::now, retention::prune_history};
+
+    fn summary(revision: u64) -> RevisionSummary {
+        RevisionSummary {
+            revision: Revision(revision),
+            etag: Etag::new(format!("etag-{revision}")).unwrap(),
+            saved_at: now() - Duration::hours(2),
+            archived_at: now() - Duration::hours(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn unbounded_policy_touches_nothing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_list_revisions().never();
+        repo.expect_delete_revisions().never();
+
+        prune_history(
+            &repo,
+            &RetentionPolicy::keep_all(),
+            &VaultId(Uuid::new_v4()),
+            now(),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn deletes_revisions_beyond_the_limit() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_list_revisions()
+            .returning(|_| Box::pin(async { Ok(vec![summary(2), summary(1), summary(0)]) }));
+        repo.expect_delete_revisions()
+            .withf(|_, revisions| revisions == [Revision(0)])
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(()) }));
+
+        let policy = RetentionPolicy {
+            max_revisions: Some(2),
+            max_age: None,
+        };
+        prune_history(&repo, &policy, &VaultId(Uuid::new_v4()), now()).await;
+    }
+
+    #[tokio::test]
+    async fn storage_failures_are_swallowed() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_list_revisions().returning(|_| {
+            Box::pin(async {
+                Err(RepositoryError::Database {
+                    message: "down".into(),
+                })
+            })
+        });
+        repo.expect_delete_revisions().never();
+
+        let policy = RetentionPolicy {
+            max_revisions: Some(1),
+            max_age: None,
+        };
+        prune_history(&repo, &policy, &VaultId(Uuid::new_v4()), now()).await;
+    }
+}
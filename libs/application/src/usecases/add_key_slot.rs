@@ -0,0 +1,317 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KdfPolicy, KeySlot, KeySlotId, KeySlotKind, RetentionPolicy, VaultId};
+use ports::{
+    clock::Clock, etag::EtagGenerator, id::IdGenerator, usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+    quota::Quotas,
+    retention::prune_history,
+};
+
+/// A slot as sent by the client; the server assigns its id.
+#[derive(Debug, Clone)]
+pub struct NewKeySlot {
+    pub kind: KeySlotKind,
+    pub wrapped_vault_key: Vec<u8>,
+}
+
+/// Adds an unlock method to a vault: one more copy of the vault key, wrapped
+/// by the client under the key of that method. The ciphertext is kept.
+pub struct AddKeySlot<R, E, C, I, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    etag_generator: E,
+    clock: C,
+    id_generator: I,
+    retention: RetentionPolicy,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, E, C, I, U> AddKeySlot<R, E, C, I, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        etag_generator: E,
+        clock: C,
+        id_generator: I,
+        retention: RetentionPolicy,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            etag_generator,
+            clock,
+            id_generator,
+            retention,
+            kdf_policy,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        expected_etag: Etag,
+        new_slot: NewKeySlot,
+    ) -> Result<(KeySlotId, Etag, u64), AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let slot = KeySlot {
+            id: self.id_generator.key_slot_id(),
+            kind: new_slot.kind,
+            wrapped_vault_key: new_slot.wrapped_vault_key,
+        };
+        // Only the new slot is held to the policy: older master-password
+        // slots below a raised minimum stay usable until they are rewrapped.
+        if let Some(kdf) = slot.kdf() {
+            self.kdf_policy.check(kdf)?;
+        }
+
+        let existing = self
+            .vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let slot_id = slot.id;
+        let package = existing
+            .package
+            .with_header(existing.package.header.with_slot(slot))?;
+        let new_etag = self
+            .etag_generator
+            .generate(existing.revision.next(), &package);
+
+        // The ciphertext is unchanged, but the revision it replaces is
+        // archived: the total grows by a whole package.
+        let package_bytes = package.byte_size();
+        let now = self.clock.now();
+        let updated = existing.update(&expected_etag, now, new_etag, package)?;
+
+        self.quotas
+            .check_bytes(identity, &owner_id, package_bytes)
+            .await?;
+
+        self.vault_repository
+            .update_if_match(&updated, &expected_etag)
+            .await?;
+        prune_history(&self.vault_repository, &self.retention, &updated.id, now).await;
+
+        Ok((slot_id, updated.etag, updated.revision.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::{
+        quota::QuotaPolicy,
+        vault::{
+            Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotId, KeySlotKind, RetentionPolicy, Vault,
+            VaultId,
+        },
+    };
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{
+        clock::FixedClock,
+        etag::MockEtagGenerator,
+        id::MockIdGenerator,
+        usage_repository::MockUsageRepository,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, owner, user, valid_package},
+        quota::Quotas,
+        usecases::add_key_slot::{AddKeySlot, NewKeySlot},
+    };
+
+    fn existing_vault() -> Vault {
+        let mut package = valid_package();
+        package.header.key_slots[0].id = KeySlotId(Uuid::from_u128(1));
+
+        Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            now() - Duration::days(1),
+            Etag::new("etag-1").unwrap(),
+            package,
+        )
+        .unwrap()
+    }
+
+    fn recovery_slot() -> NewKeySlot {
+        NewKeySlot {
+            kind: KeySlotKind::RecoveryKey { salt: vec![5; 16] },
+            wrapped_vault_key: vec![6; 72],
+        }
+    }
+
+    fn usecase<R: VaultRepository>(
+        repo: R,
+        etag_gen: MockEtagGenerator,
+    ) -> AddKeySlot<R, MockEtagGenerator, FixedClock, MockIdGenerator, MockUsageRepository> {
+        let mut id_gen = MockIdGenerator::new();
+        id_gen
+            .expect_key_slot_id()
+            .returning(|| KeySlotId(Uuid::from_u128(2)));
+
+        AddKeySlot::new(
+            repo,
+            etag_gen,
+            FixedClock(now()),
+            id_gen,
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited()),
+        )
+    }
+
+    #[tokio::test]
+    async fn appends_slot_and_keeps_ciphertext() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        repo.create(&vault).await.unwrap();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let (slot_id, etag, revision) = usecase(repo.clone(), etag_gen)
+            .execute(&user(), &vault.id, vault.etag.clone(), recovery_slot())
+            .await
+            .unwrap();
+
+        assert_eq!(slot_id, KeySlotId(Uuid::from_u128(2)));
+        assert_eq!(etag.0, "etag-2");
+        assert_eq!(revision, 1);
+
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let slots = &stored.package.header.key_slots;
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0], vault.package.header.key_slots[0]);
+        assert_eq!(slots[1].kind, recovery_slot().kind);
+        assert_eq!(stored.package.blob, vault.package.blob);
+    }
+
+    #[tokio::test]
+    async fn rejects_wrapped_key_of_wrong_length() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let mut slot = recovery_slot();
+        slot.wrapped_vault_key = vec![6; 16];
+
+        let result = usecase(repo, etag_gen)
+            .execute(&user(), &vault_id, Etag::new("etag-1").unwrap(), slot)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "key_slots.wrapped_vault_key",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_kdf_below_policy_minimum() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find().never();
+
+        let slot = NewKeySlot {
+            kind: KeySlotKind::MasterPassword {
+                kdf: KdfSpec {
+                    salt: vec![1; 16],
+                    params: KdfParams::Pbkdf2HmacSha256 { iterations: 1_000 },
+                },
+            },
+            wrapped_vault_key: vec![6; 72],
+        };
+
+        let result = usecase(repo, MockEtagGenerator::new())
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                slot,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.iterations",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_conflict_if_etag_mismatch() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let result = usecase(repo, etag_gen)
+            .execute(
+                &user(),
+                &vault_id,
+                Etag::new("stale").unwrap(),
+                recovery_slot(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+
+use chrono::Duration;
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::Clock,
+    vault_repository::VaultRepository,
+};
+use tracing::info;
+
+use crate::errors::AppError;
+
+/// Deletes attachments that no vault, trashed vault or kept revision
+/// references any more.
+///
+/// Only attachments idle for longer than the grace period are considered:
+/// an upload is not referenced until the client saves the vault that points
+/// at it, which it does once the upload is complete. Like [`PurgeDeletedVaults`](super::purge_deleted_vaults::PurgeDeletedVaults),
+/// it runs in the background and needs no identity.
+pub struct CollectOrphanedAttachments<R, T, B, C>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+{
+    vault_repository: R,
+    attachment_repository: T,
+    blob_store: B,
+    clock: C,
+    grace_period: Duration,
+}
+
+impl<R, T, B, C> CollectOrphanedAttachments<R, T, B, C>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+{
+    pub fn new(
+        vault_repository: R,
+        attachment_repository: T,
+        blob_store: B,
+        clock: C,
+        grace_period: Duration,
+    ) -> Self {
+        Self {
+            vault_repository,
+            attachment_repository,
+            blob_store,
+            clock,
+            grace_period,
+        }
+    }
+
+    /// Returns the number of attachments deleted.
+    pub async fn execute(&self) -> Result<u64, AppError> {
+        let idle_before = self.clock.now() - self.grace_period;
+
+        // The references are read without holding off writers, so a save
+        // committed after they were read is missed. What keeps a fresh upload
+        // safe is the grace period, counted from its completion: the save
+        // that references it comes right after. Only a package that takes
+        // back an attachment left unreferenced for the whole grace period
+        // can lose it.
+        let candidates = self
+            .attachment_repository
+            .list_idle_before(idle_before)
+            .await?;
+        let referenced: HashSet<_> = self.vault_repository.referenced_attachments().await?;
+
+        let orphans: Vec<_> = candidates
+            .into_iter()
+            .filter(|id| !referenced.contains(id))
+            .collect();
+
+        // Chunks go first, so a failure leaves a record to retry from rather
+        // than chunks nothing points at.
+        for id in &orphans {
+            self.blob_store.delete(id).await?;
+        }
+        self.attachment_repository.delete(&orphans).await?;
+
+        if !orphans.is_empty() {
+            info!("collected {} orphaned attachments", orphans.len());
+        }
+
+        Ok(orphans.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::Duration;
+    use domain::{
+        attachment::{Attachment, AttachmentId},
+        vault::OwnerSub,
+    };
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryBlobStore};
+    use ports::{
+        attachment_repository::{AttachmentRepository, MockAttachmentRepository},
+        blob_store::{BlobStore, MockBlobStore},
+        clock::FixedClock,
+        vault_repository::MockVaultRepository,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        fixtures::now, usecases::collect_orphaned_attachments::CollectOrphanedAttachments,
+    };
+
+    fn id(n: u128) -> AttachmentId {
+        AttachmentId(Uuid::from_u128(n))
+    }
+
+    #[tokio::test]
+    async fn deletes_old_attachments_nothing_references() {
+        let mut attachments = MockAttachmentRepository::new();
+        attachments
+            .expect_list_idle_before()
+            .withf(|before| *before == now() - Duration::days(1))
+            .returning(|_| Box::pin(async { Ok(vec![id(1), id(2), id(3)]) }));
+        attachments
+            .expect_delete()
+            .withf(|ids| ids == [id(1), id(3)])
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        let mut vaults = MockVaultRepository::new();
+        vaults
+            .expect_referenced_attachments()
+            .returning(|| Box::pin(async { Ok(HashSet::from([id(2), id(9)])) }));
+
+        let mut blobs = MockBlobStore::new();
+        blobs
+            .expect_delete()
+            .withf(|deleted| *deleted == id(1) || *deleted == id(3))
+            .times(2)
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        let collected = CollectOrphanedAttachments::new(
+            vaults,
+            attachments,
+            blobs,
+            FixedClock(now()),
+            Duration::days(1),
+        )
+        .execute()
+        .await
+        .unwrap();
+
+        assert_eq!(collected, 2);
+    }
+
+    /// The upload started before the grace period but only completed within
+    /// it; the save that references it lands after the references were read.
+    #[tokio::test]
+    async fn upload_completed_within_the_grace_period_survives_a_racing_save() {
+        let attachments = InMemoryAttachmentRepository::new();
+        let blobs = InMemoryBlobStore::new();
+        let owner = OwnerSub::new("user1").unwrap();
+
+        let slow = Attachment::new(id(1), owner.clone(), 8, 8, now() - Duration::days(2)).unwrap();
+        attachments.create(&slow).await.unwrap();
+        blobs.put_chunk(&slow.id, 0, vec![1; 8]).await.unwrap();
+        let slow = slow.complete(&[0], now() - Duration::hours(1)).unwrap();
+        attachments.mark_completed(&slow).await.unwrap();
+
+        let abandoned =
+            Attachment::new(id(2), owner.clone(), 8, 8, now() - Duration::days(2)).unwrap();
+        attachments.create(&abandoned).await.unwrap();
+
+        // Nothing references the upload yet when the collector reads.
+        let mut vaults = MockVaultRepository::new();
+        vaults
+            .expect_referenced_attachments()
+            .returning(|| Box::pin(async { Ok(HashSet::new()) }));
+
+        let collected = CollectOrphanedAttachments::new(
+            vaults,
+            attachments.clone(),
+            blobs.clone(),
+            FixedClock(now()),
+            Duration::days(1),
+        )
+        .execute()
+        .await
+        .unwrap();
+
+        assert_eq!(collected, 1);
+        assert!(
+            attachments
+                .find(&owner, &abandoned.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            attachments.find(&owner, &slow.id).await.unwrap(),
+            Some(slow.clone())
+        );
+        assert_eq!(
+            blobs.get_chunk(&slow.id, 0).await.unwrap(),
+            Some(vec![1; 8])
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use auth::domain::models::Identity;
+use domain::attachment::{Attachment, AttachmentId};
+use ports::{attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::Clock};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// Seals an upload once every chunk is stored; only complete attachments
+/// can be downloaded.
+///
+/// Completing twice returns the attachment unchanged, so a client that lost
+/// the first response can simply retry.
+pub struct CompleteAttachmentUpload<T, B, C>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+{
+    attachment_repository: T,
+    blob_store: B,
+    clock: C,
+}
+
+impl<T, B, C> CompleteAttachmentUpload<T, B, C>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+{
+    pub fn new(attachment_repository: T, blob_store: B, clock: C) -> Self {
+        Self {
+            attachment_repository,
+            blob_store,
+            clock,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        attachment_id: &AttachmentId,
+    ) -> Result<Attachment, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let attachment = self
+            .attachment_repository
+            .find(&owner_id, attachment_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Attachment,
+                id: Some(attachment_id.0.to_string()),
+            })?;
+
+        if attachment.is_complete() {
+            return Ok(attachment);
+        }
+
+        let received = self.blob_store.list_chunks(attachment_id).await?;
+        let completed = attachment.complete(&received, self.clock.now())?;
+        self.attachment_repository
+            .mark_completed(&completed)
+            .await?;
+
+        Ok(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::attachment::{Attachment, AttachmentId};
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryBlobStore};
+    use ports::{
+        attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::FixedClock,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, user},
+        ownership::owner_of,
+        usecases::complete_attachment_upload::CompleteAttachmentUpload,
+    };
+
+    async fn uploading(
+        chunks: &[u32],
+    ) -> (
+        InMemoryAttachmentRepository,
+        InMemoryBlobStore,
+        AttachmentId,
+    ) {
+        let id = AttachmentId(Uuid::from_u128(7));
+        let repo = InMemoryAttachmentRepository::new();
+        repo.create(&Attachment::new(id, owner_of(&user()).unwrap(), 2500, 1024, now()).unwrap())
+            .await
+            .unwrap();
+
+        let blobs = InMemoryBlobStore::new();
+        for &index in chunks {
+            blobs.put_chunk(&id, index, vec![0; 1]).await.unwrap();
+        }
+
+        (repo, blobs, id)
+    }
+
+    #[tokio::test]
+    async fn refuses_while_chunks_are_missing() {
+        let (repo, blobs, id) = uploading(&[0, 2]).await;
+
+        let err = CompleteAttachmentUpload::new(repo.clone(), blobs, FixedClock(now()))
+            .execute(&user(), &id)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::Validation {
+                field: "chunks",
+                ..
+            }
+        ));
+        let stored = repo.find(&owner_of(&user()).unwrap(), &id).await.unwrap();
+        assert!(!stored.unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn completes_once_and_then_is_idempotent() {
+        let (repo, blobs, id) = uploading(&[0, 1, 2]).await;
+        let later = now() + Duration::minutes(5);
+
+        let completed =
+            CompleteAttachmentUpload::new(repo.clone(), blobs.clone(), FixedClock(later))
+                .execute(&user(), &id)
+                .await
+                .unwrap();
+        let again =
+            CompleteAttachmentUpload::new(repo, blobs, FixedClock(later + Duration::minutes(1)))
+                .execute(&user(), &id)
+                .await
+                .unwrap();
+
+        assert_eq!(completed.completed_at, Some(later));
+        assert_eq!(again, completed);
+    }
+}
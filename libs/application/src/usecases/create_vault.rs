@@ -0,0 +1,337 @@
+use auth::domain::models::Identity;
+use domain::vault::{KdfPolicy, Revision, Vault, VaultPackage};
+use ports::{
+    attachment_repository::AttachmentRepository, clock::Clock, etag::EtagGenerator,
+    id::IdGenerator, usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+
+use crate::{
+    attachments::check_references,
+    errors::AppError,
+    ownership::owner_of,
+    quota::{Growth, PackageSize, Quotas},
+};
+
+pub struct CreateVault<R, T, E, C, I, U>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    E: EtagGenerator,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    attachment_repository: T,
+    etag_generator: E,
+    clock: C,
+    id_generator: I,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, T, E, C, I, U> CreateVault<R, T, E, C, I, U>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    E: EtagGenerator,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        attachment_repository: T,
+        etag_generator: E,
+        clock: C,
+        id_generator: I,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            attachment_repository,
+            etag_generator,
+            clock,
+            id_generator,
+            kdf_policy,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        package: VaultPackage,
+    ) -> Result<Vault, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        package.validate()?;
+        self.kdf_policy.check_header(&package.header)?;
+        check_references(&self.attachment_repository, &owner_id, &package).await?;
+        self.quotas
+            .check_package(
+                identity,
+                &owner_id,
+                PackageSize::of(&package),
+                Growth::NewVault,
+            )
+            .await?;
+
+        let etag = self.etag_generator.generate(Revision::INITIAL, &package);
+        let vault = Vault::new(
+            self.id_generator.vault_id(),
+            owner_id,
+            self.clock.now(),
+            etag,
+            package,
+        )?;
+
+        // Ids are generated, so a clash is practically impossible; the
+        // repository still reports one as AlreadyExists.
+        self.vault_repository.create(&vault).await?;
+
+        Ok(vault)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::AttachmentId,
+        quota::{Quota, QuotaLimit, QuotaPolicy, StorageUsage},
+        vault::{Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, Revision},
+    };
+
+    use ports::{
+        RepositoryError, attachment_repository::MockAttachmentRepository, clock::FixedClock,
+        etag::MockEtagGenerator, id::UuidV7Generator, usage_repository::MockUsageRepository,
+        vault_repository::MockVaultRepository,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::{AppError, ConflictKind},
+        fixtures::{now, user, valid_package},
+        quota::Quotas,
+        usecases::create_vault::CreateVault,
+    };
+
+    fn clock() -> FixedClock {
+        FixedClock(now())
+    }
+
+    fn unlimited() -> Quotas<MockUsageRepository> {
+        Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited())
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_package_before_touching_storage() {
+        let repo = MockVaultRepository::new();
+        let etag_gen = MockEtagGenerator::new();
+
+        let mut package = valid_package();
+        package.blob.nonce = vec![3; 4];
+
+        let usecase = CreateVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let result = usecase.execute(&user(), package).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "blob.nonce",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_kdf_below_policy_minimum() {
+        let repo = MockVaultRepository::new();
+        let etag_gen = MockEtagGenerator::new();
+
+        let mut package = valid_package();
+        package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 16 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+
+        let usecase = CreateVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let result = usecase.execute(&user(), package).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_attachment_before_storing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_create().never();
+        let mut attachments = MockAttachmentRepository::new();
+        attachments
+            .expect_find()
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+
+        let usecase = CreateVault::new(
+            repo,
+            attachments,
+            MockEtagGenerator::new(),
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let mut package = valid_package();
+        package.attachments = vec![AttachmentId(Uuid::new_v4())];
+
+        let result = usecase.execute(&user(), package).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "attachments",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_vault_over_the_quota_before_storing() {
+        let mut usage = MockUsageRepository::new();
+        usage.expect_usage().returning(|_| {
+            Box::pin(async {
+                Ok(StorageUsage {
+                    vaults: 3,
+                    ..StorageUsage::default()
+                })
+            })
+        });
+        let quota = Quota {
+            max_vaults: Some(3),
+            ..Quota::unlimited()
+        };
+
+        let usecase = CreateVault::new(
+            MockVaultRepository::new(),
+            MockAttachmentRepository::new(),
+            MockEtagGenerator::new(),
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            Quotas::new(usage, QuotaPolicy::new(quota, []).unwrap()),
+        );
+
+        let result = usecase.execute(&user(), valid_package()).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::Vaults,
+                allowed: 3,
+                requested: 4,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_already_exists_if_id_is_taken() {
+        let mut repo = MockVaultRepository::new();
+        let mut etag_gen = MockEtagGenerator::new();
+
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-1").unwrap());
+
+        repo.expect_create().returning(|v| {
+            let vault_id = v.id.0.to_string();
+            Box::pin(async move { Err(RepositoryError::AlreadyExists { vault_id }) })
+        });
+
+        let usecase = CreateVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let result = usecase.execute(&user(), valid_package()).await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Conflict {
+                kind: ConflictKind::AlreadyExists,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn creates_vault_at_initial_revision() {
+        let mut repo = MockVaultRepository::new();
+        let mut etag_gen = MockEtagGenerator::new();
+
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-1").unwrap());
+
+        repo.expect_create()
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        let usecase = CreateVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            UuidV7Generator,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let vault = usecase.execute(&user(), valid_package()).await.unwrap();
+
+        assert_eq!(
+            vault.owner_id.0,
+            "http://localhost:8000/realms/ferrispass|user1"
+        );
+        assert_eq!(vault.etag.0, "etag-1");
+        assert_eq!(vault.revision, Revision::INITIAL);
+        assert_eq!(vault.created_at, now());
+        assert_eq!(vault.updated_at, now());
+        assert_eq!(vault.id.0.get_version_num(), 7);
+    }
+}
@@ -0,0 +1,149 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, VaultId};
+use ports::{clock::Clock, vault_repository::VaultRepository};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// Moves the caller's vault to the trash. It stays restorable with
+/// `RestoreDeletedVault` until the purge job removes it for good.
+pub struct DeleteVault<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    vault_repository: R,
+    clock: C,
+}
+
+impl<R, C> DeleteVault<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    pub fn new(vault_repository: R, clock: C) -> Self {
+        Self {
+            vault_repository,
+            clock,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        expected_etag: Etag,
+    ) -> Result<(), AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let existing = self
+            .vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let deleted = existing.delete(&expected_etag, self.clock.now())?;
+
+        self.vault_repository
+            .mark_deleted(&deleted, &expected_etag)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::vault::{Etag, Vault, VaultId};
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{
+        clock::FixedClock,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, owner, user, valid_package},
+        usecases::delete_vault::DeleteVault,
+    };
+
+    async fn stored_vault(repo: &InMemoryVaultRepository) -> Vault {
+        let vault = Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            now(),
+            Etag::new("etag-1").unwrap(),
+            valid_package(),
+        )
+        .unwrap();
+        repo.create(&vault).await.unwrap();
+
+        vault
+    }
+
+    #[tokio::test]
+    async fn moves_vault_to_trash() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = stored_vault(&repo).await;
+
+        DeleteVault::new(repo.clone(), FixedClock(now()))
+            .execute(&user(), &vault.id, vault.etag.clone())
+            .await
+            .unwrap();
+
+        assert!(
+            repo.find(&vault.owner_id, &vault.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let trashed = repo
+            .find_deleted(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(trashed.deleted_at, Some(now()));
+    }
+
+    #[tokio::test]
+    async fn returns_conflict_if_etag_mismatch() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = stored_vault(&repo).await;
+
+        let result = DeleteVault::new(repo.clone(), FixedClock(now()))
+            .execute(&user(), &vault.id, Etag::new("stale").unwrap())
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+        assert!(
+            repo.find(&vault.owner_id, &vault.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_if_vault_missing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+        repo.expect_mark_deleted().never();
+
+        let result = DeleteVault::new(repo, FixedClock(now()))
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+}
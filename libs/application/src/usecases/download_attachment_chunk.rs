@@ -0,0 +1,146 @@
+use auth::domain::models::Identity;
+use domain::attachment::AttachmentId;
+use ports::{attachment_repository::AttachmentRepository, blob_store::BlobStore};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// Returns one encrypted chunk of a complete attachment.
+pub struct DownloadAttachmentChunk<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    attachment_repository: T,
+    blob_store: B,
+}
+
+impl<T, B> DownloadAttachmentChunk<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    pub fn new(attachment_repository: T, blob_store: B) -> Self {
+        Self {
+            attachment_repository,
+            blob_store,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        attachment_id: &AttachmentId,
+        index: u32,
+    ) -> Result<Vec<u8>, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let attachment = self
+            .attachment_repository
+            .find(&owner_id, attachment_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Attachment,
+                id: Some(attachment_id.0.to_string()),
+            })?;
+
+        if !attachment.is_complete() {
+            return Err(AppError::Validation {
+                field: "attachment",
+                message: "upload is not complete".into(),
+            });
+        }
+        attachment.chunk_len(index)?;
+
+        // Completion checked every chunk was stored: a gap now means the
+        // blob store lost data.
+        self.blob_store
+            .get_chunk(attachment_id, index)
+            .await?
+            .ok_or_else(|| AppError::Infrastructure {
+                message: format!("chunk {index} of attachment {} is missing", attachment_id.0),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::attachment::{Attachment, AttachmentId};
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryBlobStore};
+    use ports::{attachment_repository::AttachmentRepository, blob_store::BlobStore};
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, user},
+        ownership::owner_of,
+        usecases::download_attachment_chunk::DownloadAttachmentChunk,
+    };
+
+    async fn stored(
+        complete: bool,
+    ) -> (
+        InMemoryAttachmentRepository,
+        InMemoryBlobStore,
+        AttachmentId,
+    ) {
+        let id = AttachmentId(Uuid::from_u128(7));
+        let mut attachment =
+            Attachment::new(id, owner_of(&user()).unwrap(), 1500, 1024, now()).unwrap();
+        if complete {
+            attachment = attachment.complete(&[0, 1], now()).unwrap();
+        }
+
+        let repo = InMemoryAttachmentRepository::new();
+        repo.create(&attachment).await.unwrap();
+        let blobs = InMemoryBlobStore::new();
+        blobs.put_chunk(&id, 0, vec![1; 1024]).await.unwrap();
+        blobs.put_chunk(&id, 1, vec![2; 476]).await.unwrap();
+
+        (repo, blobs, id)
+    }
+
+    #[tokio::test]
+    async fn returns_a_chunk_of_a_complete_attachment() {
+        let (repo, blobs, id) = stored(true).await;
+
+        let chunk = DownloadAttachmentChunk::new(repo, blobs)
+            .execute(&user(), &id, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(chunk, vec![2; 476]);
+    }
+
+    #[tokio::test]
+    async fn refuses_uploads_in_progress() {
+        let (repo, blobs, id) = stored(false).await;
+
+        let err = DownloadAttachmentChunk::new(repo, blobs)
+            .execute(&user(), &id, 0)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::Validation {
+                field: "attachment",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_index_past_the_end() {
+        let (repo, blobs, id) = stored(true).await;
+
+        let err = DownloadAttachmentChunk::new(repo, blobs)
+            .execute(&user(), &id, 2)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Validation { field: "index", .. }));
+    }
+}
@@ -0,0 +1,124 @@
+use auth::domain::models::Identity;
+use domain::attachment::{Attachment, AttachmentId};
+use ports::{attachment_repository::AttachmentRepository, blob_store::BlobStore};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// An attachment and, while it is being uploaded, the chunks still to send.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentStatus {
+    pub attachment: Attachment,
+    pub missing_chunks: Vec<u32>,
+}
+
+/// Reports where an upload stands, so that a client can resume it.
+pub struct GetAttachment<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    attachment_repository: T,
+    blob_store: B,
+}
+
+impl<T, B> GetAttachment<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    pub fn new(attachment_repository: T, blob_store: B) -> Self {
+        Self {
+            attachment_repository,
+            blob_store,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        attachment_id: &AttachmentId,
+    ) -> Result<AttachmentStatus, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let attachment = self
+            .attachment_repository
+            .find(&owner_id, attachment_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Attachment,
+                id: Some(attachment_id.0.to_string()),
+            })?;
+
+        let missing_chunks = if attachment.is_complete() {
+            vec![]
+        } else {
+            let received = self.blob_store.list_chunks(attachment_id).await?;
+            attachment.missing_chunks(&received)
+        };
+
+        Ok(AttachmentStatus {
+            attachment,
+            missing_chunks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::attachment::{Attachment, AttachmentId};
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryBlobStore};
+    use ports::{
+        attachment_repository::AttachmentRepository,
+        blob_store::{BlobStore, MockBlobStore},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        fixtures::{now, user},
+        ownership::owner_of,
+        usecases::get_attachment::GetAttachment,
+    };
+
+    fn attachment() -> Attachment {
+        let id = AttachmentId(Uuid::from_u128(7));
+        Attachment::new(id, owner_of(&user()).unwrap(), 2500, 1024, now()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn lists_the_chunks_still_missing() {
+        let repo = InMemoryAttachmentRepository::new();
+        repo.create(&attachment()).await.unwrap();
+        let blobs = InMemoryBlobStore::new();
+        blobs
+            .put_chunk(&attachment().id, 1, vec![0; 1024])
+            .await
+            .unwrap();
+
+        let status = GetAttachment::new(repo, blobs)
+            .execute(&user(), &attachment().id)
+            .await
+            .unwrap();
+
+        assert_eq!(status.missing_chunks, [0, 2]);
+    }
+
+    #[tokio::test]
+    async fn complete_attachments_skip_the_blob_store() {
+        let repo = InMemoryAttachmentRepository::new();
+        let complete = attachment().complete(&[0, 1, 2], now()).unwrap();
+        repo.create(&complete).await.unwrap();
+        let mut blobs = MockBlobStore::new();
+        blobs.expect_list_chunks().never();
+
+        let status = GetAttachment::new(repo, blobs)
+            .execute(&user(), &complete.id)
+            .await
+            .unwrap();
+
+        assert!(status.attachment.is_complete());
+        assert!(status.missing_chunks.is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use auth::domain::models::Identity;
+use domain::quota::{Quota, StorageUsage};
+use ports::usage_repository::UsageRepository;
+
+use crate::{errors::AppError, ownership::owner_of, quota::Quotas};
+
+/// What the caller stores against the limits of its plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageReport {
+    /// `None` when the default quota applies.
+    pub plan: Option<String>,
+    pub quota: Quota,
+    pub usage: StorageUsage,
+}
+
+pub struct GetStorageUsage<U>
+where
+    U: UsageRepository,
+{
+    quotas: Quotas<U>,
+}
+
+impl<U> GetStorageUsage<U>
+where
+    U: UsageRepository,
+{
+    pub fn new(quotas: Quotas<U>) -> Self {
+        Self { quotas }
+    }
+
+    pub async fn execute(&self, identity: &Identity) -> Result<UsageReport, AppError> {
+        let owner_id = owner_of(identity)?;
+        let usage = self.quotas.usage_repository().usage(&owner_id).await?;
+
+        Ok(UsageReport {
+            plan: self.quotas.plan_of(identity).map(|plan| plan.name.clone()),
+            quota: *self.quotas.quota_of(identity),
+            usage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::quota::{Plan, Quota, QuotaPolicy, StorageUsage};
+    use ports::usage_repository::MockUsageRepository;
+
+    use crate::{
+        fixtures::{user, user_with_roles},
+        quota::Quotas,
+        usecases::get_storage_usage::GetStorageUsage,
+    };
+
+    fn usecase() -> GetStorageUsage<MockUsageRepository> {
+        let mut repo = MockUsageRepository::new();
+        repo.expect_usage()
+            .withf(|owner| owner.0.ends_with("|user1"))
+            .returning(|_| {
+                Box::pin(async {
+                    Ok(StorageUsage {
+                        vaults: 2,
+                        vault_bytes: 100,
+                        history_bytes: 50,
+                        attachment_bytes: 10,
+                    })
+                })
+            });
+
+        let policy = QuotaPolicy::new(
+            Quota {
+                max_vaults: Some(5),
+                ..Quota::unlimited()
+            },
+            [Plan {
+                name: "gold".into(),
+                quota: Quota::unlimited(),
+            }],
+        )
+        .unwrap();
+
+        GetStorageUsage::new(Quotas::new(repo, policy))
+    }
+
+    #[tokio::test]
+    async fn reports_usage_against_the_default_quota() {
+        let report = usecase().execute(&user()).await.unwrap();
+
+        assert_eq!(report.plan, None);
+        assert_eq!(report.quota.max_vaults, Some(5));
+        assert_eq!(report.usage.total_bytes(), 160);
+    }
+
+    #[tokio::test]
+    async fn reports_the_plan_of_the_caller() {
+        let report = usecase()
+            .execute(&user_with_roles(&["gold"]))
+            .await
+            .unwrap();
+
+        assert_eq!(report.plan.as_deref(), Some("gold"));
+        assert_eq!(report.quota, Quota::unlimited());
+    }
+}
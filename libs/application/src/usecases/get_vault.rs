@@ -0,0 +1,262 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KdfPolicy, Revision, Vault, VaultId, VaultVersion};
+use ports::vault_repository::VaultRepository;
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// A version of the vault the caller already holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownVersion {
+    /// Any existing version is acceptable (`If-None-Match: *`).
+    Any,
+    Etag(Etag),
+    Revision(Revision),
+}
+
+impl KnownVersion {
+    pub fn matches(&self, version: &VaultVersion) -> bool {
+        match self {
+            KnownVersion::Any => true,
+            KnownVersion::Etag(etag) => &version.etag == etag,
+            KnownVersion::Revision(revision) => &version.revision == revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ConditionalVault {
+    NotModified(VaultVersion),
+    Modified(Box<Vault>),
+}
+
+pub struct GetVault<R>
+where
+    R: VaultRepository,
+{
+    vault_repository: R,
+    kdf_policy: KdfPolicy,
+}
+
+impl<R> GetVault<R>
+where
+    R: VaultRepository,
+{
+    pub fn new(vault_repository: R, kdf_policy: KdfPolicy) -> Self {
+        Self {
+            vault_repository,
+            kdf_policy,
+        }
+    }
+
+    /// Whether the client should re-derive the vault key with stronger KDF
+    /// parameters, as configured by the current policy.
+    pub fn kdf_upgrade_recommended(&self, vault: &Vault) -> bool {
+        self.kdf_policy
+            .header_upgrade_recommended(&vault.package.header)
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+    ) -> Result<Vault, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        self.vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })
+    }
+
+    /// Returns the full vault only if it differs from every `known` version.
+    ///
+    /// The version check reads metadata only, so an unchanged vault never
+    /// pulls its ciphertext out of storage.
+    pub async fn execute_if_modified(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        known: &[KnownVersion],
+    ) -> Result<ConditionalVault, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let version = self
+            .vault_repository
+            .find_version(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        if known.iter().any(|k| k.matches(&version)) {
+            return Ok(ConditionalVault::NotModified(version));
+        }
+
+        self.execute(identity, vault_id)
+            .await
+            .map(|vault| ConditionalVault::Modified(Box::new(vault)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use domain::vault::{
+        Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, OwnerSub, Revision, Vault, VaultId,
+    };
+    use ports::vault_repository::MockVaultRepository;
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{user, valid_package},
+        usecases::get_vault::{ConditionalVault, GetVault, KnownVersion},
+    };
+
+    fn existing_vault() -> Vault {
+        Vault::new(
+            VaultId(Uuid::new_v4()),
+            OwnerSub::new("user1").unwrap(),
+            Utc::now(),
+            Etag::new("etag-1").unwrap(),
+            valid_package(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_if_vault_missing() {
+        let mut repo = MockVaultRepository::new();
+
+        repo.expect_find()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+
+        let result = GetVault::new(repo, KdfPolicy::default())
+            .execute(&user(), &VaultId(Uuid::new_v4()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn returns_full_vault() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+
+        let vault = GetVault::new(repo, KdfPolicy::default())
+            .execute(&user(), &vault_id)
+            .await
+            .unwrap();
+
+        assert_eq!(vault.etag.0, "etag-1");
+        assert_eq!(vault.package.blob.ciphertext.len(), 32);
+    }
+
+    #[tokio::test]
+    async fn not_modified_on_known_etag_skips_package_load() {
+        let mut repo = MockVaultRepository::new();
+        let version = existing_vault().version();
+        let vault_id = version.id;
+
+        repo.expect_find_version().returning(move |_, _| {
+            let v = version.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_find().never();
+
+        let result = GetVault::new(repo, KdfPolicy::default())
+            .execute_if_modified(
+                &user(),
+                &vault_id,
+                &[KnownVersion::Etag(Etag::new("etag-1").unwrap())],
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(result, ConditionalVault::NotModified(_)));
+    }
+
+    #[tokio::test]
+    async fn not_modified_on_known_revision() {
+        let mut repo = MockVaultRepository::new();
+        let version = existing_vault().version();
+        let vault_id = version.id;
+
+        repo.expect_find_version().returning(move |_, _| {
+            let v = version.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+
+        let result = GetVault::new(repo, KdfPolicy::default())
+            .execute_if_modified(
+                &user(),
+                &vault_id,
+                &[KnownVersion::Revision(Revision::INITIAL)],
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(result, ConditionalVault::NotModified(_)));
+    }
+
+    #[tokio::test]
+    async fn modified_loads_full_vault() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        let version = vault.version();
+
+        repo.expect_find_version().returning(move |_, _| {
+            let v = version.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_find().times(1).returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+
+        let result = GetVault::new(repo, KdfPolicy::default())
+            .execute_if_modified(
+                &user(),
+                &vault_id,
+                &[KnownVersion::Etag(Etag::new("etag-0").unwrap())],
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(result, ConditionalVault::Modified(_)));
+    }
+
+    #[test]
+    fn flags_vaults_below_the_recommended_kdf_cost() {
+        let usecase = GetVault::new(MockVaultRepository::new(), KdfPolicy::default());
+        let mut vault = existing_vault();
+
+        assert!(!usecase.kdf_upgrade_recommended(&vault));
+
+        vault.package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 64 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+        assert!(usecase.kdf_upgrade_recommended(&vault));
+    }
+}
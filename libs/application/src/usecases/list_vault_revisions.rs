@@ -0,0 +1,109 @@
+use auth::domain::models::Identity;
+use domain::vault::{RevisionSummary, VaultId};
+use ports::vault_repository::VaultRepository;
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+pub struct ListVaultRevisions<R>
+where
+    R: VaultRepository,
+{
+    vault_repository: R,
+}
+
+impl<R> ListVaultRevisions<R>
+where
+    R: VaultRepository,
+{
+    pub fn new(vault_repository: R) -> Self {
+        Self { vault_repository }
+    }
+
+    /// Archived revisions of the caller's vault, newest first. The current
+    /// revision is not included.
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+    ) -> Result<Vec<RevisionSummary>, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let version = self
+            .vault_repository
+            .find_version(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        Ok(self.vault_repository.list_revisions(&version.id).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::vault::{Etag, Revision, RevisionSummary, VaultId, VaultVersion};
+    use ports::vault_repository::MockVaultRepository;
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, user},
+        usecases::list_vault_revisions::ListVaultRevisions,
+    };
+
+    #[tokio::test]
+    async fn returns_not_found_if_vault_missing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find_version()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+        repo.expect_list_revisions().never();
+
+        let result = ListVaultRevisions::new(repo)
+            .execute(&user(), &VaultId(Uuid::new_v4()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn lists_history_of_the_callers_vault() {
+        let vault_id = VaultId(Uuid::new_v4());
+        let summary = RevisionSummary {
+            revision: Revision(0),
+            etag: Etag::new("etag-1").unwrap(),
+            saved_at: now(),
+            archived_at: now(),
+        };
+
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find_version().returning(move |_, _| {
+            Box::pin(async move {
+                Ok(Some(VaultVersion {
+                    id: vault_id,
+                    revision: Revision(1),
+                    etag: Etag::new("etag-2").unwrap(),
+                    updated_at: now(),
+                }))
+            })
+        });
+        let listed = summary.clone();
+        repo.expect_list_revisions()
+            .withf(move |id| *id == vault_id)
+            .returning(move |_| {
+                let listed = listed.clone();
+                Box::pin(async move { Ok(vec![listed]) })
+            });
+
+        let revisions = ListVaultRevisions::new(repo)
+            .execute(&user(), &vault_id)
+            .await
+            .unwrap();
+
+        assert_eq!(revisions, vec![summary]);
+    }
+}
@@ -0,0 +1,81 @@
+use auth::domain::models::Identity;
+use domain::vault::VaultSummary;
+use ports::vault_repository::VaultRepository;
+
+use crate::{errors::AppError, ownership::owner_of};
+
+/// The caller's live vaults, oldest first. Trashed vaults are left out.
+pub struct ListVaults<R>
+where
+    R: VaultRepository,
+{
+    vault_repository: R,
+}
+
+impl<R> ListVaults<R>
+where
+    R: VaultRepository,
+{
+    pub fn new(vault_repository: R) -> Self {
+        Self { vault_repository }
+    }
+
+    pub async fn execute(&self, identity: &Identity) -> Result<Vec<VaultSummary>, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        Ok(self.vault_repository.list_by_owner(&owner_id).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::domain::models::{Client, Identity};
+    use chrono::DateTime;
+    use domain::vault::{Etag, Revision, VaultId, VaultSummary};
+    use ports::vault_repository::MockVaultRepository;
+    use uuid::Uuid;
+
+    use crate::{errors::AppError, fixtures::user, usecases::list_vaults::ListVaults};
+
+    #[tokio::test]
+    async fn lists_vaults_of_the_caller() {
+        let summary = VaultSummary {
+            id: VaultId(Uuid::new_v4()),
+            revision: Revision(3),
+            etag: Etag::new("etag-3").unwrap(),
+            updated_at: DateTime::from_timestamp(1_770_000_000, 0).unwrap(),
+            metadata: None,
+        };
+
+        let mut repo = MockVaultRepository::new();
+        let listed = summary.clone();
+        repo.expect_list_by_owner()
+            .withf(|owner| owner.0 == "http://localhost:8000/realms/ferrispass|user1")
+            .returning(move |_| {
+                let listed = listed.clone();
+                Box::pin(async move { Ok(vec![listed]) })
+            });
+
+        let vaults = ListVaults::new(repo).execute(&user()).await.unwrap();
+
+        assert_eq!(vaults, vec![summary]);
+    }
+
+    #[tokio::test]
+    async fn refuses_undelegated_service_accounts() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_list_by_owner().never();
+
+        let client = Identity::Client(Client {
+            id: "service-123".into(),
+            issuer: "http://localhost:8000/realms/ferrispass".into(),
+            client_id: "ferrispass-sync".into(),
+            roles: vec!["service".into()],
+            scopes: vec![],
+        });
+
+        let result = ListVaults::new(repo).execute(&client).await;
+
+        assert!(matches!(result, Err(AppError::Forbidden { .. })));
+    }
+}
@@ -1 +1,21 @@
+pub mod add_key_slot;
+pub mod collect_orphaned_attachments;
+pub mod complete_attachment_upload;
+pub mod create_vault;
+pub mod delete_vault;
+pub mod download_attachment_chunk;
+pub mod get_attachment;
+pub mod get_storage_usage;
+pub mod get_vault;
+pub mod list_vault_revisions;
+pub mod list_vaults;
+pub mod prune_expired_history;
+pub mod purge_deleted_vaults;
 pub mod put_vault;
+pub mod restore_deleted_vault;
+pub mod restore_vault_revision;
+pub mod revoke_key_slot;
+pub mod rewrap_vault_key;
+pub mod start_attachment_upload;
+pub mod store_vault_ciphertext;
+pub mod upload_attachment_chunk;
@@ -0,0 +1,93 @@
+use domain::vault::RetentionPolicy;
+use ports::{clock::Clock, vault_repository::VaultRepository};
+
+use crate::errors::AppError;
+
+/// Drops archived revisions older than the retention age, across all vaults.
+///
+/// Writes already prune the history of the vault they touch; this runs
+/// periodically in the background so revisions of vaults nobody writes to
+/// age out too. A revision count limit only changes on writes, so it is left
+/// to them.
+pub struct PruneExpiredHistory<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    vault_repository: R,
+    clock: C,
+    retention: RetentionPolicy,
+}
+
+impl<R, C> PruneExpiredHistory<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    pub fn new(vault_repository: R, clock: C, retention: RetentionPolicy) -> Self {
+        Self {
+            vault_repository,
+            clock,
+            retention,
+        }
+    }
+
+    /// Returns the number of revisions dropped.
+    pub async fn execute(&self) -> Result<u64, AppError> {
+        let Some(max_age) = self.retention.max_age else {
+            return Ok(0);
+        };
+        let archived_before = self.clock.now() - max_age;
+
+        Ok(self
+            .vault_repository
+            .purge_revisions(archived_before)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::vault::RetentionPolicy;
+    use ports::{clock::FixedClock, vault_repository::MockVaultRepository};
+
+    use crate::{fixtures::now, usecases::prune_expired_history::PruneExpiredHistory};
+
+    #[tokio::test]
+    async fn drops_revisions_older_than_max_age() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_purge_revisions()
+            .withf(|before| *before == now() - Duration::days(90))
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(3) }));
+        let retention = RetentionPolicy {
+            max_age: Some(Duration::days(90)),
+            ..RetentionPolicy::keep_all()
+        };
+
+        let pruned = PruneExpiredHistory::new(repo, FixedClock(now()), retention)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(pruned, 3);
+    }
+
+    #[tokio::test]
+    async fn keeps_everything_without_max_age() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_purge_revisions().never();
+        let retention = RetentionPolicy {
+            max_revisions: Some(10),
+            ..RetentionPolicy::keep_all()
+        };
+
+        let pruned = PruneExpiredHistory::new(repo, FixedClock(now()), retention)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(pruned, 0);
+    }
+}
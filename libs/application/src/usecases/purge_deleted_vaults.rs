@@ -0,0 +1,63 @@
+use chrono::Duration;
+use ports::{clock::Clock, vault_repository::VaultRepository};
+
+use crate::errors::AppError;
+
+/// Hard-deletes vaults whose grace period in the trash is over.
+///
+/// Meant to run periodically in the background; it needs no identity since
+/// it only ever removes vaults their owners already deleted.
+pub struct PurgeDeletedVaults<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    vault_repository: R,
+    clock: C,
+    grace_period: Duration,
+}
+
+impl<R, C> PurgeDeletedVaults<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    pub fn new(vault_repository: R, clock: C, grace_period: Duration) -> Self {
+        Self {
+            vault_repository,
+            clock,
+            grace_period,
+        }
+    }
+
+    /// Returns the number of vaults purged.
+    pub async fn execute(&self) -> Result<u64, AppError> {
+        let deleted_before = self.clock.now() - self.grace_period;
+
+        Ok(self.vault_repository.purge_deleted(deleted_before).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use ports::{clock::FixedClock, vault_repository::MockVaultRepository};
+
+    use crate::{fixtures::now, usecases::purge_deleted_vaults::PurgeDeletedVaults};
+
+    #[tokio::test]
+    async fn purges_tombstones_older_than_grace_period() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_purge_deleted()
+            .withf(|before| *before == now() - Duration::days(30))
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(2) }));
+
+        let purged = PurgeDeletedVaults::new(repo, FixedClock(now()), Duration::days(30))
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(purged, 2);
+    }
+}
@@ -1,53 +1,105 @@
-use chrono::{DateTime, Utc};
-use domain::vault::{Etag, OwnerSub, VaultPackage};
-use ports::{etag::EtagGenerator, vault_repository::VaultRepository};
-
-use crate::errors::{AppError, Resource};
-
-pub struct PutVault<R, E>
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KdfPolicy, RetentionPolicy, VaultId, VaultPackage};
+use ports::{
+    attachment_repository::AttachmentRepository, clock::Clock, etag::EtagGenerator,
+    usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+
+use crate::{
+    attachments::check_references,
+    errors::{AppError, Resource},
+    ownership::owner_of,
+    quota::{Growth, PackageSize, Quotas},
+    retention::prune_history,
+};
+
+pub struct PutVault<R, T, E, C, U>
 where
     R: VaultRepository,
+    T: AttachmentRepository,
     E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
 {
     vault_repository: R,
+    attachment_repository: T,
     etag_generator: E,
+    clock: C,
+    retention: RetentionPolicy,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
 }
 
-impl<R, E> PutVault<R, E>
+impl<R, T, E, C, U> PutVault<R, T, E, C, U>
 where
     R: VaultRepository,
+    T: AttachmentRepository,
     E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
 {
-    pub fn new(vault_repository: R, etag_generator: E) -> Self {
+    pub fn new(
+        vault_repository: R,
+        attachment_repository: T,
+        etag_generator: E,
+        clock: C,
+        retention: RetentionPolicy,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
         Self {
             vault_repository,
+            attachment_repository,
             etag_generator,
+            clock,
+            retention,
+            kdf_policy,
+            quotas,
         }
     }
 
     pub async fn execute(
         &self,
-        owner_id: OwnerSub,
+        identity: &Identity,
+        vault_id: &VaultId,
         expected_etag: Etag,
         package: VaultPackage,
-        now: DateTime<Utc>,
     ) -> Result<(Etag, u64), AppError> {
+        let owner_id = owner_of(identity)?;
+        self.kdf_policy.check_header(&package.header)?;
+
         let existing = self
             .vault_repository
-            .find_by_owner(&owner_id)
+            .find(&owner_id, vault_id)
             .await?
             .ok_or(AppError::NotFound {
                 resource: Resource::Vault,
-                id: Some(owner_id.0.clone()),
+                id: Some(vault_id.0.to_string()),
             })?;
 
-        let new_etag = self.etag_generator.generate(&package);
+        let new_etag = self
+            .etag_generator
+            .generate(existing.revision.next(), &package);
+
+        // A content-addressed etag that did not move means the client sent
+        // back what is already stored: nothing to write.
+        if new_etag == existing.etag && expected_etag == existing.etag {
+            return Ok((existing.etag, existing.revision.0));
+        }
 
+        let size = PackageSize::of(&package);
+        let now = self.clock.now();
         let updated = existing.update(&expected_etag, now, new_etag.clone(), package)?;
+        check_references(&self.attachment_repository, &owner_id, &updated.package).await?;
+
+        self.quotas
+            .check_package(identity, &owner_id, size, Growth::Update)
+            .await?;
 
         self.vault_repository
             .update_if_match(&updated, &expected_etag)
             .await?;
+        prune_history(&self.vault_repository, &self.retention, &updated.id, now).await;
 
         Ok((updated.etag, updated.revision.0))
     }
@@ -55,45 +107,53 @@ where
 
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
-    use domain::vault::{
-        CipherBlob, CryptoVersion, Etag, KdfAlg, KdfParams, KdfSpec, OwnerSub, Vault, VaultHeader,
-        VaultId, VaultPackage,
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use auth::domain::models::{Client, Identity};
+    use chrono::Duration;
+    use domain::{
+        attachment::{Attachment, AttachmentId},
+        quota::{Quota, QuotaLimit, QuotaPolicy},
+        vault::{
+            Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, RetentionPolicy, Revision, Vault,
+            VaultId,
+        },
     };
 
-    use ports::{RepositoryError, etag::MockEtagGenerator, vault_repository::MockVaultRepository};
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryVaultRepository};
+    use ports::{
+        RepositoryError,
+        attachment_repository::{AttachmentRepository, MockAttachmentRepository},
+        clock::FixedClock,
+        etag::MockEtagGenerator,
+        usage_repository::MockUsageRepository,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
     use uuid::Uuid;
 
-    use crate::{errors::AppError, usecases::put_vault::PutVault};
-
-    fn valid_package() -> VaultPackage {
-        VaultPackage {
-            header: VaultHeader {
-                crypto_version: CryptoVersion::V1,
-                kdf: KdfSpec {
-                    alg: KdfAlg::Argon2id,
-                    salt: vec![1; 16],
-                    params: KdfParams {
-                        m_kib: 131_072,
-                        t: 3,
-                        p: 1,
-                    },
-                },
-                wrapped_vault_key: vec![2; 32],
-            },
-            blob: CipherBlob {
-                nonce: vec![3; 24],
-                aad: vec![],
-                ciphertext: vec![4; 32],
-            },
-        }
+    use crate::{
+        errors::AppError,
+        fixtures::{now, owner, user, valid_package},
+        quota::Quotas,
+        usecases::put_vault::PutVault,
+    };
+
+    fn unlimited() -> Quotas<MockUsageRepository> {
+        Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited())
+    }
+
+    fn clock() -> FixedClock {
+        FixedClock(now())
     }
 
     fn existing_vault() -> Vault {
         Vault::new(
             VaultId(Uuid::new_v4()),
-            OwnerSub::new("user1").unwrap(),
-            Utc::now(),
+            owner(),
+            now() - Duration::days(1),
             Etag::new("etag-1").unwrap(),
             valid_package(),
         )
@@ -105,17 +165,25 @@ mod tests {
         let mut repo = MockVaultRepository::new();
         let etag_gen = MockEtagGenerator::new();
 
-        repo.expect_find_by_owner()
-            .returning(|_| Box::pin(async { Ok(None) }));
+        repo.expect_find()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
 
-        let usecase = PutVault::new(repo, etag_gen);
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
 
         let result = usecase
             .execute(
-                OwnerSub::new("user1").unwrap(),
+                &user(),
+                &VaultId(Uuid::new_v4()),
                 Etag::new("etag-1").unwrap(),
                 valid_package(),
-                Utc::now(),
             )
             .await;
 
@@ -129,23 +197,33 @@ mod tests {
 
         let vault = existing_vault();
 
-        repo.expect_find_by_owner().returning(move |_| {
+        let vault_id = vault.id;
+
+        repo.expect_find().returning(move |_, _| {
             let v = vault.clone();
             Box::pin(async move { Ok(Some(v)) })
         });
 
         etag_gen
             .expect_generate()
-            .returning(|_| Etag::new("new-etag").unwrap());
-
-        let usecase = PutVault::new(repo, etag_gen);
+            .returning(|_, _| Etag::new("new-etag").unwrap());
+
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
 
         let result = usecase
             .execute(
-                OwnerSub::new("user1").unwrap(),
+                &user(),
+                &vault_id,
                 Etag::new("wrong-etag").unwrap(),
                 valid_package(),
-                Utc::now(),
             )
             .await;
 
@@ -159,14 +237,16 @@ mod tests {
 
         let vault = existing_vault();
 
-        repo.expect_find_by_owner().returning(move |_| {
+        let vault_id = vault.id;
+
+        repo.expect_find().returning(move |_, _| {
             let v = vault.clone();
             Box::pin(async move { Ok(Some(v)) })
         });
 
         etag_gen
             .expect_generate()
-            .returning(|_| Etag::new("etag-2").unwrap());
+            .returning(|_, _| Etag::new("etag-2").unwrap());
 
         repo.expect_update_if_match().returning(|_, _| {
             Box::pin(async {
@@ -176,14 +256,22 @@ mod tests {
             })
         });
 
-        let usecase = PutVault::new(repo, etag_gen);
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
 
         let result = usecase
             .execute(
-                OwnerSub::new("user1").unwrap(),
+                &user(),
+                &vault_id,
                 Etag::new("etag-1").unwrap(),
                 valid_package(),
-                Utc::now(),
             )
             .await;
 
@@ -197,26 +285,40 @@ mod tests {
 
         let vault = existing_vault();
 
-        repo.expect_find_by_owner().returning(move |_| {
-            let v = vault.clone();
-            Box::pin(async move { Ok(Some(v)) })
-        });
+        let vault_id = vault.id;
+
+        repo.expect_find()
+            .withf(move |owner, id| {
+                owner.0 == "http://localhost:8000/realms/ferrispass|user1" && *id == vault_id
+            })
+            .returning(move |_, _| {
+                let v = vault.clone();
+                Box::pin(async move { Ok(Some(v)) })
+            });
 
         etag_gen
             .expect_generate()
-            .returning(|_| Etag::new("etag-2").unwrap());
+            .returning(|_, _| Etag::new("etag-2").unwrap());
 
         repo.expect_update_if_match()
             .returning(|_, _| Box::pin(async { Ok(()) }));
 
-        let usecase = PutVault::new(repo, etag_gen);
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
 
         let (new_etag, new_revision) = usecase
             .execute(
-                OwnerSub::new("user1").unwrap(),
+                &user(),
+                &vault_id,
                 Etag::new("etag-1").unwrap(),
                 valid_package(),
-                Utc::now(),
             )
             .await
             .unwrap();
@@ -224,4 +326,333 @@ mod tests {
         assert_eq!(new_etag.0, "etag-2");
         assert_eq!(new_revision, 1);
     }
+
+    #[tokio::test]
+    async fn unchanged_content_is_not_rewritten() {
+        let mut repo = MockVaultRepository::new();
+        let mut etag_gen = MockEtagGenerator::new();
+
+        let vault = existing_vault();
+
+        let vault_id = vault.id;
+
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-1").unwrap());
+        repo.expect_update_if_match().never();
+
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let (etag, revision) = usecase
+            .execute(
+                &user(),
+                &vault_id,
+                Etag::new("etag-1").unwrap(),
+                valid_package(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(etag.0, "etag-1");
+        assert_eq!(revision, 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_kdf_above_policy_maximum() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find().never();
+        repo.expect_update_if_match().never();
+
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            MockEtagGenerator::new(),
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let mut package = valid_package();
+        package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 4 * 1024 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+
+        let result = usecase
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                package,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_attachment_still_uploading() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        repo.create(&vault).await.unwrap();
+
+        let attachments = InMemoryAttachmentRepository::new();
+        let pending = Attachment::new(
+            AttachmentId(Uuid::new_v4()),
+            vault.owner_id.clone(),
+            10,
+            10,
+            now(),
+        )
+        .unwrap();
+        attachments.create(&pending).await.unwrap();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let usecase = PutVault::new(
+            repo.clone(),
+            attachments,
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let mut package = valid_package();
+        package.attachments = vec![pending.id];
+
+        let result = usecase
+            .execute(&user(), &vault.id, vault.etag.clone(), package)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "attachments",
+                ..
+            })
+        ));
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.etag, vault.etag);
+    }
+
+    #[tokio::test]
+    async fn rejects_package_over_the_quota() {
+        let mut repo = MockVaultRepository::new();
+        let mut etag_gen = MockEtagGenerator::new();
+
+        let vault = existing_vault();
+        let vault_id = vault.id;
+
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+        repo.expect_update_if_match().never();
+
+        let quota = Quota {
+            max_package_bytes: Some(64),
+            ..Quota::unlimited()
+        };
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            Quotas::new(
+                MockUsageRepository::new(),
+                QuotaPolicy::new(quota, []).unwrap(),
+            ),
+        );
+
+        let mut package = valid_package();
+        package.blob.ciphertext = vec![4; 64];
+
+        let result = usecase
+            .execute(&user(), &vault_id, Etag::new("etag-1").unwrap(), package)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::PackageBytes,
+                allowed: 64,
+                requested: 88,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn refuses_undelegated_service_accounts() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find().never();
+        repo.expect_update_if_match().never();
+
+        let usecase = PutVault::new(
+            repo,
+            MockAttachmentRepository::new(),
+            MockEtagGenerator::new(),
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let client = Identity::Client(Client {
+            id: "service-123".into(),
+            issuer: "http://localhost:8000/realms/ferrispass".into(),
+            client_id: "ferrispass-sync".into(),
+            roles: vec!["service".into()],
+            scopes: vec![],
+        });
+
+        let result = usecase
+            .execute(
+                &client,
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                valid_package(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Forbidden { .. })));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_puts_from_the_same_etag_have_one_winner() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.create(&vault).await.unwrap();
+
+        let counter = AtomicUsize::new(2);
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen.expect_generate().returning(move |_, _| {
+            Etag::new(format!("etag-{}", counter.fetch_add(1, Ordering::SeqCst))).unwrap()
+        });
+
+        let usecase = Arc::new(PutVault::new(
+            repo.clone(),
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            unlimited(),
+        ));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let usecase = usecase.clone();
+                let expected = vault.etag.clone();
+                tokio::spawn(async move {
+                    usecase
+                        .execute(&user(), &vault_id, expected, valid_package())
+                        .await
+                })
+            })
+            .collect();
+
+        let mut winners = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(_) => winners += 1,
+                Err(AppError::Conflict { .. }) => {}
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+
+        assert_eq!(winners, 1);
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.revision.0, 1);
+        assert_eq!(stored.updated_at, now());
+        assert_eq!(stored.created_at, vault.created_at);
+    }
+
+    #[tokio::test]
+    async fn prunes_history_beyond_retention() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.create(&vault).await.unwrap();
+
+        let counter = AtomicUsize::new(2);
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen.expect_generate().returning(move |_, _| {
+            Etag::new(format!("etag-{}", counter.fetch_add(1, Ordering::SeqCst))).unwrap()
+        });
+
+        let retention = RetentionPolicy {
+            max_revisions: Some(2),
+            max_age: None,
+        };
+        let usecase = PutVault::new(
+            repo.clone(),
+            MockAttachmentRepository::new(),
+            etag_gen,
+            clock(),
+            retention,
+            KdfPolicy::default(),
+            unlimited(),
+        );
+
+        let mut etag = vault.etag.clone();
+        for _ in 0..4 {
+            (etag, _) = usecase
+                .execute(&user(), &vault_id, etag, valid_package())
+                .await
+                .unwrap();
+        }
+
+        let kept: Vec<_> = repo
+            .list_revisions(&vault.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|summary| summary.revision)
+            .collect();
+        assert_eq!(kept, vec![Revision(3), Revision(2)]);
+    }
 }
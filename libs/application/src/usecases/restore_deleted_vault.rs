@@ -0,0 +1,150 @@
+use auth::domain::models::Identity;
+use chrono::Duration;
+use domain::vault::{Vault, VaultId};
+use ports::{clock::Clock, vault_repository::VaultRepository};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// Takes the caller's vault out of the trash during its grace period.
+pub struct RestoreDeletedVault<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    vault_repository: R,
+    clock: C,
+    grace_period: Duration,
+}
+
+impl<R, C> RestoreDeletedVault<R, C>
+where
+    R: VaultRepository,
+    C: Clock,
+{
+    pub fn new(vault_repository: R, clock: C, grace_period: Duration) -> Self {
+        Self {
+            vault_repository,
+            clock,
+            grace_period,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+    ) -> Result<Vault, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let deleted = self
+            .vault_repository
+            .find_deleted(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let restored = deleted.restore(self.clock.now(), self.grace_period)?;
+
+        self.vault_repository.restore_deleted(&restored).await?;
+
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use domain::vault::{Etag, Vault, VaultId};
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{clock::FixedClock, vault_repository::VaultRepository};
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{owner, user, valid_package},
+        usecases::restore_deleted_vault::RestoreDeletedVault,
+    };
+
+    fn deleted_at() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_770_000_000, 0).unwrap()
+    }
+
+    async fn trashed_vault(repo: &InMemoryVaultRepository) -> Vault {
+        let vault = Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            deleted_at() - Duration::days(1),
+            Etag::new("etag-1").unwrap(),
+            valid_package(),
+        )
+        .unwrap();
+        repo.create(&vault).await.unwrap();
+        repo.mark_deleted(
+            &vault.delete(&vault.etag, deleted_at()).unwrap(),
+            &vault.etag,
+        )
+        .await
+        .unwrap();
+
+        vault
+    }
+
+    fn usecase(
+        repo: InMemoryVaultRepository,
+        now: DateTime<Utc>,
+    ) -> RestoreDeletedVault<InMemoryVaultRepository, FixedClock> {
+        RestoreDeletedVault::new(repo, FixedClock(now), Duration::days(30))
+    }
+
+    #[tokio::test]
+    async fn restores_within_grace_period() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = trashed_vault(&repo).await;
+
+        let restored = usecase(repo.clone(), deleted_at() + Duration::days(29))
+            .execute(&user(), &vault.id)
+            .await
+            .unwrap();
+
+        assert_eq!(restored.etag, vault.etag);
+        assert_eq!(restored.deleted_at, None);
+        assert!(
+            repo.find(&vault.owner_id, &vault.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_tombstone_is_not_found() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = trashed_vault(&repo).await;
+
+        let result = usecase(repo.clone(), deleted_at() + Duration::days(31))
+            .execute(&user(), &vault.id)
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+        assert!(
+            repo.find(&vault.owner_id, &vault.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn nothing_in_trash_is_not_found() {
+        let result = usecase(InMemoryVaultRepository::new(), deleted_at())
+            .execute(&user(), &VaultId(Uuid::new_v4()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+}
@@ -0,0 +1,331 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KdfPolicy, RetentionPolicy, Revision, VaultId};
+use ports::{
+    clock::Clock, etag::EtagGenerator, usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+    quota::{Growth, PackageSize, Quotas},
+    retention::prune_history,
+};
+
+/// Makes an archived revision current again.
+///
+/// The restore is a normal write: it needs the current etag, produces a new
+/// revision holding the old package, and archives the one it replaces. The
+/// old header must still meet the KDF policy, which may have been raised
+/// since it was saved.
+pub struct RestoreVaultRevision<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    etag_generator: E,
+    clock: C,
+    retention: RetentionPolicy,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, E, C, U> RestoreVaultRevision<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        etag_generator: E,
+        clock: C,
+        retention: RetentionPolicy,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            etag_generator,
+            clock,
+            retention,
+            kdf_policy,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        expected_etag: Etag,
+        revision: Revision,
+    ) -> Result<(Etag, u64), AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let existing = self
+            .vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let archived = self
+            .vault_repository
+            .find_revision(&existing.id, revision)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::VaultRevision,
+                id: Some(revision.0.to_string()),
+            })?;
+        self.kdf_policy.check_header(&archived.package.header)?;
+
+        let new_etag = self
+            .etag_generator
+            .generate(existing.revision.next(), &archived.package);
+
+        // Restoring content identical to the current revision writes nothing,
+        // as for a PUT of unchanged content.
+        if new_etag == existing.etag && expected_etag == existing.etag {
+            return Ok((existing.etag, existing.revision.0));
+        }
+
+        let size = PackageSize::of(&archived.package);
+        let now = self.clock.now();
+        let restored = existing.update(&expected_etag, now, new_etag, archived.package)?;
+
+        self.quotas
+            .check_package(identity, &owner_id, size, Growth::Update)
+            .await?;
+
+        self.vault_repository
+            .update_if_match(&restored, &expected_etag)
+            .await?;
+        prune_history(&self.vault_repository, &self.retention, &restored.id, now).await;
+
+        Ok((restored.etag, restored.revision.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::{
+        quota::QuotaPolicy,
+        vault::{
+            Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, RetentionPolicy, Revision, Vault,
+            VaultId, VaultPackage,
+        },
+    };
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{
+        clock::FixedClock,
+        etag::MockEtagGenerator,
+        usage_repository::MockUsageRepository,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::{AppError, Resource},
+        fixtures::{now, owner, user, valid_package},
+        quota::Quotas,
+        usecases::restore_vault_revision::RestoreVaultRevision,
+    };
+
+    fn package(ciphertext: u8) -> VaultPackage {
+        let mut package = valid_package();
+        package.blob.ciphertext = vec![ciphertext; 32];
+        package
+    }
+
+    /// A stored vault at revision 1 whose revision 0 held `first`.
+    async fn vault_with_history(repo: &InMemoryVaultRepository, first: VaultPackage) -> Vault {
+        let first = Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            now() - Duration::days(2),
+            Etag::new("etag-0").unwrap(),
+            first,
+        )
+        .unwrap();
+        repo.create(&first).await.unwrap();
+
+        let second = first
+            .update(
+                &first.etag,
+                now() - Duration::days(1),
+                Etag::new("etag-1").unwrap(),
+                package(1),
+            )
+            .unwrap();
+        repo.update_if_match(&second, &first.etag).await.unwrap();
+
+        second
+    }
+
+    fn usecase(
+        repo: InMemoryVaultRepository,
+    ) -> RestoreVaultRevision<
+        InMemoryVaultRepository,
+        MockEtagGenerator,
+        FixedClock,
+        MockUsageRepository,
+    > {
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen.expect_generate().returning(|revision, package| {
+            Etag::new(format!(
+                "etag-{}-{}",
+                revision.0, package.blob.ciphertext[0]
+            ))
+            .unwrap()
+        });
+
+        RestoreVaultRevision::new(
+            repo,
+            etag_gen,
+            FixedClock(now()),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited()),
+        )
+    }
+
+    #[tokio::test]
+    async fn restores_old_package_as_a_new_revision() {
+        let repo = InMemoryVaultRepository::new();
+        let current = vault_with_history(&repo, package(0)).await;
+
+        let (etag, revision) = usecase(repo.clone())
+            .execute(&user(), &current.id, current.etag.clone(), Revision(0))
+            .await
+            .unwrap();
+
+        assert_eq!(etag.0, "etag-2-0");
+        assert_eq!(revision, 2);
+        let stored = repo
+            .find(&current.owner_id, &current.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.package.blob.ciphertext, package(0).blob.ciphertext);
+        assert_eq!(stored.updated_at, now());
+        // The replaced revision is itself kept.
+        assert!(
+            repo.find_revision(&current.id, Revision(1))
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_conflict_if_etag_mismatch() {
+        let repo = InMemoryVaultRepository::new();
+        let current = vault_with_history(&repo, package(0)).await;
+
+        let result = usecase(repo)
+            .execute(
+                &user(),
+                &current.id,
+                Etag::new("stale").unwrap(),
+                Revision(0),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn rejects_revision_whose_kdf_is_below_the_policy() {
+        let repo = InMemoryVaultRepository::new();
+        let mut weak = package(0);
+        weak.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+        let current = vault_with_history(&repo, weak).await;
+
+        let result = usecase(repo.clone())
+            .execute(&user(), &current.id, current.etag.clone(), Revision(0))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+        let stored = repo
+            .find(&current.owner_id, &current.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.etag, current.etag);
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_for_unknown_revision() {
+        let repo = InMemoryVaultRepository::new();
+        let current = vault_with_history(&repo, package(0)).await;
+
+        let result = usecase(repo)
+            .execute(&user(), &current.id, current.etag, Revision(7))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::NotFound {
+                resource: Resource::VaultRevision,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_if_vault_missing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+        repo.expect_find_revision().never();
+
+        let usecase = RestoreVaultRevision::new(
+            repo,
+            MockEtagGenerator::new(),
+            FixedClock(now()),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited()),
+        );
+
+        let result = usecase
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                Revision(0),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::NotFound {
+                resource: Resource::Vault,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KeySlotId, RetentionPolicy, VaultId};
+use ports::{
+    clock::Clock, etag::EtagGenerator, usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+    quota::Quotas,
+    retention::prune_history,
+};
+
+/// Removes an unlock method from a vault, e.g. a lost device. The last slot
+/// cannot be revoked, since the vault could no longer be opened.
+///
+/// Revoking only drops the wrapped key from the current header: whoever
+/// already holds the vault key keeps it until the vault is re-encrypted.
+pub struct RevokeKeySlot<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    etag_generator: E,
+    clock: C,
+    retention: RetentionPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, E, C, U> RevokeKeySlot<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        etag_generator: E,
+        clock: C,
+        retention: RetentionPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            etag_generator,
+            clock,
+            retention,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        expected_etag: Etag,
+        slot_id: KeySlotId,
+    ) -> Result<(Etag, u64), AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let existing = self
+            .vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let header = &existing.package.header;
+        if header.slot(slot_id).is_none() {
+            return Err(AppError::NotFound {
+                resource: Resource::KeySlot,
+                id: Some(slot_id.0.to_string()),
+            });
+        }
+
+        let package = existing.package.with_header(header.without_slot(slot_id))?;
+        let new_etag = self
+            .etag_generator
+            .generate(existing.revision.next(), &package);
+
+        // Fewer slots, but the replaced revision is archived whole.
+        let package_bytes = package.byte_size();
+        let now = self.clock.now();
+        let updated = existing.update(&expected_etag, now, new_etag, package)?;
+
+        self.quotas
+            .check_bytes(identity, &owner_id, package_bytes)
+            .await?;
+
+        self.vault_repository
+            .update_if_match(&updated, &expected_etag)
+            .await?;
+        prune_history(&self.vault_repository, &self.retention, &updated.id, now).await;
+
+        Ok((updated.etag, updated.revision.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::{
+        quota::{Quota, QuotaLimit, QuotaPolicy, StorageUsage},
+        vault::{
+            Etag, KdfParams, KdfSpec, KeySlot, KeySlotId, KeySlotKind, RetentionPolicy, Vault,
+            VaultHeader, VaultId, VaultPackage,
+        },
+    };
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{
+        clock::FixedClock,
+        etag::MockEtagGenerator,
+        usage_repository::MockUsageRepository,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::{AppError, Resource},
+        fixtures::{header, now, owner, user, valid_package},
+        quota::Quotas,
+        usecases::revoke_key_slot::RevokeKeySlot,
+    };
+
+    fn master_password() -> KeySlot {
+        KeySlot {
+            id: KeySlotId(Uuid::from_u128(1)),
+            kind: KeySlotKind::MasterPassword {
+                kdf: KdfSpec {
+                    salt: vec![1; 16],
+                    params: KdfParams::Argon2id {
+                        m_kib: 131_072,
+                        t: 3,
+                        p: 1,
+                    },
+                },
+            },
+            wrapped_vault_key: vec![2; 72],
+        }
+    }
+
+    fn device() -> KeySlot {
+        KeySlot {
+            id: KeySlotId(Uuid::from_u128(2)),
+            kind: KeySlotKind::Device {
+                device_id: "phone".into(),
+            },
+            wrapped_vault_key: vec![5; 72],
+        }
+    }
+
+    fn existing_vault(key_slots: Vec<KeySlot>) -> Vault {
+        Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            now() - Duration::days(1),
+            Etag::new("etag-1").unwrap(),
+            VaultPackage {
+                header: VaultHeader {
+                    key_slots,
+                    ..header()
+                },
+                ..valid_package()
+            },
+        )
+        .unwrap()
+    }
+
+    fn etag_gen() -> MockEtagGenerator {
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+        etag_gen
+    }
+
+    fn usecase<R: VaultRepository>(
+        repo: R,
+    ) -> RevokeKeySlot<R, MockEtagGenerator, FixedClock, MockUsageRepository> {
+        with_quotas(
+            repo,
+            Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited()),
+        )
+    }
+
+    fn with_quotas<R: VaultRepository>(
+        repo: R,
+        quotas: Quotas<MockUsageRepository>,
+    ) -> RevokeKeySlot<R, MockEtagGenerator, FixedClock, MockUsageRepository> {
+        RevokeKeySlot::new(
+            repo,
+            etag_gen(),
+            FixedClock(now()),
+            RetentionPolicy::keep_all(),
+            quotas,
+        )
+    }
+
+    fn over_quota() -> Quotas<MockUsageRepository> {
+        let mut usage = MockUsageRepository::new();
+        usage.expect_usage().returning(|_| {
+            Box::pin(async {
+                Ok(StorageUsage {
+                    vaults: 1,
+                    vault_bytes: 56,
+                    history_bytes: 900,
+                    attachment_bytes: 0,
+                })
+            })
+        });
+        let quota = Quota {
+            max_total_bytes: Some(1000),
+            ..Quota::unlimited()
+        };
+
+        Quotas::new(usage, QuotaPolicy::new(quota, []).unwrap())
+    }
+
+    #[tokio::test]
+    async fn removes_slot() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault(vec![master_password(), device()]);
+        repo.create(&vault).await.unwrap();
+
+        let (etag, revision) = usecase(repo.clone())
+            .execute(&user(), &vault.id, vault.etag.clone(), device().id)
+            .await
+            .unwrap();
+
+        assert_eq!(etag.0, "etag-2");
+        assert_eq!(revision, 1);
+
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.package.header.key_slots, vec![master_password()]);
+        assert_eq!(stored.package.blob, vault.package.blob);
+    }
+
+    #[tokio::test]
+    async fn last_slot_cannot_be_revoked() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault(vec![master_password()]);
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let result = usecase(repo)
+            .execute(
+                &user(),
+                &vault_id,
+                Etag::new("etag-1").unwrap(),
+                master_password().id,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "key_slots",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_if_slot_missing() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault(vec![master_password()]);
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let result = usecase(repo)
+            .execute(
+                &user(),
+                &vault_id,
+                Etag::new("etag-1").unwrap(),
+                device().id,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::NotFound {
+                resource: Resource::KeySlot,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_conflict_if_etag_mismatch() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault(vec![master_password(), device()]);
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let result = usecase(repo)
+            .execute(&user(), &vault_id, Etag::new("stale").unwrap(), device().id)
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn refuses_a_revocation_whose_archived_revision_exceeds_the_quota() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault(vec![master_password(), device()]);
+        repo.create(&vault).await.unwrap();
+
+        let err = with_quotas(repo.clone(), over_quota())
+            .execute(&user(), &vault.id, vault.etag.clone(), device().id)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::QuotaExceeded {
+                limit: QuotaLimit::TotalBytes,
+                ..
+            }
+        ));
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.package.header.key_slots.len(), 2);
+    }
+}
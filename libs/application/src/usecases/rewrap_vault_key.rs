@@ -0,0 +1,364 @@
+use auth::domain::models::Identity;
+use domain::vault::{Etag, KdfPolicy, RetentionPolicy, VaultHeader, VaultId};
+use ports::{
+    clock::Clock, etag::EtagGenerator, usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+    quota::Quotas,
+    retention::prune_history,
+};
+
+/// Replaces the header of a vault (salt, KDF parameters and wrapped vault
+/// key) while keeping its ciphertext, so a master-password change does not
+/// require uploading the whole vault again.
+pub struct RewrapVaultKey<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    etag_generator: E,
+    clock: C,
+    retention: RetentionPolicy,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, E, C, U> RewrapVaultKey<R, E, C, U>
+where
+    R: VaultRepository,
+    E: EtagGenerator,
+    C: Clock,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        etag_generator: E,
+        clock: C,
+        retention: RetentionPolicy,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            etag_generator,
+            clock,
+            retention,
+            kdf_policy,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        vault_id: &VaultId,
+        expected_etag: Etag,
+        header: VaultHeader,
+    ) -> Result<(Etag, u64), AppError> {
+        let owner_id = owner_of(identity)?;
+        self.kdf_policy.check_header(&header)?;
+
+        let existing = self
+            .vault_repository
+            .find(&owner_id, vault_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Vault,
+                id: Some(vault_id.0.to_string()),
+            })?;
+
+        let package = existing.package.with_header(header)?;
+        let new_etag = self
+            .etag_generator
+            .generate(existing.revision.next(), &package);
+
+        if new_etag == existing.etag && expected_etag == existing.etag {
+            return Ok((existing.etag, existing.revision.0));
+        }
+
+        // As for a new slot, the replaced revision is archived whole.
+        let package_bytes = package.byte_size();
+        let now = self.clock.now();
+        let updated = existing.update(&expected_etag, now, new_etag, package)?;
+
+        self.quotas
+            .check_bytes(identity, &owner_id, package_bytes)
+            .await?;
+
+        self.vault_repository
+            .update_if_match(&updated, &expected_etag)
+            .await?;
+        prune_history(&self.vault_repository, &self.retention, &updated.id, now).await;
+
+        Ok((updated.etag, updated.revision.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use domain::{
+        quota::{Quota, QuotaLimit, QuotaPolicy, StorageUsage},
+        vault::{
+            CryptoVersion, Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, RetentionPolicy,
+            Vault, VaultHeader, VaultId, VaultPackage,
+        },
+    };
+    use memory_storage::InMemoryVaultRepository;
+    use ports::{
+        clock::FixedClock,
+        etag::MockEtagGenerator,
+        usage_repository::MockUsageRepository,
+        vault_repository::{MockVaultRepository, VaultRepository},
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{self, now, owner, user, valid_package},
+        quota::Quotas,
+        usecases::rewrap_vault_key::RewrapVaultKey,
+    };
+
+    fn header(salt: u8) -> VaultHeader {
+        let mut header = fixtures::header();
+        let slot = &mut header.key_slots[0];
+        if let KeySlotKind::MasterPassword { kdf } = &mut slot.kind {
+            kdf.salt = vec![salt; 16];
+        }
+        slot.wrapped_vault_key = vec![salt; 72];
+        header
+    }
+
+    fn existing_vault() -> Vault {
+        Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner(),
+            now() - Duration::days(1),
+            Etag::new("etag-1").unwrap(),
+            VaultPackage {
+                header: header(1),
+                ..valid_package()
+            },
+        )
+        .unwrap()
+    }
+
+    fn usecase<R: VaultRepository>(
+        repo: R,
+        etag_gen: MockEtagGenerator,
+    ) -> RewrapVaultKey<R, MockEtagGenerator, FixedClock, MockUsageRepository> {
+        with_quotas(
+            repo,
+            etag_gen,
+            Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited()),
+        )
+    }
+
+    fn with_quotas<R: VaultRepository>(
+        repo: R,
+        etag_gen: MockEtagGenerator,
+        quotas: Quotas<MockUsageRepository>,
+    ) -> RewrapVaultKey<R, MockEtagGenerator, FixedClock, MockUsageRepository> {
+        RewrapVaultKey::new(
+            repo,
+            etag_gen,
+            FixedClock(now()),
+            RetentionPolicy::keep_all(),
+            KdfPolicy::default(),
+            quotas,
+        )
+    }
+
+    fn over_quota() -> Quotas<MockUsageRepository> {
+        let mut usage = MockUsageRepository::new();
+        usage.expect_usage().returning(|_| {
+            Box::pin(async {
+                Ok(StorageUsage {
+                    vaults: 1,
+                    vault_bytes: 56,
+                    history_bytes: 900,
+                    attachment_bytes: 0,
+                })
+            })
+        });
+        let quota = Quota {
+            max_total_bytes: Some(1000),
+            ..Quota::unlimited()
+        };
+
+        Quotas::new(usage, QuotaPolicy::new(quota, []).unwrap())
+    }
+
+    #[tokio::test]
+    async fn replaces_header_and_keeps_ciphertext() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        repo.create(&vault).await.unwrap();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let (etag, revision) = usecase(repo.clone(), etag_gen)
+            .execute(&user(), &vault.id, vault.etag.clone(), header(2))
+            .await
+            .unwrap();
+
+        assert_eq!(etag.0, "etag-2");
+        assert_eq!(revision, 1);
+
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.package.header.key_slots, header(2).key_slots);
+        assert_eq!(stored.package.blob, vault.package.blob);
+        assert_eq!(repo.list_revisions(&vault.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn returns_conflict_if_etag_mismatch() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let result = usecase(repo, etag_gen)
+            .execute(&user(), &vault_id, Etag::new("stale").unwrap(), header(2))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn crypto_version_cannot_change() {
+        let mut repo = MockVaultRepository::new();
+        let vault = existing_vault();
+        let vault_id = vault.id;
+        repo.expect_find().returning(move |_, _| {
+            let v = vault.clone();
+            Box::pin(async move { Ok(Some(v)) })
+        });
+        repo.expect_update_if_match().never();
+
+        let mut header = header(2);
+        header.crypto_version = CryptoVersion::V3;
+        header.key_slots[0].wrapped_vault_key = vec![2; 60];
+
+        let result = usecase(repo, MockEtagGenerator::new())
+            .execute(&user(), &vault_id, Etag::new("etag-1").unwrap(), header)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "crypto_version",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_kdf_below_policy_minimum() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find().never();
+
+        let mut header = header(2);
+        header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![2; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+
+        let result = usecase(repo, MockEtagGenerator::new())
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                header,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_not_found_if_vault_missing() {
+        let mut repo = MockVaultRepository::new();
+        repo.expect_find()
+            .returning(|_, _| Box::pin(async { Ok(None) }));
+
+        let result = usecase(repo, MockEtagGenerator::new())
+            .execute(
+                &user(),
+                &VaultId(Uuid::new_v4()),
+                Etag::new("etag-1").unwrap(),
+                header(2),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn refuses_a_rewrap_whose_archived_revision_exceeds_the_quota() {
+        let repo = InMemoryVaultRepository::new();
+        let vault = existing_vault();
+        repo.create(&vault).await.unwrap();
+
+        let mut etag_gen = MockEtagGenerator::new();
+        etag_gen
+            .expect_generate()
+            .returning(|_, _| Etag::new("etag-2").unwrap());
+
+        let err = with_quotas(repo.clone(), etag_gen, over_quota())
+            .execute(&user(), &vault.id, vault.etag.clone(), header(7))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::QuotaExceeded {
+                limit: QuotaLimit::TotalBytes,
+                allowed: 1000,
+                requested: 1012,
+            }
+        ));
+        let stored = repo
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.etag, vault.etag);
+    }
+}
@@ -0,0 +1,174 @@
+use auth::domain::models::Identity;
+use domain::attachment::Attachment;
+use ports::{
+    attachment_repository::AttachmentRepository, clock::Clock, id::IdGenerator,
+    usage_repository::UsageRepository,
+};
+
+use crate::{errors::AppError, ownership::owner_of, quota::Quotas};
+
+/// Opens an upload: the client announces the encrypted size and how it is
+/// chunked, then sends the chunks in any order.
+///
+/// The announced size counts against the owner's quota from now on, so an
+/// upload that would not fit is refused before any chunk is sent.
+pub struct StartAttachmentUpload<T, C, I, U>
+where
+    T: AttachmentRepository,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    attachment_repository: T,
+    clock: C,
+    id_generator: I,
+    quotas: Quotas<U>,
+}
+
+impl<T, C, I, U> StartAttachmentUpload<T, C, I, U>
+where
+    T: AttachmentRepository,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    pub fn new(attachment_repository: T, clock: C, id_generator: I, quotas: Quotas<U>) -> Self {
+        Self {
+            attachment_repository,
+            clock,
+            id_generator,
+            quotas,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        size: u64,
+        chunk_size: u32,
+    ) -> Result<Attachment, AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let attachment = Attachment::new(
+            self.id_generator.attachment_id(),
+            owner_id,
+            size,
+            chunk_size,
+            self.clock.now(),
+        )?;
+        self.quotas
+            .check_bytes(identity, &attachment.owner_id, size)
+            .await?;
+
+        self.attachment_repository.create(&attachment).await?;
+
+        Ok(attachment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::AttachmentId,
+        quota::{Quota, QuotaLimit, QuotaPolicy, StorageUsage},
+    };
+    use ports::{
+        attachment_repository::MockAttachmentRepository, clock::FixedClock, id::MockIdGenerator,
+        usage_repository::MockUsageRepository,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::AppError,
+        fixtures::{now, user},
+        quota::Quotas,
+        usecases::start_attachment_upload::StartAttachmentUpload,
+    };
+
+    fn ids() -> MockIdGenerator {
+        let mut ids = MockIdGenerator::new();
+        ids.expect_attachment_id()
+            .returning(|| AttachmentId(Uuid::from_u128(7)));
+        ids
+    }
+
+    fn unlimited() -> Quotas<MockUsageRepository> {
+        Quotas::new(MockUsageRepository::new(), QuotaPolicy::unlimited())
+    }
+
+    #[tokio::test]
+    async fn records_an_open_upload_for_the_caller() {
+        let mut repo = MockAttachmentRepository::new();
+        repo.expect_create()
+            .withf(|a| {
+                a.id == AttachmentId(Uuid::from_u128(7))
+                    && a.owner_id.0.ends_with("|user1")
+                    && a.created_at == now()
+                    && !a.is_complete()
+            })
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(()) }));
+
+        let attachment = StartAttachmentUpload::new(repo, FixedClock(now()), ids(), unlimited())
+            .execute(&user(), 2500, 1024)
+            .await
+            .unwrap();
+
+        assert_eq!(attachment.chunk_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_chunks_before_touching_storage() {
+        let mut repo = MockAttachmentRepository::new();
+        repo.expect_create().never();
+
+        let err = StartAttachmentUpload::new(repo, FixedClock(now()), ids(), unlimited())
+            .execute(&user(), 10, u32::MAX)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::Validation {
+                field: "chunk_size",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_uploads_that_would_exceed_the_quota() {
+        let mut repo = MockAttachmentRepository::new();
+        repo.expect_create().never();
+        let mut usage = MockUsageRepository::new();
+        usage.expect_usage().returning(|_| {
+            Box::pin(async {
+                Ok(StorageUsage {
+                    vaults: 1,
+                    vault_bytes: 600,
+                    history_bytes: 0,
+                    attachment_bytes: 0,
+                })
+            })
+        });
+        let quota = Quota {
+            max_total_bytes: Some(1000),
+            ..Quota::unlimited()
+        };
+        let quotas = Quotas::new(usage, QuotaPolicy::new(quota, []).unwrap());
+
+        let err = StartAttachmentUpload::new(repo, FixedClock(now()), ids(), quotas)
+            .execute(&user(), 500, 1024)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::QuotaExceeded {
+                limit: QuotaLimit::TotalBytes,
+                allowed: 1000,
+                requested: 1100,
+            }
+        ));
+    }
+}
@@ -0,0 +1,557 @@
+use std::mem;
+
+use auth::domain::models::Identity;
+use domain::{
+    DomainError,
+    attachment::{Attachment, AttachmentId, MAX_CHUNK_SIZE},
+    vault::{
+        Etag, KdfPolicy, STORED_CIPHERTEXT_SHA256_LEN, StoredCiphertext, VaultId, VaultPackage,
+    },
+};
+use ports::{
+    attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::Clock,
+    id::IdGenerator, usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    errors::{AppError, ConflictKind, Resource},
+    ownership::owner_of,
+    quota::{Growth, PackageSize, Quotas},
+};
+
+/// The vault a streamed ciphertext is for.
+#[derive(Debug, Clone, Copy)]
+pub enum CiphertextTarget<'a> {
+    /// A vault still to be created.
+    NewVault,
+    /// An existing vault, to be replaced if it is still at `expected_etag`.
+    Vault {
+        vault_id: &'a VaultId,
+        expected_etag: &'a Etag,
+    },
+}
+
+/// A ciphertext being streamed into the blob store. Holds at most one chunk
+/// in memory, however large the ciphertext.
+pub struct CiphertextUpload {
+    attachment: Attachment,
+    hasher: Sha256,
+    buffer: Vec<u8>,
+    next_index: u32,
+    received: u64,
+}
+
+impl CiphertextUpload {
+    pub fn attachment(&self) -> &Attachment {
+        &self.attachment
+    }
+}
+
+/// Streams the blob ciphertext of a package into the blob store, as the
+/// chunks of an attachment owned by the vault owner, and hashes it on the
+/// way so that the etag never needs the whole ciphertext.
+///
+/// The returned [`StoredCiphertext`] goes into the package that
+/// [`CreateVault`](super::create_vault::CreateVault) or
+/// [`PutVault`](super::put_vault::PutVault) then saves. A ciphertext whose
+/// package is never saved, or is refused, is left to
+/// [`CollectOrphanedAttachments`](super::collect_orphaned_attachments::CollectOrphanedAttachments)
+/// like any abandoned upload, unless the caller [discards](Self::discard)
+/// it first.
+///
+/// What can be checked before the ciphertext arrives is: the owner, the KDF
+/// policy, the package shape, the quotas and, for an existing vault, its
+/// etag. The use case saving the package checks again, as the vault may move
+/// while the ciphertext streams in.
+pub struct StoreVaultCiphertext<R, T, B, C, I, U>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    vault_repository: R,
+    attachment_repository: T,
+    blob_store: B,
+    clock: C,
+    id_generator: I,
+    kdf_policy: KdfPolicy,
+    quotas: Quotas<U>,
+}
+
+impl<R, T, B, C, I, U> StoreVaultCiphertext<R, T, B, C, I, U>
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    B: BlobStore,
+    C: Clock,
+    I: IdGenerator,
+    U: UsageRepository,
+{
+    pub fn new(
+        vault_repository: R,
+        attachment_repository: T,
+        blob_store: B,
+        clock: C,
+        id_generator: I,
+        kdf_policy: KdfPolicy,
+        quotas: Quotas<U>,
+    ) -> Self {
+        Self {
+            vault_repository,
+            attachment_repository,
+            blob_store,
+            clock,
+            id_generator,
+            kdf_policy,
+            quotas,
+        }
+    }
+
+    /// Opens the upload of a `size`-byte ciphertext for `package`, whose
+    /// blob ciphertext must be empty. The package and its target are checked
+    /// before any byte is stored.
+    pub async fn start(
+        &self,
+        identity: &Identity,
+        target: CiphertextTarget<'_>,
+        package: &VaultPackage,
+        size: u64,
+    ) -> Result<CiphertextUpload, AppError> {
+        let owner_id = owner_of(identity)?;
+        self.kdf_policy.check_header(&package.header)?;
+
+        if let CiphertextTarget::Vault {
+            vault_id,
+            expected_etag,
+        } = target
+        {
+            let current = self
+                .vault_repository
+                .find_version(&owner_id, vault_id)
+                .await?
+                .ok_or(AppError::NotFound {
+                    resource: Resource::Vault,
+                    id: Some(vault_id.0.to_string()),
+                })?;
+
+            if current.etag != *expected_etag {
+                return Err(AppError::Conflict {
+                    kind: ConflictKind::Concurrency,
+                    resource: Resource::Vault,
+                    id: Some(vault_id.0.to_string()),
+                });
+            }
+        }
+
+        if !package.blob.ciphertext.is_empty() {
+            return Err(DomainError::Validation {
+                field: "blob.ciphertext",
+                message: "must be empty when the ciphertext is streamed".into(),
+            }
+            .into());
+        }
+        let announced = package.clone().with_stored_ciphertext(StoredCiphertext {
+            attachment_id: AttachmentId(Uuid::nil()),
+            size,
+            sha256: vec![0; STORED_CIPHERTEXT_SHA256_LEN],
+        });
+        announced.validate()?;
+
+        let attachment = Attachment::new(
+            self.id_generator.attachment_id(),
+            owner_id,
+            size,
+            MAX_CHUNK_SIZE,
+            self.clock.now(),
+        )?;
+        self.quotas
+            .check_package(
+                identity,
+                &attachment.owner_id,
+                PackageSize {
+                    full: announced.full_byte_size(),
+                    inline: size,
+                },
+                Growth::Update,
+            )
+            .await?;
+
+        self.attachment_repository.create(&attachment).await?;
+
+        Ok(CiphertextUpload {
+            attachment,
+            hasher: Sha256::new(),
+            buffer: Vec::with_capacity(MAX_CHUNK_SIZE as usize),
+            next_index: 0,
+            received: 0,
+        })
+    }
+
+    /// Appends `bytes` to the ciphertext, storing every chunk as it fills.
+    pub async fn write(&self, upload: &mut CiphertextUpload, bytes: &[u8]) -> Result<(), AppError> {
+        let size = upload.attachment.size;
+        if upload.received + bytes.len() as u64 > size {
+            return Err(DomainError::Validation {
+                field: "ciphertext",
+                message: format!("longer than the announced {size} bytes"),
+            }
+            .into());
+        }
+
+        upload.hasher.update(bytes);
+        upload.received += bytes.len() as u64;
+
+        let chunk_size = upload.attachment.chunk_size as usize;
+        let mut bytes = bytes;
+        while !bytes.is_empty() {
+            let take = bytes.len().min(chunk_size - upload.buffer.len());
+            upload.buffer.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+
+            if upload.buffer.len() == chunk_size {
+                self.flush(upload).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores the last chunk and completes the upload once every announced
+    /// byte was written.
+    pub async fn finish(&self, mut upload: CiphertextUpload) -> Result<StoredCiphertext, AppError> {
+        let size = upload.attachment.size;
+        if upload.received != size {
+            return Err(DomainError::Validation {
+                field: "ciphertext",
+                message: format!("{} bytes sent, {size} announced", upload.received),
+            }
+            .into());
+        }
+
+        if !upload.buffer.is_empty() {
+            self.flush(&mut upload).await?;
+        }
+
+        let received: Vec<u32> = (0..upload.next_index).collect();
+        let completed = upload.attachment.complete(&received, self.clock.now())?;
+        self.attachment_repository
+            .mark_completed(&completed)
+            .await?;
+
+        Ok(StoredCiphertext {
+            attachment_id: completed.id,
+            size,
+            sha256: upload.hasher.finalize().to_vec(),
+        })
+    }
+
+    /// Deletes a ciphertext whose package was not saved, complete or not.
+    ///
+    /// Runs once the request has already failed or been answered, so a
+    /// failure here is only logged: the attachment is then left to the
+    /// collector.
+    pub async fn discard(&self, attachment_id: &AttachmentId) {
+        let result = async {
+            self.blob_store.delete(attachment_id).await?;
+            self.attachment_repository.delete(&[*attachment_id]).await?;
+            Ok::<_, AppError>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            warn!("failed to discard attachment {}: {}", attachment_id.0, e);
+        }
+    }
+
+    async fn flush(&self, upload: &mut CiphertextUpload) -> Result<(), AppError> {
+        let chunk = mem::replace(
+            &mut upload.buffer,
+            Vec::with_capacity(upload.attachment.chunk_size as usize),
+        );
+        self.blob_store
+            .put_chunk(&upload.attachment.id, upload.next_index, chunk)
+            .await?;
+        upload.next_index += 1;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::{AttachmentId, MAX_CHUNK_SIZE},
+        quota::{Quota, QuotaLimit, QuotaPolicy},
+        vault::{Etag, KdfParams, KdfPolicy, KdfSpec, KeySlotKind, OwnerSub, Vault, VaultId},
+    };
+    use memory_storage::{
+        InMemoryAttachmentRepository, InMemoryBlobStore, InMemoryVaultRepository,
+    };
+    use ports::{
+        attachment_repository::AttachmentRepository, blob_store::BlobStore, clock::FixedClock,
+        id::MockIdGenerator, usage_repository::MockUsageRepository,
+        vault_repository::VaultRepository,
+    };
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    use crate::{
+        errors::{AppError, ConflictKind},
+        fixtures::{now, skeleton, user},
+        ownership::owner_of,
+        quota::Quotas,
+        usecases::store_vault_ciphertext::{CiphertextTarget, StoreVaultCiphertext},
+    };
+
+    type Usecase = StoreVaultCiphertext<
+        InMemoryVaultRepository,
+        InMemoryAttachmentRepository,
+        InMemoryBlobStore,
+        FixedClock,
+        MockIdGenerator,
+        MockUsageRepository,
+    >;
+
+    fn id() -> AttachmentId {
+        AttachmentId(Uuid::from_u128(7))
+    }
+
+    fn usecase(
+        attachments: &InMemoryAttachmentRepository,
+        blobs: &InMemoryBlobStore,
+        policy: QuotaPolicy,
+    ) -> Usecase {
+        with_vaults(&InMemoryVaultRepository::new(), attachments, blobs, policy)
+    }
+
+    fn with_vaults(
+        vaults: &InMemoryVaultRepository,
+        attachments: &InMemoryAttachmentRepository,
+        blobs: &InMemoryBlobStore,
+        policy: QuotaPolicy,
+    ) -> Usecase {
+        let mut ids = MockIdGenerator::new();
+        ids.expect_attachment_id().returning(id);
+
+        StoreVaultCiphertext::new(
+            vaults.clone(),
+            attachments.clone(),
+            blobs.clone(),
+            FixedClock(now()),
+            ids,
+            KdfPolicy::default(),
+            Quotas::new(MockUsageRepository::new(), policy),
+        )
+    }
+
+    #[tokio::test]
+    async fn stores_the_stream_in_chunks_and_hashes_it() {
+        let attachments = InMemoryAttachmentRepository::new();
+        let blobs = InMemoryBlobStore::new();
+        let usecase = usecase(&attachments, &blobs, QuotaPolicy::unlimited());
+        let ciphertext: Vec<u8> = (0..MAX_CHUNK_SIZE + 100).map(|i| i as u8).collect();
+
+        let mut upload = usecase
+            .start(
+                &user(),
+                CiphertextTarget::NewVault,
+                &skeleton(),
+                ciphertext.len() as u64,
+            )
+            .await
+            .unwrap();
+        for piece in ciphertext.chunks(64 * 1024 + 1) {
+            usecase.write(&mut upload, piece).await.unwrap();
+        }
+        let stored = usecase.finish(upload).await.unwrap();
+
+        assert_eq!(stored.attachment_id, id());
+        assert_eq!(stored.size, ciphertext.len() as u64);
+        assert_eq!(stored.sha256, Sha256::digest(&ciphertext).to_vec());
+        assert_eq!(blobs.list_chunks(&id()).await.unwrap(), vec![0, 1]);
+        assert_eq!(
+            blobs.get_chunk(&id(), 1).await.unwrap().unwrap(),
+            ciphertext[MAX_CHUNK_SIZE as usize..]
+        );
+
+        let owner_id = owner_of(&user()).unwrap();
+        let attachment = attachments.find(&owner_id, &id()).await.unwrap().unwrap();
+        assert!(attachment.is_complete());
+        assert!(skeleton().with_stored_ciphertext(stored).validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn refuses_more_or_fewer_bytes_than_announced() {
+        let usecase = usecase(
+            &InMemoryAttachmentRepository::new(),
+            &InMemoryBlobStore::new(),
+            QuotaPolicy::unlimited(),
+        );
+
+        let mut upload = usecase
+            .start(&user(), CiphertextTarget::NewVault, &skeleton(), 32)
+            .await
+            .unwrap();
+        usecase.write(&mut upload, &[4; 30]).await.unwrap();
+        assert!(matches!(
+            usecase.write(&mut upload, &[4; 3]).await,
+            Err(AppError::Validation {
+                field: "ciphertext",
+                ..
+            })
+        ));
+        assert!(matches!(
+            usecase.finish(upload).await,
+            Err(AppError::Validation {
+                field: "ciphertext",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn checks_the_package_before_storing_anything() {
+        let attachments = InMemoryAttachmentRepository::new();
+        let owner_id = owner_of(&user()).unwrap();
+        let quota = Quota {
+            max_package_bytes: Some(1000),
+            ..Quota::unlimited()
+        };
+        let usecase = usecase(
+            &attachments,
+            &InMemoryBlobStore::new(),
+            QuotaPolicy::new(quota, []).unwrap(),
+        );
+
+        assert!(matches!(
+            usecase
+                .start(&user(), CiphertextTarget::NewVault, &skeleton(), 8)
+                .await,
+            Err(AppError::Validation {
+                field: "stored_ciphertext.size",
+                ..
+            })
+        ));
+
+        let result = usecase
+            .start(&user(), CiphertextTarget::NewVault, &skeleton(), 1000)
+            .await;
+        assert!(matches!(
+            result,
+            Err(AppError::QuotaExceeded {
+                limit: QuotaLimit::PackageBytes,
+                allowed: 1000,
+                requested: 1024,
+            })
+        ));
+        assert!(attachments.find(&owner_id, &id()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn checks_the_target_vault_before_storing_anything() {
+        let vaults = InMemoryVaultRepository::new();
+        let attachments = InMemoryAttachmentRepository::new();
+        let owner_id = owner_of(&user()).unwrap();
+        let mut package = skeleton();
+        package.blob.ciphertext = vec![4; 32];
+        let vault = Vault::new(
+            VaultId(Uuid::new_v4()),
+            owner_id.clone(),
+            now(),
+            Etag::new("etag-1").unwrap(),
+            package,
+        )
+        .unwrap();
+        vaults.create(&vault).await.unwrap();
+        let usecase = with_vaults(
+            &vaults,
+            &attachments,
+            &InMemoryBlobStore::new(),
+            QuotaPolicy::unlimited(),
+        );
+
+        let stale = Etag::new("etag-0").unwrap();
+        let result = usecase
+            .start(
+                &user(),
+                CiphertextTarget::Vault {
+                    vault_id: &vault.id,
+                    expected_etag: &stale,
+                },
+                &skeleton(),
+                32,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(AppError::Conflict {
+                kind: ConflictKind::Concurrency,
+                ..
+            })
+        ));
+
+        let missing = VaultId(Uuid::new_v4());
+        let result = usecase
+            .start(
+                &user(),
+                CiphertextTarget::Vault {
+                    vault_id: &missing,
+                    expected_etag: &vault.etag,
+                },
+                &skeleton(),
+                32,
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+
+        let mut weak = skeleton();
+        weak.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Argon2id {
+                    m_kib: 16 * 1024,
+                    t: 3,
+                    p: 1,
+                },
+            },
+        };
+        let result = usecase
+            .start(&user(), CiphertextTarget::NewVault, &weak, 32)
+            .await;
+        assert!(matches!(
+            result,
+            Err(AppError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+
+        assert!(attachments.find(&owner_id, &id()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn discard_drops_the_chunks_and_the_record() {
+        let attachments = InMemoryAttachmentRepository::new();
+        let blobs = InMemoryBlobStore::new();
+        let usecase = usecase(&attachments, &blobs, QuotaPolicy::unlimited());
+
+        let mut upload = usecase
+            .start(&user(), CiphertextTarget::NewVault, &skeleton(), 32)
+            .await
+            .unwrap();
+        usecase.write(&mut upload, &[4; 32]).await.unwrap();
+        let stored = usecase.finish(upload).await.unwrap();
+
+        usecase.discard(&stored.attachment_id).await;
+
+        let owner_id: OwnerSub = owner_of(&user()).unwrap();
+        assert!(attachments.find(&owner_id, &id()).await.unwrap().is_none());
+        assert!(blobs.list_chunks(&id()).await.unwrap().is_empty());
+    }
+}
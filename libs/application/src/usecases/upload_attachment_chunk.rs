@@ -0,0 +1,134 @@
+use auth::domain::models::Identity;
+use domain::attachment::AttachmentId;
+use ports::{attachment_repository::AttachmentRepository, blob_store::BlobStore};
+
+use crate::{
+    errors::{AppError, Resource},
+    ownership::owner_of,
+};
+
+/// Stores one chunk of an open upload. Sending a chunk again replaces it, so
+/// an interrupted upload resumes by resending whatever is missing.
+pub struct UploadAttachmentChunk<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    attachment_repository: T,
+    blob_store: B,
+}
+
+impl<T, B> UploadAttachmentChunk<T, B>
+where
+    T: AttachmentRepository,
+    B: BlobStore,
+{
+    pub fn new(attachment_repository: T, blob_store: B) -> Self {
+        Self {
+            attachment_repository,
+            blob_store,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        identity: &Identity,
+        attachment_id: &AttachmentId,
+        index: u32,
+        bytes: Vec<u8>,
+    ) -> Result<(), AppError> {
+        let owner_id = owner_of(identity)?;
+
+        let attachment = self
+            .attachment_repository
+            .find(&owner_id, attachment_id)
+            .await?
+            .ok_or(AppError::NotFound {
+                resource: Resource::Attachment,
+                id: Some(attachment_id.0.to_string()),
+            })?;
+
+        attachment.accept_chunk(index, bytes.len())?;
+        self.blob_store
+            .put_chunk(attachment_id, index, bytes)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::{Attachment, AttachmentId},
+        vault::OwnerSub,
+    };
+    use memory_storage::{InMemoryAttachmentRepository, InMemoryBlobStore};
+    use ports::{
+        attachment_repository::AttachmentRepository, blob_store::BlobStore,
+        blob_store::MockBlobStore,
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        errors::{AppError, Resource},
+        fixtures::{now, user},
+        ownership::owner_of,
+        usecases::upload_attachment_chunk::UploadAttachmentChunk,
+    };
+
+    async fn repo_with(owner_id: OwnerSub) -> (InMemoryAttachmentRepository, AttachmentId) {
+        let repo = InMemoryAttachmentRepository::new();
+        let id = AttachmentId(Uuid::from_u128(7));
+        repo.create(&Attachment::new(id, owner_id, 2500, 1024, now()).unwrap())
+            .await
+            .unwrap();
+
+        (repo, id)
+    }
+
+    #[tokio::test]
+    async fn stores_a_chunk_of_the_expected_length() {
+        let (repo, id) = repo_with(owner_of(&user()).unwrap()).await;
+        let blobs = InMemoryBlobStore::new();
+
+        UploadAttachmentChunk::new(repo, blobs.clone())
+            .execute(&user(), &id, 2, vec![9; 452])
+            .await
+            .unwrap();
+
+        assert_eq!(blobs.get_chunk(&id, 2).await.unwrap(), Some(vec![9; 452]));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_short_chunk_before_storing_it() {
+        let (repo, id) = repo_with(owner_of(&user()).unwrap()).await;
+        let mut blobs = MockBlobStore::new();
+        blobs.expect_put_chunk().never();
+
+        let err = UploadAttachmentChunk::new(repo, blobs)
+            .execute(&user(), &id, 0, vec![9; 1000])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Validation { field: "chunk", .. }));
+    }
+
+    #[tokio::test]
+    async fn attachments_of_others_are_not_found() {
+        let (repo, id) = repo_with(OwnerSub::new("someone|else").unwrap()).await;
+
+        let err = UploadAttachmentChunk::new(repo, InMemoryBlobStore::new())
+            .execute(&user(), &id, 0, vec![9; 1024])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::NotFound {
+                resource: Resource::Attachment,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::domain::{
+    models::{AuthError, Identity, Token},
+    ports::{Authenticator, IdentityResolver, TokenVerifier},
+};
+
+/// Verifies the bearer token, then resolves the identity from its claims.
+pub struct TokenAuthenticator<V, R>
+where
+    V: TokenVerifier,
+    R: IdentityResolver,
+{
+    verifier: V,
+    resolver: R,
+}
+
+impl<V, R> TokenAuthenticator<V, R>
+where
+    V: TokenVerifier,
+    R: IdentityResolver,
+{
+    pub fn new(verifier: V, resolver: R) -> Self {
+        Self { verifier, resolver }
+    }
+}
+
+impl<V, R> Authenticator for TokenAuthenticator<V, R>
+where
+    V: TokenVerifier,
+    R: IdentityResolver,
+{
+    async fn authenticate(&self, token: &Token) -> Result<Identity, AuthError> {
+        let jwt = self.verifier.verify(token).await?;
+
+        self.resolver.resolve(jwt.claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::domain::{
+        authenticator::TokenAuthenticator,
+        models::{AuthError, Claims, Jwt, RoleMapping, Token},
+        ports::{Authenticator, MockIdentityResolver, MockTokenVerifier},
+    };
+
+    fn claims() -> Claims {
+        serde_json::from_value(json!({
+            "sub": "user-123",
+            "iss": "http://localhost:8000/realms/ferrispass",
+            "scope": "openid",
+            "preferred_username": "johndoe",
+            "realm_access": { "roles": ["user"] },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn resolves_identity_from_verified_claims() {
+        let mut verifier = MockTokenVerifier::new();
+
+        verifier.expect_verify().returning(|token| {
+            let jwt = Jwt {
+                claims: claims(),
+                token: token.clone(),
+            };
+            Box::pin(async move { Ok(jwt) })
+        });
+
+        let authenticator = TokenAuthenticator::new(verifier, RoleMapping::default());
+
+        let identity = authenticator
+            .authenticate(&Token::new("a.b.c"))
+            .await
+            .unwrap();
+
+        assert_eq!(identity.id(), "user-123");
+        assert!(identity.has_role("user"));
+    }
+
+    #[tokio::test]
+    async fn stops_when_verification_fails() {
+        let mut verifier = MockTokenVerifier::new();
+        let mut resolver = MockIdentityResolver::new();
+
+        verifier
+            .expect_verify()
+            .returning(|_| Box::pin(async { Err(AuthError::Expired) }));
+        resolver.expect_resolve().never();
+
+        let authenticator = TokenAuthenticator::new(verifier, resolver);
+
+        assert!(matches!(
+            authenticator.authenticate(&Token::new("a.b.c")).await,
+            Err(AuthError::Expired)
+        ));
+    }
+}
@@ -1,2 +1,3 @@
+pub mod authenticator;
 pub mod models;
 pub mod ports;
@@ -18,12 +18,23 @@ pub enum Audience {
     Multiple(Vec<String>),
 }
 
+impl Audience {
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::Single(a) => a == audience,
+            Audience::Multiple(all) => all.iter().any(|a| a == audience),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Subject,
     pub iss: String,
     pub aud: Option<Audience>,
     pub exp: Option<u64>,
+    pub nbf: Option<u64>,
+    pub iat: Option<u64>,
 
     pub email: Option<String>,
     pub email_verified: Option<bool>,
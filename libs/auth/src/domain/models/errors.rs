@@ -16,4 +16,16 @@ pub enum AuthError {
 
     #[error("token expired")]
     Expired,
+
+    #[error("token not yet valid")]
+    NotYetValid,
+
+    #[error("untrusted issuer: {issuer}")]
+    InvalidIssuer { issuer: String },
+
+    #[error("token audience does not include {expected}")]
+    InvalidAudience { expected: String },
+
+    #[error("missing claim: {claim}")]
+    MissingClaim { claim: &'static str },
 }
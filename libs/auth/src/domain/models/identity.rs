@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::domain::models::{Claims, Client, User};
+use crate::domain::models::{Claims, Client, RoleMapping, User};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Identity {
@@ -16,6 +16,14 @@ impl Identity {
         }
     }
 
+    /// The `iss` the identity was issued by; subjects are only unique per issuer.
+    pub fn issuer(&self) -> &str {
+        match self {
+            Identity::User(u) => &u.issuer,
+            Identity::Client(c) => &c.issuer,
+        }
+    }
+
     pub fn is_user(&self) -> bool {
         matches!(self, Identity::User(_))
     }
@@ -41,34 +49,43 @@ impl Identity {
     pub fn has_role(&self, role: &str) -> bool {
         self.roles().iter().any(|r| r == role)
     }
-}
 
-impl From<Claims> for Identity {
-    fn from(claims: Claims) -> Self {
+    pub fn from_claims(claims: Claims, roles: &RoleMapping) -> Self {
+        let roles = roles.extract(&claims);
+
         if let Some(client_id) = claims.client_id {
             Identity::Client(Client {
                 id: claims.sub.0,
+                issuer: claims.iss,
                 client_id,
-                roles: Vec::new(),
-                scopes: Vec::new(),
+                roles,
+                scopes: claims.scope.split_whitespace().map(String::from).collect(),
             })
         } else {
             Identity::User(User {
                 id: claims.sub.0,
+                issuer: claims.iss,
                 email: claims.email,
                 name: claims.name,
-                roles: Vec::new(),
+                roles,
                 username: claims.preferred_username,
             })
         }
     }
 }
 
+impl From<Claims> for Identity {
+    fn from(claims: Claims) -> Self {
+        Identity::from_claims(claims, &RoleMapping::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
 
     use crate::domain::models::{
+        RoleMapping,
         claims::{Audience, Claims},
         identity::Identity,
     };
@@ -81,6 +98,8 @@ mod tests {
             email: Some("john.doe@example.com".to_string()),
             email_verified: Some(true),
             exp: None,
+            nbf: None,
+            iat: None,
             name: Some("John Doe".to_string()),
             preferred_username: "johndoe".to_string(),
             given_name: Some("John".to_string()),
@@ -109,6 +128,8 @@ mod tests {
             email_verified: Some(false),
             name: None,
             exp: None,
+            nbf: None,
+            iat: None,
             preferred_username: "service-account-bot".to_string(),
             given_name: None,
             family_name: None,
@@ -165,8 +186,10 @@ mod tests {
         assert!(identity.is_user());
         assert!(!identity.is_client());
         assert_eq!(identity.id(), "user-123");
+        assert_eq!(identity.issuer(), "https://auth.ferriscord.com");
         assert_eq!(identity.username(), "johndoe");
-        assert!(identity.roles().is_empty());
+        assert_eq!(identity.roles(), ["user", "moderator"]);
+        assert!(identity.has_role("moderator"));
         assert!(!identity.has_role("admin"));
     }
 
@@ -179,7 +202,37 @@ mod tests {
         assert!(!identity.is_user());
         assert_eq!(identity.id(), "service-123");
         assert_eq!(identity.username(), "ferriscord-bot");
-        assert!(identity.roles().is_empty());
-        assert!(!identity.has_role("service"));
+        assert_eq!(identity.roles(), ["service", "bot"]);
+        assert!(identity.has_role("service"));
+        assert!(!identity.has_role("admin"));
+    }
+
+    #[test]
+    fn test_client_scopes_are_split_from_scope_claim() {
+        let claims = create_service_account_claims();
+
+        match Identity::from(claims) {
+            Identity::Client(client) => {
+                assert_eq!(
+                    client.scopes,
+                    vec!["admin:all", "read:users", "write:messages"]
+                );
+            }
+            Identity::User(_) => panic!("Expected Client, got User"),
+        }
+    }
+
+    #[test]
+    fn test_client_roles_from_resource_access() {
+        let mut claims = create_user_claims();
+        claims.extra.insert(
+            "resource_access".to_string(),
+            json!({ "ferriscord-api": { "roles": ["admin"] } }),
+        );
+
+        let identity = Identity::from_claims(claims, &RoleMapping::keycloak(["ferriscord-api"]));
+
+        assert!(identity.has_role("admin"));
+        assert!(identity.has_role("user"));
     }
 }
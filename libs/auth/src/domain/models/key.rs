@@ -0,0 +1,9 @@
+use jsonwebtoken::{Algorithm, DecodingKey};
+
+/// Public key a token signature is checked against.
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub kid: String,
+    pub alg: Algorithm,
+    pub key: DecodingKey,
+}
@@ -2,12 +2,18 @@ pub(crate) mod claims;
 pub(crate) mod client;
 pub(crate) mod errors;
 pub(crate) mod identity;
+pub(crate) mod key;
+pub(crate) mod roles;
 pub(crate) mod token;
 pub(crate) mod user;
+pub(crate) mod validation;
 
 pub use claims::*;
 pub use client::*;
 pub use errors::*;
 pub use identity::*;
+pub use key::*;
+pub use roles::*;
 pub use token::*;
 pub use user::*;
+pub use validation::*;
@@ -0,0 +1,166 @@
+use serde_json::Value;
+
+use crate::domain::{
+    models::{AuthError, Claims, Identity},
+    ports::IdentityResolver,
+};
+
+/// Location of a claim inside the token payload, one segment per object key.
+///
+/// Segments are kept apart rather than dot-joined because some IdPs use
+/// URL-namespaced claim names such as `https://example.com/roles`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimPath(pub Vec<String>);
+
+impl ClaimPath {
+    pub fn new(segments: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self(segments.into_iter().map(Into::into).collect())
+    }
+
+    /// Parses a dot-separated path such as `realm_access.roles`.
+    pub fn parse(path: &str) -> Self {
+        Self::new(path.split('.'))
+    }
+
+    fn resolve<'a>(&self, claims: &'a Claims) -> Option<&'a Value> {
+        let (first, rest) = self.0.split_first()?;
+
+        rest.iter()
+            .try_fold(claims.extra.get(first)?, |value, key| value.get(key))
+    }
+}
+
+/// Where roles are read from in the claims.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleMapping {
+    pub paths: Vec<ClaimPath>,
+}
+
+impl RoleMapping {
+    pub fn new(paths: impl IntoIterator<Item = ClaimPath>) -> Self {
+        Self {
+            paths: paths.into_iter().collect(),
+        }
+    }
+
+    /// Keycloak layout: realm roles plus the roles of each listed client
+    /// under `resource_access.<client>.roles`.
+    pub fn keycloak(clients: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut paths = vec![ClaimPath::parse("realm_access.roles")];
+
+        for client in clients {
+            let client: String = client.into();
+            paths.push(ClaimPath::new(["resource_access", &client, "roles"]));
+        }
+
+        Self { paths }
+    }
+
+    /// Collects roles from every configured path, without duplicates.
+    ///
+    /// A path may hold an array of strings or a space-separated string;
+    /// anything else is ignored.
+    pub fn extract(&self, claims: &Claims) -> Vec<String> {
+        let mut roles: Vec<String> = Vec::new();
+
+        for value in self.paths.iter().filter_map(|p| p.resolve(claims)) {
+            let found: Vec<&str> = match value {
+                Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+                Value::String(s) => s.split_whitespace().collect(),
+                _ => Vec::new(),
+            };
+
+            for role in found {
+                if !roles.iter().any(|r| r == role) {
+                    roles.push(role.to_string());
+                }
+            }
+        }
+
+        roles
+    }
+}
+
+impl Default for RoleMapping {
+    fn default() -> Self {
+        Self::keycloak(Vec::<String>::new())
+    }
+}
+
+impl IdentityResolver for RoleMapping {
+    fn resolve(&self, claims: Claims) -> Result<Identity, AuthError> {
+        Ok(Identity::from_claims(claims, self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::domain::models::{ClaimPath, Claims, RoleMapping};
+
+    fn claims(extra: serde_json::Value) -> Claims {
+        let mut base = json!({
+            "sub": "user-123",
+            "iss": "http://localhost:8000/realms/ferrispass",
+            "scope": "openid",
+            "preferred_username": "johndoe",
+        });
+
+        for (k, v) in extra.as_object().unwrap() {
+            base[k] = v.clone();
+        }
+
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn default_reads_realm_roles() {
+        let claims = claims(json!({
+            "realm_access": { "roles": ["user", "offline_access"] },
+        }));
+
+        assert_eq!(
+            RoleMapping::default().extract(&claims),
+            vec!["user", "offline_access"]
+        );
+    }
+
+    #[test]
+    fn keycloak_reads_listed_client_roles() {
+        let claims = claims(json!({
+            "realm_access": { "roles": ["user"] },
+            "resource_access": {
+                "ferrispass-api": { "roles": ["vault-admin", "user"] },
+                "account": { "roles": ["manage-account"] },
+            },
+        }));
+
+        assert_eq!(
+            RoleMapping::keycloak(["ferrispass-api"]).extract(&claims),
+            vec!["user", "vault-admin"]
+        );
+    }
+
+    #[test]
+    fn custom_paths_support_namespaced_claims_and_strings() {
+        let claims = claims(json!({
+            "https://ferrispass.io/roles": ["auditor"],
+            "groups": "ops admins",
+        }));
+
+        let mapping = RoleMapping::new([
+            ClaimPath::new(["https://ferrispass.io/roles"]),
+            ClaimPath::parse("groups"),
+        ]);
+
+        assert_eq!(mapping.extract(&claims), vec!["auditor", "ops", "admins"]);
+    }
+
+    #[test]
+    fn missing_or_malformed_paths_yield_no_roles() {
+        let claims = claims(json!({ "realm_access": { "roles": 42 } }));
+
+        assert!(RoleMapping::default().extract(&claims).is_empty());
+    }
+}
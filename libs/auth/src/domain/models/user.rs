@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct User {
     pub id: String,
+    pub issuer: String,
     pub username: String,
     pub email: Option<String>,
     pub name: Option<String>,
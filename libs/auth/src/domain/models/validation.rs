@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use crate::domain::models::{AuthError, Claims};
+
+/// Rules a verified token's claims must satisfy before it is trusted.
+#[derive(Debug, Clone)]
+pub struct ClaimsPolicy {
+    /// Accepted `iss` values; an empty list trusts no issuer.
+    pub issuers: Vec<String>,
+    /// Audience that must appear in `aud`, if any.
+    pub audience: Option<String>,
+    /// Tolerated clock skew between us and the identity provider.
+    pub leeway: Duration,
+    pub require_exp: bool,
+    pub require_nbf: bool,
+    pub require_iat: bool,
+}
+
+impl ClaimsPolicy {
+    pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+    pub fn new(issuers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            issuers: issuers.into_iter().map(Into::into).collect(),
+            audience: None,
+            leeway: Self::DEFAULT_LEEWAY,
+            require_exp: true,
+            require_nbf: false,
+            require_iat: false,
+        }
+    }
+
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Validates `claims` at `now`, expressed in seconds since the Unix epoch.
+    pub fn validate(&self, claims: &Claims, now: u64) -> Result<(), AuthError> {
+        let leeway = self.leeway.as_secs();
+
+        if !self.issuers.iter().any(|i| i == &claims.iss) {
+            return Err(AuthError::InvalidIssuer {
+                issuer: claims.iss.clone(),
+            });
+        }
+
+        if let Some(expected) = &self.audience
+            && !claims
+                .aud
+                .as_ref()
+                .is_some_and(|aud| aud.contains(expected))
+        {
+            return Err(AuthError::InvalidAudience {
+                expected: expected.clone(),
+            });
+        }
+
+        match claims.exp {
+            Some(exp) if exp.saturating_add(leeway) <= now => return Err(AuthError::Expired),
+            None if self.require_exp => return Err(AuthError::MissingClaim { claim: "exp" }),
+            _ => {}
+        }
+
+        match claims.nbf {
+            Some(nbf) if nbf > now.saturating_add(leeway) => return Err(AuthError::NotYetValid),
+            None if self.require_nbf => return Err(AuthError::MissingClaim { claim: "nbf" }),
+            _ => {}
+        }
+
+        match claims.iat {
+            Some(iat) if iat > now.saturating_add(leeway) => {
+                return Err(AuthError::InvalidToken {
+                    message: "token issued in the future".to_string(),
+                });
+            }
+            Some(iat) if claims.exp.is_some_and(|exp| exp < iat) => {
+                return Err(AuthError::InvalidToken {
+                    message: "token expires before it was issued".to_string(),
+                });
+            }
+            None if self.require_iat => return Err(AuthError::MissingClaim { claim: "iat" }),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::domain::models::{AuthError, Claims, ClaimsPolicy};
+
+    const NOW: u64 = 1_770_000_000;
+    const ISSUER: &str = "http://localhost:8000/realms/ferrispass";
+
+    fn claims(overrides: serde_json::Value) -> Claims {
+        let mut base = json!({
+            "sub": "user-123",
+            "iss": ISSUER,
+            "aud": ["ferrispass-api", "account"],
+            "exp": NOW + 300,
+            "iat": NOW - 10,
+            "scope": "openid",
+            "preferred_username": "johndoe",
+        });
+
+        for (k, v) in overrides.as_object().unwrap() {
+            base[k] = v.clone();
+        }
+
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn policy() -> ClaimsPolicy {
+        ClaimsPolicy::new([ISSUER])
+            .with_audience("ferrispass-api")
+            .with_leeway(Duration::from_secs(30))
+    }
+
+    #[test]
+    fn accepts_valid_claims() {
+        assert!(policy().validate(&claims(json!({})), NOW).is_ok());
+    }
+
+    #[test]
+    fn rejects_foreign_issuer() {
+        let result = policy().validate(
+            &claims(json!({ "iss": "http://localhost:8000/realms/other" })),
+            NOW,
+        );
+
+        assert!(matches!(result, Err(AuthError::InvalidIssuer { .. })));
+    }
+
+    #[test]
+    fn empty_issuer_list_trusts_nobody() {
+        let result = ClaimsPolicy::new(Vec::<String>::new()).validate(&claims(json!({})), NOW);
+
+        assert!(matches!(result, Err(AuthError::InvalidIssuer { .. })));
+    }
+
+    #[test]
+    fn rejects_missing_audience() {
+        let result = policy().validate(&claims(json!({ "aud": "account" })), NOW);
+
+        assert!(matches!(result, Err(AuthError::InvalidAudience { .. })));
+    }
+
+    #[test]
+    fn accepts_single_string_audience() {
+        let result = policy().validate(&claims(json!({ "aud": "ferrispass-api" })), NOW);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn rejects_expired_token_beyond_leeway() {
+        let result = policy().validate(&claims(json!({ "exp": NOW - 31 })), NOW);
+
+        assert!(matches!(result, Err(AuthError::Expired)));
+    }
+
+    #[test]
+    fn tolerates_expiry_within_leeway() {
+        let result = policy().validate(&claims(json!({ "exp": NOW - 10 })), NOW);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn requires_exp_by_default() {
+        let mut c = claims(json!({}));
+        c.exp = None;
+
+        assert!(matches!(
+            policy().validate(&c, NOW),
+            Err(AuthError::MissingClaim { claim: "exp" })
+        ));
+    }
+
+    #[test]
+    fn rejects_token_not_yet_valid() {
+        let result = policy().validate(&claims(json!({ "nbf": NOW + 120 })), NOW);
+
+        assert!(matches!(result, Err(AuthError::NotYetValid)));
+    }
+
+    #[test]
+    fn requires_nbf_when_configured() {
+        let mut policy = policy();
+        policy.require_nbf = true;
+
+        assert!(matches!(
+            policy.validate(&claims(json!({})), NOW),
+            Err(AuthError::MissingClaim { claim: "nbf" })
+        ));
+    }
+
+    #[test]
+    fn rejects_iat_in_the_future() {
+        let result = policy().validate(&claims(json!({ "iat": NOW + 120 })), NOW);
+
+        assert!(matches!(result, Err(AuthError::InvalidToken { .. })));
+    }
+}
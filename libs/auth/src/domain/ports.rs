@@ -1 +1,29 @@
+use crate::domain::models::{AuthError, Claims, Identity, Jwt, Token, VerificationKey};
 
+/// Supplies the public key matching a token's `kid`.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait KeyProvider: Send + Sync {
+    fn get_key(&self, kid: &str)
+    -> impl Future<Output = Result<VerificationKey, AuthError>> + Send;
+}
+
+/// Checks a token's signature and claims, returning the trusted payload.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait TokenVerifier: Send + Sync {
+    fn verify(&self, token: &Token) -> impl Future<Output = Result<Jwt, AuthError>> + Send;
+}
+
+/// Turns verified claims into the caller's identity.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait IdentityResolver: Send + Sync {
+    fn resolve(&self, claims: Claims) -> Result<Identity, AuthError>;
+}
+
+/// Entry point for the API layer: bearer token in, verified identity out.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait Authenticator: Send + Sync {
+    fn authenticate(
+        &self,
+        token: &Token,
+    ) -> impl Future<Output = Result<Identity, AuthError>> + Send;
+}
@@ -0,0 +1,368 @@
+use std::{collections::HashMap, time::Duration};
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, PublicKeyUse},
+};
+use tokio::{
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    domain::{
+        models::{AuthError, VerificationKey},
+        ports::KeyProvider,
+    },
+    infrastructure::jwks::source::JwksSource,
+};
+
+/// Signature algorithms accepted from the identity provider.
+pub const SUPPORTED_ALGORITHMS: [Algorithm; 3] =
+    [Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA];
+
+#[derive(Debug, Clone)]
+pub struct JwksCacheConfig {
+    /// Keys older than this are refreshed on next use.
+    pub max_age: Duration,
+    /// Delay before retrying after a failed or fruitless refresh.
+    pub min_backoff: Duration,
+    /// Upper bound of the exponential backoff.
+    pub max_backoff: Duration,
+}
+
+impl Default for JwksCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(15 * 60),
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    keys: HashMap<String, VerificationKey>,
+    fetched_at: Option<Instant>,
+    retry_at: Option<Instant>,
+    backoff: Option<Duration>,
+}
+
+impl CacheState {
+    fn is_fresh(&self, max_age: Duration) -> bool {
+        self.fetched_at.is_some_and(|at| at.elapsed() < max_age)
+    }
+}
+
+/// Verification keys indexed by `kid`.
+///
+/// An unknown `kid` triggers a refresh, which is how key rotation is picked
+/// up; repeated misses or fetch failures back off exponentially so a flood of
+/// forged tokens cannot hammer the identity provider.
+pub struct JwksCache<S>
+where
+    S: JwksSource,
+{
+    source: S,
+    config: JwksCacheConfig,
+    state: RwLock<CacheState>,
+    refresh: Mutex<()>,
+}
+
+impl<S> JwksCache<S>
+where
+    S: JwksSource,
+{
+    pub fn new(source: S) -> Self {
+        Self::with_config(source, JwksCacheConfig::default())
+    }
+
+    pub fn with_config(source: S, config: JwksCacheConfig) -> Self {
+        Self {
+            source,
+            config,
+            state: RwLock::new(CacheState::default()),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    pub async fn get(&self, kid: &str) -> Result<VerificationKey, AuthError> {
+        if let Some(key) = self.fresh_key(kid).await {
+            return Ok(key);
+        }
+
+        // Only one task refreshes; the others wait and re-read its result.
+        let _guard = self.refresh.lock().await;
+
+        if let Some(key) = self.fresh_key(kid).await {
+            return Ok(key);
+        }
+
+        {
+            let state = self.state.read().await;
+
+            if state.retry_at.is_some_and(|at| Instant::now() < at) {
+                // Backing off: a stale key is still better than no key.
+                return state
+                    .keys
+                    .get(kid)
+                    .cloned()
+                    .ok_or_else(|| AuthError::KeyNotFound {
+                        key: kid.to_string(),
+                    });
+            }
+        }
+
+        // No lock is held while fetching so cached keys stay usable meanwhile.
+        let fetched = self.source.fetch().await;
+        let mut state = self.state.write().await;
+
+        match fetched {
+            Ok(set) => {
+                state.keys = parse_key_set(&set);
+                state.fetched_at = Some(Instant::now());
+                debug!("refreshed JWKS: {} usable keys", state.keys.len());
+
+                match state.keys.get(kid).cloned() {
+                    Some(key) => {
+                        state.retry_at = None;
+                        state.backoff = None;
+                        Ok(key)
+                    }
+                    None => {
+                        self.back_off(&mut state);
+                        Err(AuthError::KeyNotFound {
+                            key: kid.to_string(),
+                        })
+                    }
+                }
+            }
+            Err(e) => {
+                self.back_off(&mut state);
+
+                match state.keys.get(kid) {
+                    Some(key) => {
+                        warn!("JWKS refresh failed, using cached key {}: {}", kid, e);
+                        Ok(key.clone())
+                    }
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    async fn fresh_key(&self, kid: &str) -> Option<VerificationKey> {
+        let state = self.state.read().await;
+
+        if !state.is_fresh(self.config.max_age) {
+            return None;
+        }
+
+        state.keys.get(kid).cloned()
+    }
+
+    fn back_off(&self, state: &mut CacheState) {
+        let backoff = state
+            .backoff
+            .map(|b| (b * 2).min(self.config.max_backoff))
+            .unwrap_or(self.config.min_backoff);
+
+        state.backoff = Some(backoff);
+        state.retry_at = Some(Instant::now() + backoff);
+    }
+}
+
+impl<S> KeyProvider for JwksCache<S>
+where
+    S: JwksSource,
+{
+    async fn get_key(&self, kid: &str) -> Result<VerificationKey, AuthError> {
+        self.get(kid).await
+    }
+}
+
+fn parse_key_set(set: &JwkSet) -> HashMap<String, VerificationKey> {
+    set.keys
+        .iter()
+        .filter_map(|jwk| match parse_key(jwk) {
+            Ok(key) => Some((key.kid.clone(), key)),
+            Err(reason) => {
+                debug!(
+                    "skipping JWK {:?}: {}",
+                    jwk.common.key_id.as_deref().unwrap_or("<no kid>"),
+                    reason
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_key(jwk: &Jwk) -> Result<VerificationKey, String> {
+    let kid = jwk.common.key_id.clone().ok_or("missing kid")?;
+
+    if matches!(jwk.common.public_key_use, Some(PublicKeyUse::Encryption)) {
+        return Err("encryption key".into());
+    }
+
+    let alg = match jwk.common.key_algorithm {
+        Some(KeyAlgorithm::RS256) => Algorithm::RS256,
+        Some(KeyAlgorithm::ES256) => Algorithm::ES256,
+        Some(KeyAlgorithm::EdDSA) => Algorithm::EdDSA,
+        Some(other) => return Err(format!("unsupported algorithm {other}")),
+        // `alg` is optional in a JWK; infer it from the key material.
+        None => match &jwk.algorithm {
+            AlgorithmParameters::RSA(_) => Algorithm::RS256,
+            AlgorithmParameters::EllipticCurve(p) if p.curve == EllipticCurve::P256 => {
+                Algorithm::ES256
+            }
+            AlgorithmParameters::OctetKeyPair(p) if p.curve == EllipticCurve::Ed25519 => {
+                Algorithm::EdDSA
+            }
+            _ => return Err("unsupported key type".into()),
+        },
+    };
+
+    let key = DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?;
+
+    Ok(VerificationKey { kid, alg, key })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use jsonwebtoken::{Algorithm, jwk::JwkSet};
+
+    use crate::{
+        domain::models::AuthError,
+        infrastructure::jwks::{
+            cache::{JwksCache, JwksCacheConfig},
+            source::JwksSource,
+        },
+    };
+
+    const JWKS: &str = include_str!("../../../fixtures/jwks.json");
+
+    /// Serves the fixture key set, counts fetches and can be switched off.
+    #[derive(Clone)]
+    struct CountingSource {
+        fetches: Arc<AtomicUsize>,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl CountingSource {
+        fn new() -> Self {
+            Self {
+                fetches: Arc::new(AtomicUsize::new(0)),
+                fail: Arc::new(AtomicBool::new(false)),
+            }
+        }
+
+        fn fetches(&self) -> usize {
+            self.fetches.load(Ordering::SeqCst)
+        }
+    }
+
+    impl JwksSource for CountingSource {
+        async fn fetch(&self) -> Result<JwkSet, AuthError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(AuthError::Network {
+                    message: "down".into(),
+                });
+            }
+
+            Ok(serde_json::from_str(JWKS).unwrap())
+        }
+    }
+
+    fn config() -> JwksCacheConfig {
+        JwksCacheConfig {
+            max_age: Duration::from_secs(600),
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_all_supported_algorithms() {
+        let cache = JwksCache::with_config(CountingSource::new(), config());
+
+        assert_eq!(cache.get("rs256-key").await.unwrap().alg, Algorithm::RS256);
+        assert_eq!(cache.get("es256-key").await.unwrap().alg, Algorithm::ES256);
+        assert_eq!(cache.get("eddsa-key").await.unwrap().alg, Algorithm::EdDSA);
+    }
+
+    #[tokio::test]
+    async fn caches_keys_between_calls() {
+        let source = CountingSource::new();
+        let cache = JwksCache::with_config(source.clone(), config());
+
+        cache.get("rs256-key").await.unwrap();
+        cache.get("es256-key").await.unwrap();
+
+        assert_eq!(source.fetches(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unknown_kid_refreshes_then_backs_off() {
+        let source = CountingSource::new();
+        let cache = JwksCache::with_config(source.clone(), config());
+
+        cache.get("rs256-key").await.unwrap();
+
+        assert!(matches!(
+            cache.get("rotated-key").await,
+            Err(AuthError::KeyNotFound { .. })
+        ));
+        assert_eq!(source.fetches(), 2);
+
+        // Within the backoff window no further fetch happens.
+        assert!(cache.get("rotated-key").await.is_err());
+        assert_eq!(source.fetches(), 2);
+
+        tokio::time::advance(Duration::from_millis(1100)).await;
+        assert!(cache.get("rotated-key").await.is_err());
+        assert_eq!(source.fetches(), 3);
+
+        // The window doubled, so one second is no longer enough.
+        tokio::time::advance(Duration::from_millis(1100)).await;
+        assert!(cache.get("rotated-key").await.is_err());
+        assert_eq!(source.fetches(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stale_keys_survive_a_failed_refresh() {
+        let source = CountingSource::new();
+        let cache = JwksCache::with_config(source.clone(), config());
+        cache.get("rs256-key").await.unwrap();
+
+        source.fail.store(true, Ordering::SeqCst);
+        tokio::time::advance(Duration::from_secs(601)).await;
+
+        assert!(cache.get("rs256-key").await.is_ok());
+        assert_eq!(source.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn fetch_failure_without_cached_key_is_reported() {
+        let source = CountingSource::new();
+        source.fail.store(true, Ordering::SeqCst);
+        let cache = JwksCache::with_config(source, config());
+
+        assert!(matches!(
+            cache.get("rs256-key").await,
+            Err(AuthError::Network { .. })
+        ));
+    }
+}
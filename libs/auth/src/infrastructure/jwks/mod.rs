@@ -0,0 +1,7 @@
+pub(crate) mod cache;
+pub(crate) mod source;
+pub(crate) mod verifier;
+
+pub use cache::*;
+pub use source::*;
+pub use verifier::*;
@@ -0,0 +1,165 @@
+use std::{path::PathBuf, time::Duration};
+
+use jsonwebtoken::jwk::JwkSet;
+use tracing::error;
+
+use crate::domain::models::AuthError;
+
+/// Where a JWKS document is read from.
+pub trait JwksSource: Send + Sync {
+    fn fetch(&self) -> impl Future<Output = Result<JwkSet, AuthError>> + Send;
+}
+
+/// Fetches the JWKS from the identity provider, e.g.
+/// `{issuer}/protocol/openid-connect/certs` for Keycloak.
+#[derive(Debug, Clone)]
+pub struct HttpJwksSource {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpJwksSource {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn new(url: impl Into<String>) -> Result<Self, AuthError> {
+        let client = reqwest::Client::builder()
+            .timeout(Self::DEFAULT_TIMEOUT)
+            .build()
+            .map_err(|e| AuthError::Internal {
+                message: format!("failed to build HTTP client: {e}"),
+            })?;
+
+        Ok(Self::with_client(client, url))
+    }
+
+    pub fn with_client(client: reqwest::Client, url: impl Into<String>) -> Self {
+        Self {
+            client,
+            url: url.into(),
+        }
+    }
+}
+
+impl JwksSource for HttpJwksSource {
+    async fn fetch(&self) -> Result<JwkSet, AuthError> {
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                error!("failed to fetch JWKS from {}: {:?}", self.url, e);
+                AuthError::Network {
+                    message: format!("failed to fetch JWKS from {}: {e}", self.url),
+                }
+            })?;
+
+        response.json::<JwkSet>().await.map_err(|e| {
+            error!("failed to parse JWKS from {}: {:?}", self.url, e);
+            AuthError::Network {
+                message: format!("invalid JWKS document at {}: {e}", self.url),
+            }
+        })
+    }
+}
+
+/// Reads the JWKS from a local file, for air-gapped setups and tests.
+#[derive(Debug, Clone)]
+pub struct FileJwksSource {
+    path: PathBuf,
+}
+
+impl FileJwksSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl JwksSource for FileJwksSource {
+    async fn fetch(&self) -> Result<JwkSet, AuthError> {
+        let raw = tokio::fs::read(&self.path)
+            .await
+            .map_err(|e| AuthError::Internal {
+                message: format!("failed to read JWKS file {}: {e}", self.path.display()),
+            })?;
+
+        serde_json::from_slice(&raw).map_err(|e| AuthError::Internal {
+            message: format!("invalid JWKS file {}: {e}", self.path.display()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use crate::{
+        domain::models::AuthError,
+        infrastructure::jwks::source::{FileJwksSource, HttpJwksSource, JwksSource},
+    };
+
+    const JWKS: &str = include_str!("../../../fixtures/jwks.json");
+
+    #[tokio::test]
+    async fn http_source_fetches_jwks() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/realms/ferrispass/protocol/openid-connect/certs"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(JWKS, "application/json"))
+            .mount(&server)
+            .await;
+
+        let source = HttpJwksSource::new(format!(
+            "{}/realms/ferrispass/protocol/openid-connect/certs",
+            server.uri()
+        ))
+        .unwrap();
+
+        let set = source.fetch().await.unwrap();
+
+        assert!(set.find("rs256-key").is_some());
+        assert_eq!(set.keys.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn http_source_maps_server_errors_to_network() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let source = HttpJwksSource::new(format!("{}/certs", server.uri())).unwrap();
+
+        assert!(matches!(
+            source.fetch().await,
+            Err(AuthError::Network { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn file_source_reads_jwks() {
+        let source =
+            FileJwksSource::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/jwks.json"));
+
+        let set = source.fetch().await.unwrap();
+
+        assert!(set.find("eddsa-key").is_some());
+    }
+
+    #[tokio::test]
+    async fn file_source_reports_missing_file() {
+        let source = FileJwksSource::new("/nonexistent/jwks.json");
+
+        assert!(matches!(
+            source.fetch().await,
+            Err(AuthError::Internal { .. })
+        ));
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::{Validation, decode, decode_header, errors::ErrorKind};
+use tracing::debug;
+
+use crate::{
+    domain::{
+        models::{AuthError, Claims, ClaimsPolicy, Jwt, Token},
+        ports::{KeyProvider, TokenVerifier},
+    },
+    infrastructure::jwks::cache::SUPPORTED_ALGORITHMS,
+};
+
+/// Checks JWT signatures against the identity provider's published keys,
+/// then applies the [`ClaimsPolicy`] to the verified claims.
+pub struct JwtVerifier<K>
+where
+    K: KeyProvider,
+{
+    keys: K,
+    policy: ClaimsPolicy,
+}
+
+impl<K> JwtVerifier<K>
+where
+    K: KeyProvider,
+{
+    pub fn new(keys: K, policy: ClaimsPolicy) -> Self {
+        Self { keys, policy }
+    }
+}
+
+impl<K> TokenVerifier for JwtVerifier<K>
+where
+    K: KeyProvider,
+{
+    async fn verify(&self, token: &Token) -> Result<Jwt, AuthError> {
+        let header = decode_header(token.as_str()).map_err(|e| AuthError::InvalidToken {
+            message: format!("malformed JWT header: {e}"),
+        })?;
+
+        if !SUPPORTED_ALGORITHMS.contains(&header.alg) {
+            return Err(AuthError::InvalidToken {
+                message: format!("unsupported signature algorithm {:?}", header.alg),
+            });
+        }
+
+        let kid = header.kid.ok_or_else(|| AuthError::InvalidToken {
+            message: "JWT header has no kid".to_string(),
+        })?;
+
+        let key = self.keys.get_key(&kid).await?;
+
+        // The key decides the algorithm, never the token: this is what stops
+        // algorithm confusion between key types.
+        if key.alg != header.alg {
+            return Err(AuthError::InvalidToken {
+                message: format!(
+                    "algorithm {:?} does not match key {} ({:?})",
+                    header.alg, kid, key.alg
+                ),
+            });
+        }
+
+        // Claims are checked by our own policy below, with precise errors.
+        let mut validation = Validation::new(key.alg);
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+        validation.required_spec_claims.clear();
+
+        let data = decode::<Claims>(token.as_str(), &key.key, &validation).map_err(|e| {
+            debug!("JWT verification failed: {:?}", e);
+            match e.kind() {
+                ErrorKind::InvalidSignature => AuthError::InvalidToken {
+                    message: "invalid signature".to_string(),
+                },
+                _ => AuthError::InvalidToken {
+                    message: format!("failed to verify JWT: {e}"),
+                },
+            }
+        })?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AuthError::Internal {
+                message: format!("system clock before Unix epoch: {e}"),
+            })?
+            .as_secs();
+
+        self.policy.validate(&data.claims, now)?;
+
+        Ok(Jwt {
+            claims: data.claims,
+            token: token.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use base64::{Engine, engine::general_purpose};
+    use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+    use serde_json::{Value, json};
+
+    use crate::{
+        domain::{
+            models::{AuthError, ClaimsPolicy, Token},
+            ports::TokenVerifier,
+        },
+        infrastructure::jwks::{cache::JwksCache, source::FileJwksSource, verifier::JwtVerifier},
+    };
+
+    // Throwaway keys generated for these tests only.
+    const RS256_PEM: &[u8] = include_bytes!("../../../fixtures/rs256.pem");
+    const ES256_PEM: &[u8] = include_bytes!("../../../fixtures/es256.pem");
+    const EDDSA_PEM: &[u8] = include_bytes!("../../../fixtures/eddsa.pem");
+
+    const ISSUER: &str = "http://localhost:8000/realms/ferrispass";
+
+    fn verifier() -> JwtVerifier<JwksCache<FileJwksSource>> {
+        JwtVerifier::new(
+            JwksCache::new(FileJwksSource::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/jwks.json"
+            ))),
+            ClaimsPolicy::new([ISSUER]),
+        )
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn claims() -> Value {
+        json!({
+            "sub": "user-123",
+            "iss": ISSUER,
+            "exp": now() + 300,
+            "scope": "openid",
+            "preferred_username": "johndoe",
+        })
+    }
+
+    fn sign_claims(alg: Algorithm, kid: &str, key: &EncodingKey, claims: &Value) -> Token {
+        let mut header = Header::new(alg);
+        header.kid = Some(kid.to_string());
+
+        Token::new(encode(&header, claims, key).unwrap())
+    }
+
+    fn sign(alg: Algorithm, kid: &str, key: &EncodingKey) -> Token {
+        sign_claims(alg, kid, key, &claims())
+    }
+
+    fn rs256() -> EncodingKey {
+        EncodingKey::from_rsa_pem(RS256_PEM).unwrap()
+    }
+
+    #[tokio::test]
+    async fn verifies_rs256() {
+        let token = sign(Algorithm::RS256, "rs256-key", &rs256());
+
+        let jwt = verifier().verify(&token).await.unwrap();
+
+        assert_eq!(jwt.claims.sub.0, "user-123");
+    }
+
+    #[tokio::test]
+    async fn verifies_es256() {
+        let key = EncodingKey::from_ec_pem(ES256_PEM).unwrap();
+        let token = sign(Algorithm::ES256, "es256-key", &key);
+
+        assert!(verifier().verify(&token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn verifies_eddsa() {
+        let key = EncodingKey::from_ed_pem(EDDSA_PEM).unwrap();
+        let token = sign(Algorithm::EdDSA, "eddsa-key", &key);
+
+        assert!(verifier().verify(&token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_tampered_payload() {
+        let token = sign(Algorithm::RS256, "rs256-key", &rs256());
+        let parts: Vec<&str> = token.as_str().split('.').collect();
+
+        let mut tampered = claims();
+        tampered["sub"] = json!("admin");
+        let payload = general_purpose::URL_SAFE_NO_PAD.encode(tampered.to_string());
+        let forged = Token::new(format!("{}.{}.{}", parts[0], payload, parts[2]));
+
+        assert!(matches!(
+            verifier().verify(&forged).await,
+            Err(AuthError::InvalidToken { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_signature_from_another_key() {
+        let key = EncodingKey::from_ec_pem(ES256_PEM).unwrap();
+        // Signed with the EC key but claiming the RSA key id.
+        let token = sign(Algorithm::ES256, "rs256-key", &key);
+
+        assert!(matches!(
+            verifier().verify(&token).await,
+            Err(AuthError::InvalidToken { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_hmac_tokens() {
+        let key = EncodingKey::from_secret(b"secret");
+        let token = sign(Algorithm::HS256, "rs256-key", &key);
+
+        assert!(matches!(
+            verifier().verify(&token).await,
+            Err(AuthError::InvalidToken { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_kid() {
+        let token = sign(Algorithm::RS256, "unknown-key", &rs256());
+
+        assert!(matches!(
+            verifier().verify(&token).await,
+            Err(AuthError::KeyNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_token_after_signature_check() {
+        let mut expired = claims();
+        expired["exp"] = json!(now() - 3600);
+        let token = sign_claims(Algorithm::RS256, "rs256-key", &rs256(), &expired);
+
+        assert!(matches!(
+            verifier().verify(&token).await,
+            Err(AuthError::Expired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_token_from_another_realm() {
+        let mut foreign = claims();
+        foreign["iss"] = json!("http://localhost:8000/realms/master");
+        let token = sign_claims(Algorithm::RS256, "rs256-key", &rs256(), &foreign);
+
+        assert!(matches!(
+            verifier().verify(&token).await,
+            Err(AuthError::InvalidIssuer { .. })
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{shared::errors::DomainError, vault::OwnerSub};
+
+/// Largest chunk a client may upload in one request.
+pub const MAX_CHUNK_SIZE: u32 = 1024 * 1024;
+
+/// Most chunks an attachment may be split into.
+pub const MAX_CHUNKS: u32 = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AttachmentId(pub Uuid);
+
+/// A file encrypted by the client and uploaded in fixed-size chunks.
+///
+/// The server only sees opaque bytes: each chunk is sealed on its own by the
+/// client, and the key lives inside the vault ciphertext. Every chunk but the
+/// last is exactly `chunk_size` bytes, so a resumed upload can tell from the
+/// stored chunks alone which ones are still missing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: AttachmentId,
+    pub owner_id: OwnerSub,
+
+    /// Total bytes over all chunks.
+    pub size: u64,
+    pub chunk_size: u32,
+
+    pub created_at: DateTime<Utc>,
+
+    /// Set once every chunk is stored; chunks are immutable from then on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Attachment {
+    pub fn new(
+        id: AttachmentId,
+        owner_id: OwnerSub,
+        size: u64,
+        chunk_size: u32,
+        now: DateTime<Utc>,
+    ) -> Result<Self, DomainError> {
+        if size == 0 {
+            return Err(DomainError::Validation {
+                field: "size",
+                message: "must not be zero".into(),
+            });
+        }
+
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(DomainError::Validation {
+                field: "chunk_size",
+                message: format!("must be between 1 and {MAX_CHUNK_SIZE}"),
+            });
+        }
+
+        if size.div_ceil(chunk_size.into()) > MAX_CHUNKS.into() {
+            return Err(DomainError::Validation {
+                field: "size",
+                message: format!("must fit in {MAX_CHUNKS} chunks"),
+            });
+        }
+
+        Ok(Self {
+            id,
+            owner_id,
+            size,
+            chunk_size,
+            created_at: now,
+            completed_at: None,
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    pub fn chunk_count(&self) -> u32 {
+        // Bounded by MAX_CHUNKS on creation.
+        self.size.div_ceil(self.chunk_size.into()) as u32
+    }
+
+    /// Length chunk `index` must have: `chunk_size`, except for the last
+    /// chunk which holds the remainder.
+    pub fn chunk_len(&self, index: u32) -> Result<usize, DomainError> {
+        let count = self.chunk_count();
+        if index >= count {
+            return Err(DomainError::Validation {
+                field: "index",
+                message: format!("must be below {count}"),
+            });
+        }
+
+        let start = u64::from(index) * u64::from(self.chunk_size);
+        let len = (self.size - start).min(self.chunk_size.into());
+
+        Ok(len as usize)
+    }
+
+    /// Checks a chunk before it is stored.
+    pub fn accept_chunk(&self, index: u32, len: usize) -> Result<(), DomainError> {
+        self.ensure_uploading()?;
+
+        let expected = self.chunk_len(index)?;
+        if len != expected {
+            return Err(DomainError::Validation {
+                field: "chunk",
+                message: format!("chunk {index} must be {expected} bytes"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Indices of the chunks not in `received`, in order.
+    pub fn missing_chunks(&self, received: &[u32]) -> Vec<u32> {
+        (0..self.chunk_count())
+            .filter(|index| !received.contains(index))
+            .collect()
+    }
+
+    /// Seals the upload once every chunk has been received.
+    pub fn complete(&self, received: &[u32], now: DateTime<Utc>) -> Result<Self, DomainError> {
+        self.ensure_uploading()?;
+
+        let missing = self.missing_chunks(received);
+        if !missing.is_empty() {
+            return Err(DomainError::Validation {
+                field: "chunks",
+                message: format!("{} chunks missing, first {}", missing.len(), missing[0]),
+            });
+        }
+
+        Ok(Self {
+            completed_at: Some(now),
+            ..self.clone()
+        })
+    }
+
+    fn ensure_uploading(&self) -> Result<(), DomainError> {
+        if self.is_complete() {
+            return Err(DomainError::Validation {
+                field: "attachment",
+                message: "upload is already complete".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        attachment::aggregate::{Attachment, AttachmentId, MAX_CHUNK_SIZE},
+        shared::errors::DomainError,
+        vault::OwnerSub,
+    };
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_770_000_000, 0).unwrap()
+    }
+
+    fn attachment(size: u64, chunk_size: u32) -> Result<Attachment, DomainError> {
+        Attachment::new(
+            AttachmentId(Uuid::nil()),
+            OwnerSub::new("user1").unwrap(),
+            size,
+            chunk_size,
+            now(),
+        )
+    }
+
+    #[test]
+    fn last_chunk_holds_the_remainder() {
+        let attachment = attachment(10, 4).unwrap();
+
+        assert_eq!(attachment.chunk_count(), 3);
+        assert_eq!(attachment.chunk_len(0).unwrap(), 4);
+        assert_eq!(attachment.chunk_len(2).unwrap(), 2);
+        assert!(attachment.chunk_len(3).is_err());
+        assert!(attachment.accept_chunk(2, 2).is_ok());
+        assert!(attachment.accept_chunk(1, 2).is_err());
+    }
+
+    #[test]
+    fn layout_is_bounded() {
+        assert!(attachment(0, 4).is_err());
+        assert!(attachment(10, 0).is_err());
+        assert!(attachment(10, MAX_CHUNK_SIZE + 1).is_err());
+        assert!(attachment(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn completion_needs_every_chunk() {
+        let attachment = attachment(10, 4).unwrap();
+
+        assert_eq!(attachment.missing_chunks(&[0, 2]), vec![1]);
+        assert!(matches!(
+            attachment.complete(&[0, 2], now()),
+            Err(DomainError::Validation {
+                field: "chunks",
+                ..
+            })
+        ));
+
+        let complete = attachment.complete(&[2, 0, 1], now()).unwrap();
+        assert!(complete.is_complete());
+        assert!(complete.accept_chunk(0, 4).is_err());
+        assert!(complete.complete(&[0, 1, 2], now()).is_err());
+    }
+}
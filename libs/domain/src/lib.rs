@@ -1,3 +1,5 @@
+pub mod attachment;
+pub mod quota;
 pub(crate) mod shared;
 pub mod vault;
 
@@ -0,0 +1,276 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared::errors::DomainError;
+
+/// Which limit of a [`Quota`] a write would exceed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaLimit {
+    PackageBytes,
+    TotalBytes,
+    Vaults,
+}
+
+impl Display for QuotaLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaLimit::PackageBytes => write!(f, "package_bytes"),
+            QuotaLimit::TotalBytes => write!(f, "total_bytes"),
+            QuotaLimit::Vaults => write!(f, "vaults"),
+        }
+    }
+}
+
+/// Storage an owner holds. Trashed vaults count until they are purged, and
+/// history until it is pruned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageUsage {
+    /// Vaults, live or in the trash.
+    pub vaults: u64,
+    /// Encrypted bytes of the current packages.
+    pub vault_bytes: u64,
+    /// Encrypted bytes of the archived revisions.
+    pub history_bytes: u64,
+    /// Declared sizes of the attachments, complete or not.
+    pub attachment_bytes: u64,
+}
+
+impl StorageUsage {
+    pub fn total_bytes(&self) -> u64 {
+        self.vault_bytes
+            .saturating_add(self.history_bytes)
+            .saturating_add(self.attachment_bytes)
+    }
+}
+
+/// Limits on what one owner may store; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quota {
+    /// Largest single package, see [`VaultPackage::byte_size`](crate::vault::VaultPackage::byte_size).
+    pub max_package_bytes: Option<u64>,
+    /// Vaults, history and attachments together.
+    pub max_total_bytes: Option<u64>,
+    pub max_vaults: Option<u64>,
+}
+
+impl Quota {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Whether checking a write needs the owner's current usage.
+    pub fn needs_usage(&self) -> bool {
+        self.max_total_bytes.is_some() || self.max_vaults.is_some()
+    }
+
+    pub fn check_package(&self, package_bytes: u64) -> Result<(), DomainError> {
+        check(
+            QuotaLimit::PackageBytes,
+            self.max_package_bytes,
+            package_bytes,
+        )
+    }
+
+    /// Checks that `usage` grown by `added_bytes` and `added_vaults` stays
+    /// within the limits.
+    pub fn check_growth(
+        &self,
+        usage: &StorageUsage,
+        added_bytes: u64,
+        added_vaults: u64,
+    ) -> Result<(), DomainError> {
+        if added_vaults > 0 {
+            check(
+                QuotaLimit::Vaults,
+                self.max_vaults,
+                usage.vaults.saturating_add(added_vaults),
+            )?;
+        }
+
+        check(
+            QuotaLimit::TotalBytes,
+            self.max_total_bytes,
+            usage.total_bytes().saturating_add(added_bytes),
+        )
+    }
+}
+
+fn check(limit: QuotaLimit, allowed: Option<u64>, requested: u64) -> Result<(), DomainError> {
+    match allowed {
+        Some(allowed) if requested > allowed => Err(DomainError::QuotaExceeded {
+            limit,
+            allowed,
+            requested,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// A named set of limits, e.g. for a paid tier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub name: String,
+    pub quota: Quota,
+}
+
+/// Quotas per plan, chosen from the plans an owner is subscribed to.
+///
+/// Plans are listed by precedence: an owner on several plans gets the first
+/// one listed, and owners on none get the default quota.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaPolicy {
+    default: Quota,
+    plans: Vec<Plan>,
+}
+
+impl QuotaPolicy {
+    pub fn new(default: Quota, plans: impl IntoIterator<Item = Plan>) -> Result<Self, DomainError> {
+        let plans: Vec<Plan> = plans.into_iter().collect();
+
+        for (index, plan) in plans.iter().enumerate() {
+            if plan.name.trim().is_empty() {
+                return Err(DomainError::Validation {
+                    field: "quota_policy.plans",
+                    message: "plan names must not be empty".into(),
+                });
+            }
+
+            if plans[..index].iter().any(|other| other.name == plan.name) {
+                return Err(DomainError::Validation {
+                    field: "quota_policy.plans",
+                    message: format!("plan {} is configured twice", plan.name),
+                });
+            }
+        }
+
+        Ok(Self { default, plans })
+    }
+
+    /// No limits at all.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn plans(&self) -> &[Plan] {
+        &self.plans
+    }
+
+    /// The plan that applies among `subscribed`, or `None` for the default
+    /// quota.
+    pub fn plan_for<'a>(&self, subscribed: impl IntoIterator<Item = &'a str>) -> Option<&Plan> {
+        let subscribed: Vec<&str> = subscribed.into_iter().collect();
+
+        self.plans
+            .iter()
+            .find(|plan| subscribed.contains(&plan.name.as_str()))
+    }
+
+    pub fn quota_for<'a>(&self, subscribed: impl IntoIterator<Item = &'a str>) -> &Quota {
+        self.plan_for(subscribed)
+            .map(|plan| &plan.quota)
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DomainError,
+        quota::{Plan, Quota, QuotaLimit, QuotaPolicy, StorageUsage},
+    };
+
+    fn quota() -> Quota {
+        Quota {
+            max_package_bytes: Some(100),
+            max_total_bytes: Some(1000),
+            max_vaults: Some(2),
+        }
+    }
+
+    fn usage() -> StorageUsage {
+        StorageUsage {
+            vaults: 2,
+            vault_bytes: 300,
+            history_bytes: 500,
+            attachment_bytes: 100,
+        }
+    }
+
+    #[test]
+    fn package_size_is_bounded() {
+        assert!(quota().check_package(100).is_ok());
+        assert!(matches!(
+            quota().check_package(101),
+            Err(DomainError::QuotaExceeded {
+                limit: QuotaLimit::PackageBytes,
+                allowed: 100,
+                requested: 101,
+            })
+        ));
+    }
+
+    #[test]
+    fn growth_counts_history_and_attachments() {
+        assert!(quota().check_growth(&usage(), 100, 0).is_ok());
+        assert!(matches!(
+            quota().check_growth(&usage(), 101, 0),
+            Err(DomainError::QuotaExceeded {
+                limit: QuotaLimit::TotalBytes,
+                requested: 1001,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn vault_count_is_only_checked_for_new_vaults() {
+        assert!(quota().check_growth(&usage(), 0, 0).is_ok());
+        assert!(matches!(
+            quota().check_growth(&usage(), 0, 1),
+            Err(DomainError::QuotaExceeded {
+                limit: QuotaLimit::Vaults,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn first_listed_plan_wins_over_the_default() {
+        let gold = Quota::unlimited();
+        let silver = Quota {
+            max_vaults: Some(10),
+            ..Quota::unlimited()
+        };
+        let policy = QuotaPolicy::new(
+            quota(),
+            [
+                Plan {
+                    name: "gold".into(),
+                    quota: gold,
+                },
+                Plan {
+                    name: "silver".into(),
+                    quota: silver,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(policy.quota_for(["silver", "gold"]), &gold);
+        assert_eq!(policy.quota_for(["silver"]), &silver);
+        assert_eq!(policy.quota_for(["other"]), &quota());
+        assert!(policy.plan_for([]).is_none());
+    }
+
+    #[test]
+    fn plans_must_be_unique() {
+        let plan = || Plan {
+            name: "gold".into(),
+            quota: Quota::unlimited(),
+        };
+
+        assert!(QuotaPolicy::new(quota(), [plan(), plan()]).is_err());
+    }
+}
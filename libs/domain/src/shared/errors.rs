@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::quota::QuotaLimit;
+
 #[derive(Debug, Clone, Error)]
 pub enum DomainError {
     #[error("vault {vault_id} not found")]
@@ -12,9 +14,19 @@ pub enum DomainError {
         actual: String,
     },
 
+    #[error("vault {vault_id} is deleted")]
+    VaultDeleted { vault_id: String },
+
     #[error("validation error on {field}: {message}")]
     Validation {
         field: &'static str,
         message: String,
     },
+
+    #[error("{limit} quota exceeded: {requested} over {allowed}")]
+    QuotaExceeded {
+        limit: QuotaLimit,
+        allowed: u64,
+        requested: u64,
+    },
 }
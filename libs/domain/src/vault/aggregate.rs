@@ -1,10 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     shared::errors::DomainError,
     vault::{
-        package::VaultPackage,
+        package::{CipherBlob, VaultPackage},
         value_objects::{Etag, OwnerSub, Revision, VaultId},
     },
 };
@@ -21,6 +21,32 @@ pub struct Vault {
 
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+
+    /// Tombstone: set while the vault sits in the trash, awaiting either a
+    /// restore or its purge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Version metadata of a vault, without the encrypted package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultVersion {
+    pub id: VaultId,
+    pub revision: Revision,
+    pub etag: Etag,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What an owner sees when listing their vaults: version metadata and the
+/// encrypted name, without the vault content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultSummary {
+    pub id: VaultId,
+    pub revision: Revision,
+    pub etag: Etag,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CipherBlob>,
 }
 
 impl Vault {
@@ -41,18 +67,25 @@ impl Vault {
             etag,
             created_at: now,
             updated_at: now,
+            deleted_at: None,
         })
     }
 
-    pub fn update(
-        &self,
-        expected_etag: &Etag,
-        now: DateTime<Utc>,
-        new_etag: Etag,
-        new_package: VaultPackage,
-    ) -> Result<Self, DomainError> {
-        new_package.validate()?;
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    fn ensure_not_deleted(&self) -> Result<(), DomainError> {
+        if self.is_deleted() {
+            return Err(DomainError::VaultDeleted {
+                vault_id: self.id.0.to_string(),
+            });
+        }
 
+        Ok(())
+    }
+
+    fn ensure_etag(&self, expected_etag: &Etag) -> Result<(), DomainError> {
         if &self.etag != expected_etag {
             return Err(DomainError::ConcurrencyConflict {
                 vault_id: self.id.0.to_string(),
@@ -61,6 +94,39 @@ impl Vault {
             });
         }
 
+        Ok(())
+    }
+
+    pub fn summary(&self) -> VaultSummary {
+        VaultSummary {
+            id: self.id,
+            revision: self.revision,
+            etag: self.etag.clone(),
+            updated_at: self.updated_at,
+            metadata: self.package.metadata.clone(),
+        }
+    }
+
+    pub fn version(&self) -> VaultVersion {
+        VaultVersion {
+            id: self.id,
+            revision: self.revision,
+            etag: self.etag.clone(),
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub fn update(
+        &self,
+        expected_etag: &Etag,
+        now: DateTime<Utc>,
+        new_etag: Etag,
+        new_package: VaultPackage,
+    ) -> Result<Self, DomainError> {
+        self.ensure_not_deleted()?;
+        new_package.validate()?;
+        self.ensure_etag(expected_etag)?;
+
         // Invariant: etag doit changer (sinon update inutile / bug client)
         if new_etag == self.etag {
             return Err(DomainError::Validation {
@@ -77,20 +143,56 @@ impl Vault {
             etag: new_etag,
             created_at: self.created_at,
             updated_at: now,
+            deleted_at: None,
+        })
+    }
+
+    /// Moves the vault to the trash. Content, revision and etag are kept so
+    /// that a restore brings back exactly what was deleted.
+    pub fn delete(&self, expected_etag: &Etag, now: DateTime<Utc>) -> Result<Self, DomainError> {
+        self.ensure_not_deleted()?;
+        self.ensure_etag(expected_etag)?;
+
+        Ok(Self {
+            deleted_at: Some(now),
+            ..self.clone()
+        })
+    }
+
+    /// Takes the vault out of the trash, as long as it was deleted less than
+    /// `grace_period` ago. Past that it is due for purge and treated as gone.
+    pub fn restore(&self, now: DateTime<Utc>, grace_period: Duration) -> Result<Self, DomainError> {
+        let Some(deleted_at) = self.deleted_at else {
+            return Err(DomainError::Validation {
+                field: "deleted_at",
+                message: "vault is not deleted".into(),
+            });
+        };
+
+        if deleted_at + grace_period <= now {
+            return Err(DomainError::VaultNotFound {
+                vault_id: self.id.0.to_string(),
+            });
+        }
+
+        Ok(Self {
+            deleted_at: None,
+            ..self.clone()
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
     use uuid::Uuid;
 
     use crate::{
         shared::errors::DomainError,
         vault::{
             aggregate::Vault,
-            header::{KdfAlg, KdfParams, KdfSpec, VaultHeader},
+            header::{KdfParams, KdfSpec, VaultHeader},
+            key_slot::{KeySlot, KeySlotId, KeySlotKind},
             package::{CipherBlob, VaultPackage},
             value_objects::{CryptoVersion, Etag, OwnerSub, Revision, VaultId},
         },
@@ -100,22 +202,29 @@ mod tests {
         VaultPackage {
             header: VaultHeader {
                 crypto_version: CryptoVersion::V1,
-                kdf: KdfSpec {
-                    alg: KdfAlg::Argon2id,
-                    salt: vec![1; 16],
-                    params: KdfParams {
-                        m_kib: 131_072,
-                        t: 3,
-                        p: 1,
+                key_slots: vec![KeySlot {
+                    id: KeySlotId(Uuid::nil()),
+                    kind: KeySlotKind::MasterPassword {
+                        kdf: KdfSpec {
+                            salt: vec![1; 16],
+                            params: KdfParams::Argon2id {
+                                m_kib: 131_072,
+                                t: 3,
+                                p: 1,
+                            },
+                        },
                     },
-                },
-                wrapped_vault_key: vec![2; 32],
+                    wrapped_vault_key: vec![2; 72],
+                }],
             },
             blob: CipherBlob {
                 nonce: vec![3; 24],
                 aad: vec![],
                 ciphertext: vec![4; 32],
             },
+            metadata: None,
+            attachments: vec![],
+            stored_ciphertext: None,
         }
     }
 
@@ -184,4 +293,77 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn delete_sets_tombstone_and_keeps_version() {
+        let vault = valid_vault();
+        let now = Utc::now();
+
+        let deleted = vault.delete(&Etag::new("etag-1").unwrap(), now).unwrap();
+
+        assert_eq!(deleted.deleted_at, Some(now));
+        assert_eq!(deleted.etag, vault.etag);
+        assert_eq!(deleted.revision, vault.revision);
+    }
+
+    #[test]
+    fn delete_fails_on_wrong_etag() {
+        let result = valid_vault().delete(&Etag::new("wrong").unwrap(), Utc::now());
+
+        assert!(matches!(
+            result,
+            Err(DomainError::ConcurrencyConflict { .. })
+        ));
+    }
+
+    #[test]
+    fn deleted_vault_rejects_updates() {
+        let deleted = valid_vault()
+            .delete(&Etag::new("etag-1").unwrap(), Utc::now())
+            .unwrap();
+
+        let result = deleted.update(
+            &Etag::new("etag-1").unwrap(),
+            Utc::now(),
+            Etag::new("etag-2").unwrap(),
+            valid_package(),
+        );
+        assert!(matches!(result, Err(DomainError::VaultDeleted { .. })));
+
+        let result = deleted.delete(&Etag::new("etag-1").unwrap(), Utc::now());
+        assert!(matches!(result, Err(DomainError::VaultDeleted { .. })));
+    }
+
+    #[test]
+    fn restore_within_grace_period_clears_tombstone() {
+        let deleted_at = Utc::now();
+        let deleted = valid_vault()
+            .delete(&Etag::new("etag-1").unwrap(), deleted_at)
+            .unwrap();
+
+        let restored = deleted
+            .restore(deleted_at + Duration::days(29), Duration::days(30))
+            .unwrap();
+
+        assert!(!restored.is_deleted());
+    }
+
+    #[test]
+    fn restore_after_grace_period_fails() {
+        let deleted_at = Utc::now();
+        let deleted = valid_vault()
+            .delete(&Etag::new("etag-1").unwrap(), deleted_at)
+            .unwrap();
+
+        let result = deleted.restore(deleted_at + Duration::days(30), Duration::days(30));
+
+        assert!(matches!(result, Err(DomainError::VaultNotFound { .. })));
+    }
+
+    #[test]
+    fn restore_of_live_vault_fails() {
+        let result = valid_vault().restore(Utc::now(), Duration::days(30));
+
+        assert!(matches!(result, Err(DomainError::Validation { .. })));
+    }
 }
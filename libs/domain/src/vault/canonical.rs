@@ -0,0 +1,344 @@
+//! Stable byte encoding of a [`VaultPackage`], for content addressing.
+//!
+//! The layout is written by hand instead of going through serde so that it
+//! only changes when we decide it does. Every field is emitted in a fixed
+//! order; byte strings are prefixed with their length as a big-endian `u64`
+//! and integers are fixed-width big-endian. Any change to the layout must
+//! bump [`CANONICAL_TAG`].
+//!
+//! Optional sections are appended after the fields above, each behind a
+//! marker byte, and only when present: packages that do not use them are
+//! encoded as if the section did not exist.
+//!
+//! Bumping [`CANONICAL_TAG`] changes the encoding of every package, and so
+//! every etag: the etags clients hold no longer match and their conditional
+//! writes fail with `412 Precondition Failed` until they fetch the vault
+//! again.
+
+use crate::vault::{
+    header::KdfParams,
+    key_slot::{KeySlot, KeySlotKind},
+    package::VaultPackage,
+};
+
+/// Leads every encoding, so hashes of different layouts never collide.
+pub const CANONICAL_TAG: &[u8] = b"ferrispass/vault-package/v3";
+
+/// Marks the encrypted metadata section.
+const METADATA_MARKER: u8 = 1;
+
+/// Marks the list of referenced attachments.
+const ATTACHMENTS_MARKER: u8 = 2;
+
+/// Marks the size and digest of a ciphertext stored apart from the package.
+/// The attachment holding it is left out, so that the same content streamed
+/// twice hashes alike.
+const STORED_CIPHERTEXT_MARKER: u8 = 3;
+
+impl VaultPackage {
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let blob = &self.blob;
+
+        let mut out = Vec::with_capacity(
+            128 + header.key_slots.len() * 160
+                + blob.nonce.len()
+                + blob.aad.len()
+                + blob.ciphertext.len(),
+        );
+
+        bytes(&mut out, CANONICAL_TAG);
+
+        out.extend_from_slice(&header.crypto_version.0.to_be_bytes());
+        out.extend_from_slice(&(header.key_slots.len() as u64).to_be_bytes());
+        for slot in &header.key_slots {
+            key_slot(&mut out, slot);
+        }
+
+        bytes(&mut out, &blob.nonce);
+        bytes(&mut out, &blob.aad);
+        bytes(&mut out, &blob.ciphertext);
+
+        if let Some(metadata) = &self.metadata {
+            out.push(METADATA_MARKER);
+            bytes(&mut out, &metadata.nonce);
+            bytes(&mut out, &metadata.aad);
+            bytes(&mut out, &metadata.ciphertext);
+        }
+
+        if !self.attachments.is_empty() {
+            out.push(ATTACHMENTS_MARKER);
+            out.extend_from_slice(&(self.attachments.len() as u64).to_be_bytes());
+            for id in &self.attachments {
+                out.extend_from_slice(id.0.as_bytes());
+            }
+        }
+
+        if let Some(stored) = &self.stored_ciphertext {
+            out.push(STORED_CIPHERTEXT_MARKER);
+            out.extend_from_slice(&stored.size.to_be_bytes());
+            bytes(&mut out, &stored.sha256);
+        }
+
+        out
+    }
+}
+
+/// Slot id, kind tag and the fields of that kind, then the wrapped key.
+fn key_slot(out: &mut Vec<u8>, slot: &KeySlot) {
+    out.extend_from_slice(slot.id.0.as_bytes());
+
+    match &slot.kind {
+        KeySlotKind::MasterPassword { kdf } => {
+            bytes(out, b"master-password");
+            bytes(out, kdf_alg_tag(&kdf.params));
+            bytes(out, &kdf.salt);
+            kdf_params(out, &kdf.params);
+        }
+        KeySlotKind::RecoveryKey { salt } => {
+            bytes(out, b"recovery-key");
+            bytes(out, salt);
+        }
+        KeySlotKind::Device { device_id } => {
+            bytes(out, b"device");
+            bytes(out, device_id.as_bytes());
+        }
+        KeySlotKind::PasskeyPrf {
+            credential_id,
+            prf_salt,
+        } => {
+            bytes(out, b"passkey-prf");
+            bytes(out, credential_id);
+            bytes(out, prf_salt);
+        }
+    }
+
+    bytes(out, &slot.wrapped_vault_key);
+}
+
+fn bytes(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+fn kdf_alg_tag(params: &KdfParams) -> &'static [u8] {
+    match params {
+        KdfParams::Argon2id { .. } => b"argon2id",
+        KdfParams::Scrypt { .. } => b"scrypt",
+        KdfParams::Pbkdf2HmacSha256 { .. } => b"pbkdf2-hmac-sha256",
+    }
+}
+
+/// Fixed-width costs; the algorithm tag before them decides how many follow.
+fn kdf_params(out: &mut Vec<u8>, params: &KdfParams) {
+    match *params {
+        KdfParams::Argon2id { m_kib, t, p } => {
+            out.extend_from_slice(&m_kib.to_be_bytes());
+            out.extend_from_slice(&t.to_be_bytes());
+            out.extend_from_slice(&p.to_be_bytes());
+        }
+        KdfParams::Scrypt { log_n, r, p } => {
+            out.push(log_n);
+            out.extend_from_slice(&r.to_be_bytes());
+            out.extend_from_slice(&p.to_be_bytes());
+        }
+        KdfParams::Pbkdf2HmacSha256 { iterations } => {
+            out.extend_from_slice(&iterations.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{
+        attachment::AttachmentId,
+        vault::{
+            header::{KdfParams, KdfSpec, VaultHeader},
+            key_slot::{KeySlot, KeySlotId, KeySlotKind},
+            package::{CipherBlob, StoredCiphertext, VaultPackage},
+            value_objects::CryptoVersion,
+        },
+    };
+
+    fn master_password(params: KdfParams) -> KeySlot {
+        KeySlot {
+            id: KeySlotId(Uuid::from_u128(5)),
+            kind: KeySlotKind::MasterPassword {
+                kdf: KdfSpec {
+                    salt: vec![1; 2],
+                    params,
+                },
+            },
+            wrapped_vault_key: vec![2; 1],
+        }
+    }
+
+    fn package() -> VaultPackage {
+        VaultPackage {
+            header: VaultHeader {
+                crypto_version: CryptoVersion::V1,
+                key_slots: vec![master_password(KdfParams::Argon2id {
+                    m_kib: 65_536,
+                    t: 3,
+                    p: 4,
+                })],
+            },
+            blob: CipherBlob {
+                nonce: vec![3; 1],
+                aad: vec![],
+                ciphertext: vec![4; 2],
+            },
+            metadata: None,
+            attachments: vec![],
+            stored_ciphertext: None,
+        }
+    }
+
+    // Pinned layout: if this test breaks, etags of every stored vault change.
+    #[test]
+    fn encoding_is_pinned() {
+        let len = |n: u8| [0, 0, 0, 0, 0, 0, 0, n];
+        let mut expected = Vec::new();
+        expected.extend(len(27));
+        expected.extend(b"ferrispass/vault-package/v3");
+        expected.extend([0, 1]);
+        expected.extend(len(1));
+        expected.extend(5u128.to_be_bytes());
+        expected.extend(len(15));
+        expected.extend(b"master-password");
+        expected.extend(len(8));
+        expected.extend(b"argon2id");
+        expected.extend(len(2));
+        expected.extend([1, 1]);
+        expected.extend([0, 1, 0, 0, 0, 0, 0, 3, 0, 0, 0, 4]);
+        expected.extend(len(1));
+        expected.extend([2]);
+        expected.extend(len(1));
+        expected.extend([3]);
+        expected.extend(len(0));
+        expected.extend(len(2));
+        expected.extend([4, 4]);
+
+        assert_eq!(package().canonical_bytes(), expected);
+    }
+
+    #[test]
+    fn length_prefixes_keep_fields_apart() {
+        let mut a = package();
+        let mut b = package();
+        a.blob.nonce = vec![3, 3];
+        a.blob.aad = vec![];
+        b.blob.nonce = vec![3];
+        b.blob.aad = vec![3];
+
+        assert_ne!(a.canonical_bytes(), b.canonical_bytes());
+    }
+
+    #[test]
+    fn metadata_is_appended_after_the_blob() {
+        let mut with_metadata = package();
+        with_metadata.metadata = Some(CipherBlob {
+            nonce: vec![6],
+            aad: vec![],
+            ciphertext: vec![7, 7],
+        });
+
+        let mut expected = package().canonical_bytes();
+        expected.push(1);
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 1, 6]);
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 2, 7, 7]);
+
+        assert_eq!(with_metadata.canonical_bytes(), expected);
+    }
+
+    #[test]
+    fn attachments_are_appended_last() {
+        let mut with_attachments = package();
+        with_attachments.attachments = vec![AttachmentId(Uuid::from_u128(7))];
+
+        let mut expected = package().canonical_bytes();
+        expected.push(2);
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        expected.extend(7u128.to_be_bytes());
+
+        assert_eq!(with_attachments.canonical_bytes(), expected);
+    }
+
+    #[test]
+    fn stored_ciphertext_is_hashed_by_size_and_digest() {
+        let stored = |id: u128| StoredCiphertext {
+            attachment_id: AttachmentId(Uuid::from_u128(id)),
+            size: 300,
+            sha256: vec![9, 9],
+        };
+        let mut with_stored = package();
+        with_stored.blob.ciphertext = vec![];
+        let mut expected = with_stored.canonical_bytes();
+        with_stored = with_stored.with_stored_ciphertext(stored(8));
+
+        expected.push(3);
+        expected.extend(300u64.to_be_bytes());
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 2, 9, 9]);
+
+        assert_eq!(with_stored.canonical_bytes(), expected);
+        assert_eq!(
+            package()
+                .with_stored_ciphertext(stored(9))
+                .canonical_bytes(),
+            expected
+        );
+    }
+
+    #[test]
+    fn other_kdfs_encode_their_own_costs() {
+        let mut pbkdf2 = package();
+        pbkdf2.header.key_slots = vec![master_password(KdfParams::Pbkdf2HmacSha256 {
+            iterations: 600_000,
+        })];
+
+        let mut expected = Vec::new();
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 18]);
+        expected.extend(b"pbkdf2-hmac-sha256");
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 2, 1, 1]);
+        expected.extend(600_000u32.to_be_bytes());
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 1, 2]);
+
+        assert!(
+            pbkdf2
+                .canonical_bytes()
+                .windows(expected.len())
+                .any(|window| window == expected)
+        );
+    }
+
+    #[test]
+    fn every_slot_is_encoded_in_order() {
+        let recovery = KeySlot {
+            id: KeySlotId(Uuid::from_u128(6)),
+            kind: KeySlotKind::RecoveryKey { salt: vec![8] },
+            wrapped_vault_key: vec![9],
+        };
+        let mut two_slots = package();
+        two_slots.header.key_slots.push(recovery.clone());
+        let mut swapped = package();
+        swapped.header.key_slots.insert(0, recovery);
+
+        let mut expected = Vec::new();
+        expected.extend(6u128.to_be_bytes());
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 12]);
+        expected.extend(b"recovery-key");
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 1, 8]);
+        expected.extend([0, 0, 0, 0, 0, 0, 0, 1, 9]);
+
+        let bytes = two_slots.canonical_bytes();
+        assert!(
+            bytes
+                .windows(expected.len())
+                .any(|window| window == expected)
+        );
+        assert_ne!(bytes, swapped.canonical_bytes());
+    }
+}
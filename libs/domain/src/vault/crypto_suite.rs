@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    shared::errors::DomainError,
+    vault::{package::CipherBlob, value_objects::CryptoVersion},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AeadAlg {
+    XChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+/// Length a byte field must have under a suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteLen {
+    Exact(usize),
+    /// Only a lower bound, as checked before versions fixed their lengths.
+    AtLeast(usize),
+}
+
+impl ByteLen {
+    pub fn admits(self, len: usize) -> bool {
+        match self {
+            Self::Exact(n) => len == n,
+            Self::AtLeast(n) => len >= n,
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Self::Exact(n) => format!("must be {n} bytes"),
+            Self::AtLeast(n) => format!("must be at least {n} bytes"),
+        }
+    }
+}
+
+/// What a crypto version commits the client to.
+///
+/// Blobs carry their nonce separately and the tag at the end of the
+/// ciphertext. Each key slot wraps the 256-bit vault key as
+/// `nonce || ciphertext || tag`, sealed under the key of its unlock method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CryptoSuite {
+    pub version: CryptoVersion,
+    pub aead: AeadAlg,
+    pub nonce_len: ByteLen,
+    pub tag_len: usize,
+    pub wrapped_key_len: ByteLen,
+    /// Still accepted, but clients should move to a newer version.
+    pub deprecated: bool,
+}
+
+/// Every version the server understands; any other is refused on write.
+const SUITES: &[CryptoSuite] = &[
+    // Written before versions fixed their lengths: only the bounds checked
+    // then still apply, so that vaults saved at the time stay valid.
+    CryptoSuite {
+        version: CryptoVersion::V1,
+        aead: AeadAlg::XChaCha20Poly1305,
+        nonce_len: ByteLen::AtLeast(12),
+        tag_len: 16,
+        wrapped_key_len: ByteLen::AtLeast(32),
+        deprecated: true,
+    },
+    CryptoSuite {
+        version: CryptoVersion::V2,
+        aead: AeadAlg::XChaCha20Poly1305,
+        nonce_len: ByteLen::Exact(24),
+        tag_len: 16,
+        wrapped_key_len: ByteLen::Exact(24 + 32 + 16),
+        deprecated: false,
+    },
+    CryptoSuite {
+        version: CryptoVersion::V3,
+        aead: AeadAlg::Aes256Gcm,
+        nonce_len: ByteLen::Exact(12),
+        tag_len: 16,
+        wrapped_key_len: ByteLen::Exact(12 + 32 + 16),
+        deprecated: false,
+    },
+];
+
+impl CryptoSuite {
+    pub fn all() -> &'static [CryptoSuite] {
+        SUITES
+    }
+
+    pub fn for_version(version: CryptoVersion) -> Result<&'static CryptoSuite, DomainError> {
+        SUITES
+            .iter()
+            .find(|suite| suite.version == version)
+            .ok_or_else(|| DomainError::Validation {
+                field: "crypto_version",
+                message: format!("unsupported version {}", version.0),
+            })
+    }
+
+    pub fn check_wrapped_key(&self, wrapped_key: &[u8]) -> Result<(), DomainError> {
+        if !self.wrapped_key_len.admits(wrapped_key.len()) {
+            return Err(DomainError::Validation {
+                field: "key_slots.wrapped_vault_key",
+                message: self.wrapped_key_len.describe(),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn check_blob(
+        &self,
+        blob: &CipherBlob,
+        nonce_field: &'static str,
+        ciphertext_field: &'static str,
+    ) -> Result<(), DomainError> {
+        self.check_nonce(&blob.nonce, nonce_field)?;
+        self.check_ciphertext_len(blob.ciphertext.len() as u64, ciphertext_field)
+    }
+
+    pub fn check_nonce(&self, nonce: &[u8], field: &'static str) -> Result<(), DomainError> {
+        if !self.nonce_len.admits(nonce.len()) {
+            return Err(DomainError::Validation {
+                field,
+                message: self.nonce_len.describe(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the length of a ciphertext, which may be stored apart from the
+    /// package.
+    pub fn check_ciphertext_len(&self, len: u64, field: &'static str) -> Result<(), DomainError> {
+        if len < self.tag_len as u64 {
+            return Err(DomainError::Validation {
+                field,
+                message: format!("too small to hold the {}-byte tag", self.tag_len),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vault::{
+        crypto_suite::{AeadAlg, CryptoSuite},
+        package::CipherBlob,
+        value_objects::CryptoVersion,
+    };
+
+    fn blob(nonce_len: usize, ciphertext_len: usize) -> CipherBlob {
+        CipherBlob {
+            nonce: vec![1; nonce_len],
+            aad: vec![],
+            ciphertext: vec![2; ciphertext_len],
+        }
+    }
+
+    #[test]
+    fn versions_are_unique() {
+        let suites = CryptoSuite::all();
+
+        for (i, suite) in suites.iter().enumerate() {
+            assert!(suites[i + 1..].iter().all(|s| s.version != suite.version));
+        }
+    }
+
+    #[test]
+    fn unknown_versions_are_refused() {
+        assert!(CryptoSuite::for_version(CryptoVersion(99)).is_err());
+    }
+
+    #[test]
+    fn nonce_length_is_exact_from_v2() {
+        let v2 = CryptoSuite::for_version(CryptoVersion::V2).unwrap();
+        let v3 = CryptoSuite::for_version(CryptoVersion::V3).unwrap();
+
+        assert_eq!(v2.aead, AeadAlg::XChaCha20Poly1305);
+        assert!(v2.check_blob(&blob(24, 16), "nonce", "ciphertext").is_ok());
+        assert!(v2.check_blob(&blob(12, 16), "nonce", "ciphertext").is_err());
+
+        assert_eq!(v3.aead, AeadAlg::Aes256Gcm);
+        assert!(v3.check_blob(&blob(12, 16), "nonce", "ciphertext").is_ok());
+        assert!(v3.check_blob(&blob(24, 16), "nonce", "ciphertext").is_err());
+    }
+
+    #[test]
+    fn v1_keeps_its_legacy_bounds() {
+        let v1 = CryptoSuite::for_version(CryptoVersion::V1).unwrap();
+
+        assert!(v1.deprecated);
+        assert!(v1.check_blob(&blob(12, 16), "nonce", "ciphertext").is_ok());
+        assert!(v1.check_blob(&blob(24, 16), "nonce", "ciphertext").is_ok());
+        assert!(v1.check_blob(&blob(11, 16), "nonce", "ciphertext").is_err());
+        assert!(v1.check_wrapped_key(&[0; 32]).is_ok());
+        assert!(v1.check_wrapped_key(&[0; 100]).is_ok());
+        assert!(v1.check_wrapped_key(&[0; 31]).is_err());
+    }
+
+    #[test]
+    fn ciphertext_must_hold_the_tag() {
+        let v2 = CryptoSuite::for_version(CryptoVersion::V2).unwrap();
+
+        assert!(v2.check_blob(&blob(24, 15), "nonce", "ciphertext").is_err());
+    }
+
+    #[test]
+    fn wrapped_key_length_is_exact_from_v2() {
+        let v2 = CryptoSuite::for_version(CryptoVersion::V2).unwrap();
+        let v3 = CryptoSuite::for_version(CryptoVersion::V3).unwrap();
+
+        assert!(v2.check_wrapped_key(&[0; 72]).is_ok());
+        assert!(v2.check_wrapped_key(&[0; 60]).is_err());
+        assert!(v3.check_wrapped_key(&[0; 60]).is_ok());
+        assert!(v3.check_wrapped_key(&[0; 72]).is_err());
+    }
+}
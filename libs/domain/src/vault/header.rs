@@ -1,27 +1,124 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::{shared::errors::DomainError, vault::value_objects::CryptoVersion};
+use crate::{
+    shared::errors::DomainError,
+    vault::{
+        crypto_suite::CryptoSuite,
+        key_slot::{KeySlot, KeySlotId, KeySlotKind},
+        value_objects::CryptoVersion,
+    },
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct KdfParams {
-    pub m_kib: u32, // memory cost in KiB
-    pub t: u32,     // time cost
-    pub p: u32,     // parallelization factor
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum KdfAlg {
     Argon2id,
+    Scrypt,
+    Pbkdf2HmacSha256,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Cost parameters of the password KDF, shaped by algorithm.
+///
+/// On the wire the variant name travels as `alg` next to the salt, and the
+/// fields as `params`, so Argon2id headers keep the layout they always had.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "alg", content = "params")]
+pub enum KdfParams {
+    Argon2id {
+        m_kib: u32, // memory cost in KiB
+        t: u32,     // time cost
+        p: u32,     // parallelization factor
+    },
+    Scrypt {
+        log_n: u8, // CPU/memory cost, as a power of two
+        r: u32,    // block size
+        p: u32,    // parallelization factor
+    },
+    /// PBKDF2-HMAC-SHA256, for FIPS-constrained clients.
+    Pbkdf2HmacSha256 { iterations: u32 },
+}
+
+impl KdfParams {
+    pub fn alg(&self) -> KdfAlg {
+        match self {
+            KdfParams::Argon2id { .. } => KdfAlg::Argon2id,
+            KdfParams::Scrypt { .. } => KdfAlg::Scrypt,
+            KdfParams::Pbkdf2HmacSha256 { .. } => KdfAlg::Pbkdf2HmacSha256,
+        }
+    }
+
+    /// Every cost knob with its field name. A higher value is always the
+    /// more expensive setting, which is what `KdfPolicy` bounds rely on.
+    pub fn costs(&self) -> Vec<(&'static str, u32)> {
+        match *self {
+            KdfParams::Argon2id { m_kib, t, p } => vec![
+                ("kdf.params.m_kib", m_kib),
+                ("kdf.params.t", t),
+                ("kdf.params.p", p),
+            ],
+            KdfParams::Scrypt { log_n, r, p } => vec![
+                ("kdf.params.log_n", log_n.into()),
+                ("kdf.params.r", r),
+                ("kdf.params.p", p),
+            ],
+            KdfParams::Pbkdf2HmacSha256 { iterations } => {
+                vec![("kdf.params.iterations", iterations)]
+            }
+        }
+    }
+
+    /// Structural checks from each algorithm's specification; cost bounds are
+    /// enforced by `KdfPolicy`.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        for (field, value) in self.costs() {
+            if value < 1 {
+                return Err(DomainError::Validation {
+                    field,
+                    message: "must be at least 1".into(),
+                });
+            }
+        }
+
+        match *self {
+            // RFC 9106: at least 8 KiB of memory per lane.
+            KdfParams::Argon2id { m_kib, p, .. } if u64::from(m_kib) < 8 * u64::from(p) => {
+                Err(DomainError::Validation {
+                    field: "kdf.params.m_kib",
+                    message: "must be at least 8 KiB per lane".into(),
+                })
+            }
+            // RFC 7914: N = 2^log_n must fit below 2^(128 * r / 8), and
+            // r * p must stay below 2^30.
+            KdfParams::Scrypt { log_n, r, .. } if u64::from(log_n) >= 16 * u64::from(r) => {
+                Err(DomainError::Validation {
+                    field: "kdf.params.log_n",
+                    message: "must be less than 16 * r".into(),
+                })
+            }
+            KdfParams::Scrypt { r, p, .. } if u64::from(r) * u64::from(p) >= 1 << 30 => {
+                Err(DomainError::Validation {
+                    field: "kdf.params.p",
+                    message: "r * p must be less than 2^30".into(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KdfSpec {
-    pub alg: KdfAlg,
     pub salt: Vec<u8>,
+    #[serde(flatten)]
     pub params: KdfParams,
 }
 
 impl KdfSpec {
+    pub fn alg(&self) -> KdfAlg {
+        self.params.alg()
+    }
+
+    /// Structural checks only; cost bounds are enforced by `KdfPolicy`.
     pub fn validate(&self) -> Result<(), DomainError> {
         if self.salt.len() < 16 {
             return Err(DomainError::Validation {
@@ -30,63 +127,237 @@ impl KdfSpec {
             });
         }
 
-        if self.params.m_kib < 32 * 1024 {
+        self.params.validate()
+    }
+}
+
+/// Upper bound on key slots, keeping headers small.
+pub const MAX_KEY_SLOTS: usize = 16;
+
+/// Id of the slot a legacy single-slot header is read as. Fixed, so that the
+/// same legacy header always yields the same package and etag.
+pub const LEGACY_SLOT_ID: KeySlotId = KeySlotId(Uuid::nil());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "WireHeader")]
+pub struct VaultHeader {
+    pub crypto_version: CryptoVersion,
+    pub key_slots: Vec<KeySlot>,
+}
+
+/// A header as clients send it: with its key slots, or in the single-slot
+/// layout written before key slots, where the master-password KDF and the
+/// wrapped key sit on the header itself.
+#[derive(Deserialize)]
+struct WireHeader {
+    crypto_version: CryptoVersion,
+    #[serde(default)]
+    key_slots: Option<Vec<KeySlot>>,
+    #[serde(default)]
+    kdf: Option<KdfSpec>,
+    #[serde(default)]
+    wrapped_vault_key: Option<Vec<u8>>,
+}
+
+impl TryFrom<WireHeader> for VaultHeader {
+    type Error = String;
+
+    fn try_from(wire: WireHeader) -> Result<Self, Self::Error> {
+        let key_slots = match (wire.key_slots, wire.kdf, wire.wrapped_vault_key) {
+            (Some(key_slots), None, None) => key_slots,
+            (None, Some(kdf), Some(wrapped_vault_key)) => vec![KeySlot {
+                id: LEGACY_SLOT_ID,
+                kind: KeySlotKind::MasterPassword { kdf },
+                wrapped_vault_key,
+            }],
+            (Some(_), _, _) => {
+                return Err("key_slots cannot be combined with kdf or wrapped_vault_key".into());
+            }
+            (None, _, _) => {
+                return Err("expected key_slots, or kdf with wrapped_vault_key".into());
+            }
+        };
+
+        Ok(Self {
+            crypto_version: wire.crypto_version,
+            key_slots,
+        })
+    }
+}
+
+impl VaultHeader {
+    /// Checks the header against its crypto version and returns that
+    /// version's suite, which the rest of the package must also follow.
+    pub fn validate(&self) -> Result<&'static CryptoSuite, DomainError> {
+        let suite = CryptoSuite::for_version(self.crypto_version)?;
+
+        if self.key_slots.is_empty() {
             return Err(DomainError::Validation {
-                field: "kdf.params.m_kib",
-                message: "must be at least 32 MiB (32768 KiB)".into(),
+                field: "key_slots",
+                message: "at least one slot must remain".into(),
             });
         }
 
-        if self.params.t < 1 {
+        if self.key_slots.len() > MAX_KEY_SLOTS {
             return Err(DomainError::Validation {
-                field: "kdf.params.t",
-                message: "must be at least 1".into(),
+                field: "key_slots",
+                message: format!("at most {MAX_KEY_SLOTS} slots"),
             });
         }
 
-        if self.params.p < 1 {
-            return Err(DomainError::Validation {
-                field: "kdf.params.p",
-                message: "must be at least 1".into(),
-            });
+        for (i, slot) in self.key_slots.iter().enumerate() {
+            if self.key_slots[..i].iter().any(|other| other.id == slot.id) {
+                return Err(DomainError::Validation {
+                    field: "key_slots.id",
+                    message: format!("duplicate slot {}", slot.id.0),
+                });
+            }
+
+            slot.validate()?;
+            suite.check_wrapped_key(&slot.wrapped_vault_key)?;
         }
 
-        Ok(())
+        Ok(suite)
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VaultHeader {
-    pub crypto_version: CryptoVersion,
-    pub kdf: KdfSpec,
-    pub wrapped_vault_key: Vec<u8>,
-}
+    pub fn slot(&self, id: KeySlotId) -> Option<&KeySlot> {
+        self.key_slots.iter().find(|slot| slot.id == id)
+    }
 
-impl VaultHeader {
-    pub fn validate(&self) -> Result<(), DomainError> {
-        self.kdf.validate()?;
+    /// KDF settings of the master-password slots.
+    pub fn kdfs(&self) -> impl Iterator<Item = &KdfSpec> {
+        self.key_slots.iter().filter_map(KeySlot::kdf)
+    }
 
-        if self.wrapped_vault_key.len() < 32 {
-            return Err(DomainError::Validation {
-                field: "wrapped_vault_key",
-                message: "too small (min 32 bytes)".into(),
-            });
-        }
+    pub fn with_slot(&self, slot: KeySlot) -> Self {
+        let mut header = self.clone();
+        header.key_slots.push(slot);
+        header
+    }
 
-        Ok(())
+    pub fn without_slot(&self, id: KeySlotId) -> Self {
+        let mut header = self.clone();
+        header.key_slots.retain(|slot| slot.id != id);
+        header
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::vault::header::{KdfAlg, KdfParams, KdfSpec};
+    use uuid::Uuid;
+
+    use crate::{
+        shared::errors::DomainError,
+        vault::{
+            header::{KdfAlg, KdfParams, KdfSpec, LEGACY_SLOT_ID, MAX_KEY_SLOTS, VaultHeader},
+            key_slot::{KeySlot, KeySlotId, KeySlotKind},
+            value_objects::CryptoVersion,
+        },
+    };
+
+    fn recovery_slot() -> KeySlot {
+        KeySlot {
+            id: KeySlotId(Uuid::new_v4()),
+            kind: KeySlotKind::RecoveryKey { salt: vec![1; 16] },
+            wrapped_vault_key: vec![2; 72],
+        }
+    }
+
+    fn header(key_slots: Vec<KeySlot>) -> VaultHeader {
+        VaultHeader {
+            crypto_version: CryptoVersion::V1,
+            key_slots,
+        }
+    }
+
+    #[test]
+    fn legacy_single_slot_header_reads_as_a_master_password_slot() {
+        let header: VaultHeader = serde_json::from_value(serde_json::json!({
+            "crypto_version": 1,
+            "kdf": {
+                "alg": "Argon2id",
+                "salt": vec![1; 16],
+                "params": { "m_kib": 131_072, "t": 3, "p": 1 },
+            },
+            "wrapped_vault_key": vec![2; 48],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            header.key_slots,
+            vec![KeySlot {
+                id: LEGACY_SLOT_ID,
+                kind: KeySlotKind::MasterPassword {
+                    kdf: KdfSpec {
+                        salt: vec![1; 16],
+                        params: KdfParams::Argon2id {
+                            m_kib: 131_072,
+                            t: 3,
+                            p: 1,
+                        },
+                    },
+                },
+                wrapped_vault_key: vec![2; 48],
+            }]
+        );
+        assert!(header.validate().is_ok());
+        // Written back, it takes the key-slot layout.
+        assert!(serde_json::to_value(&header).unwrap()["key_slots"].is_array());
+    }
+
+    #[test]
+    fn header_layouts_cannot_be_mixed() {
+        let slots = serde_json::to_value(header(vec![recovery_slot()])).unwrap();
+        let mut mixed = slots.clone();
+        mixed["wrapped_vault_key"] = serde_json::json!(vec![2; 48]);
+
+        assert!(serde_json::from_value::<VaultHeader>(slots).is_ok());
+        assert!(serde_json::from_value::<VaultHeader>(mixed).is_err());
+        assert!(
+            serde_json::from_value::<VaultHeader>(serde_json::json!({ "crypto_version": 1 }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn at_least_one_slot_must_remain() {
+        let slot = recovery_slot();
+        let header = header(vec![slot.clone()]);
+
+        assert!(header.validate().is_ok());
+        assert!(header.without_slot(slot.id).validate().is_err());
+    }
+
+    #[test]
+    fn slots_are_unique_and_bounded() {
+        let slot = recovery_slot();
+        assert!(header(vec![slot.clone(), slot]).validate().is_err());
+
+        let many = (0..=MAX_KEY_SLOTS).map(|_| recovery_slot()).collect();
+        assert!(header(many).validate().is_err());
+    }
+
+    #[test]
+    fn every_slot_wraps_a_full_key() {
+        let mut slot = recovery_slot();
+        slot.wrapped_vault_key = vec![2; 32];
+        let mut header = header(vec![recovery_slot(), slot]);
+        header.crypto_version = CryptoVersion::V2;
+
+        assert!(matches!(
+            header.validate(),
+            Err(DomainError::Validation {
+                field: "key_slots.wrapped_vault_key",
+                ..
+            })
+        ));
+    }
 
     #[test]
     fn salt_must_be_minimum_size() {
         let spec = KdfSpec {
-            alg: KdfAlg::Argon2id,
             salt: vec![1; 8],
-            params: KdfParams {
+            params: KdfParams::Argon2id {
                 m_kib: 131_072,
                 t: 3,
                 p: 1,
@@ -97,15 +368,50 @@ mod tests {
     }
 
     #[test]
-    fn memory_must_be_sufficient() {
+    fn argon2id_wire_format_is_unchanged() {
+        let spec: KdfSpec = serde_json::from_str(
+            r#"{"alg":"Argon2id","salt":[1],"params":{"m_kib":65536,"t":3,"p":4}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.alg(), KdfAlg::Argon2id);
+        assert_eq!(
+            spec.params,
+            KdfParams::Argon2id {
+                m_kib: 65_536,
+                t: 3,
+                p: 4
+            }
+        );
+    }
+
+    #[test]
+    fn params_must_match_the_algorithm() {
+        let result = serde_json::from_str::<KdfSpec>(
+            r#"{"alg":"Scrypt","salt":[1],"params":{"m_kib":65536,"t":3,"p":4}}"#,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn scrypt_rules_follow_rfc_7914() {
+        let spec = |log_n, r, p| KdfSpec {
+            salt: vec![1; 16],
+            params: KdfParams::Scrypt { log_n, r, p },
+        };
+
+        assert!(spec(17, 8, 1).validate().is_ok());
+        assert!(spec(0, 8, 1).validate().is_err());
+        assert!(spec(16, 1, 1).validate().is_err());
+        assert!(spec(17, 1 << 15, 1 << 15).validate().is_err());
+    }
+
+    #[test]
+    fn pbkdf2_needs_iterations() {
         let spec = KdfSpec {
-            alg: KdfAlg::Argon2id,
             salt: vec![1; 16],
-            params: KdfParams {
-                m_kib: 1024,
-                t: 3,
-                p: 1,
-            },
+            params: KdfParams::Pbkdf2HmacSha256 { iterations: 0 },
         };
 
         assert!(spec.validate().is_err());
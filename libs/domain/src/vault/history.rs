@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::vault::{
+    aggregate::Vault,
+    package::VaultPackage,
+    value_objects::{Etag, Revision, VaultId},
+};
+
+/// A past revision of a vault, kept after a newer one replaced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultRevision {
+    pub vault_id: VaultId,
+    pub revision: Revision,
+    pub etag: Etag,
+    pub package: VaultPackage,
+
+    /// When this revision was written.
+    pub saved_at: DateTime<Utc>,
+    /// When a newer revision replaced it.
+    pub archived_at: DateTime<Utc>,
+}
+
+impl VaultRevision {
+    /// Archives the current state of `vault`, replaced at `archived_at`.
+    pub fn archive(vault: &Vault, archived_at: DateTime<Utc>) -> Self {
+        Self {
+            vault_id: vault.id,
+            revision: vault.revision,
+            etag: vault.etag.clone(),
+            package: vault.package.clone(),
+            saved_at: vault.updated_at,
+            archived_at,
+        }
+    }
+
+    pub fn summary(&self) -> RevisionSummary {
+        RevisionSummary {
+            revision: self.revision,
+            etag: self.etag.clone(),
+            saved_at: self.saved_at,
+            archived_at: self.archived_at,
+        }
+    }
+}
+
+/// Revision metadata, without the ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionSummary {
+    pub revision: Revision,
+    pub etag: Etag,
+    pub saved_at: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// How much history is kept per vault. Both limits apply; `None` means
+/// unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// Most archived revisions kept, newest first.
+    pub max_revisions: Option<usize>,
+    /// Archived revisions older than this, counted from when they were
+    /// replaced, are dropped.
+    pub max_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn keep_all() -> Self {
+        Self::default()
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.max_revisions.is_none() && self.max_age.is_none()
+    }
+
+    /// Revisions of `history` that fall outside the policy at `now`.
+    pub fn expired(&self, history: &[RevisionSummary], now: DateTime<Utc>) -> Vec<Revision> {
+        let mut newest_first: Vec<&RevisionSummary> = history.iter().collect();
+        newest_first.sort_by_key(|summary| Reverse(summary.revision));
+
+        newest_first
+            .into_iter()
+            .enumerate()
+            .filter(|(index, summary)| {
+                self.max_revisions.is_some_and(|max| *index >= max)
+                    || self
+                        .max_age
+                        .is_some_and(|age| summary.archived_at + age < now)
+            })
+            .map(|(_, summary)| summary.revision)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use crate::vault::{
+        history::{RetentionPolicy, RevisionSummary},
+        value_objects::{Etag, Revision},
+    };
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_770_000_000, 0).unwrap()
+    }
+
+    /// Revisions 0..n, revision `i` archived `n - i` days ago.
+    fn history(n: u64) -> Vec<RevisionSummary> {
+        (0..n)
+            .map(|i| RevisionSummary {
+                revision: Revision(i),
+                etag: Etag::new(format!("etag-{i}")).unwrap(),
+                saved_at: now() - Duration::days((n - i + 1) as i64),
+                archived_at: now() - Duration::days((n - i) as i64),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keep_all_expires_nothing() {
+        assert!(
+            RetentionPolicy::keep_all()
+                .expired(&history(5), now())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn count_limit_keeps_newest() {
+        let policy = RetentionPolicy {
+            max_revisions: Some(2),
+            max_age: None,
+        };
+
+        assert_eq!(
+            policy.expired(&history(5), now()),
+            vec![Revision(2), Revision(1), Revision(0)]
+        );
+    }
+
+    #[test]
+    fn age_limit_counts_from_archival() {
+        let policy = RetentionPolicy {
+            max_revisions: None,
+            max_age: Some(Duration::days(3)),
+        };
+
+        // Archived 5, 4, 3, 2 and 1 days ago.
+        assert_eq!(
+            policy.expired(&history(5), now()),
+            vec![Revision(1), Revision(0)]
+        );
+    }
+
+    #[test]
+    fn both_limits_apply() {
+        let policy = RetentionPolicy {
+            max_revisions: Some(1),
+            max_age: Some(Duration::days(3)),
+        };
+
+        assert_eq!(policy.expired(&history(5), now()).len(), 4);
+    }
+}
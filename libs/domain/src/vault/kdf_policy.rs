@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    shared::errors::DomainError,
+    vault::header::{KdfAlg, KdfParams, KdfSpec, VaultHeader},
+};
+
+/// Cost bounds for one KDF algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfBounds {
+    minimum: KdfParams,
+    recommended: KdfParams,
+    maximum: KdfParams,
+}
+
+impl KdfBounds {
+    pub fn new(
+        minimum: KdfParams,
+        recommended: KdfParams,
+        maximum: KdfParams,
+    ) -> Result<Self, DomainError> {
+        if recommended.alg() != minimum.alg() || maximum.alg() != minimum.alg() {
+            return Err(DomainError::Validation {
+                field: "kdf_policy",
+                message: "bounds must all use the same algorithm".into(),
+            });
+        }
+
+        if minimum.validate().is_err() {
+            return Err(DomainError::Validation {
+                field: "kdf_policy.minimum",
+                message: "must be valid parameters for the algorithm".into(),
+            });
+        }
+
+        if !at_most(&minimum, &recommended) {
+            return Err(DomainError::Validation {
+                field: "kdf_policy.recommended",
+                message: "must not be below the minimum".into(),
+            });
+        }
+
+        if !at_most(&recommended, &maximum) {
+            return Err(DomainError::Validation {
+                field: "kdf_policy.maximum",
+                message: "must not be below the recommended level".into(),
+            });
+        }
+
+        Ok(Self {
+            minimum,
+            recommended,
+            maximum,
+        })
+    }
+
+    pub fn alg(&self) -> KdfAlg {
+        self.minimum.alg()
+    }
+
+    pub fn minimum(&self) -> &KdfParams {
+        &self.minimum
+    }
+
+    pub fn recommended(&self) -> &KdfParams {
+        &self.recommended
+    }
+
+    pub fn maximum(&self) -> &KdfParams {
+        &self.maximum
+    }
+
+    fn check(&self, params: &KdfParams) -> Result<(), DomainError> {
+        let bounds = params
+            .costs()
+            .into_iter()
+            .zip(self.minimum.costs())
+            .zip(self.maximum.costs());
+
+        for (((field, value), (_, min)), (_, max)) in bounds {
+            if value < min {
+                return Err(DomainError::Validation {
+                    field,
+                    message: format!("must be at least {min}"),
+                });
+            }
+
+            if value > max {
+                return Err(DomainError::Validation {
+                    field,
+                    message: format!("must be at most {max}"),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Server-side rules for the password KDF: which algorithms are accepted,
+/// and the cost bounds of each.
+///
+/// Configured at startup so costs can be raised over time without a release.
+/// Writes outside `minimum..=maximum` are refused; the maximum keeps low-end
+/// clients from being handed parameters they cannot run. Stored headers below
+/// `recommended`, or using an algorithm no longer allowed, stay readable but
+/// are flagged for an upgrade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfPolicy {
+    allowed: BTreeMap<KdfAlg, KdfBounds>,
+}
+
+impl KdfPolicy {
+    /// Allows exactly the algorithms given bounds for.
+    pub fn new(bounds: impl IntoIterator<Item = KdfBounds>) -> Result<Self, DomainError> {
+        let mut allowed = BTreeMap::new();
+
+        for bounds in bounds {
+            if allowed.insert(bounds.alg(), bounds).is_some() {
+                return Err(DomainError::Validation {
+                    field: "kdf_policy",
+                    message: "an algorithm is configured twice".into(),
+                });
+            }
+        }
+
+        if allowed.is_empty() {
+            return Err(DomainError::Validation {
+                field: "kdf_policy",
+                message: "at least one algorithm must be allowed".into(),
+            });
+        }
+
+        Ok(Self { allowed })
+    }
+
+    pub fn bounds(&self, alg: KdfAlg) -> Option<&KdfBounds> {
+        self.allowed.get(&alg)
+    }
+
+    /// Refuses a disallowed algorithm or a cost outside its bounds.
+    pub fn check(&self, spec: &KdfSpec) -> Result<(), DomainError> {
+        let bounds = self
+            .bounds(spec.alg())
+            .ok_or_else(|| DomainError::Validation {
+                field: "kdf.alg",
+                message: "not allowed by the server policy".into(),
+            })?;
+
+        bounds.check(&spec.params)
+    }
+
+    /// Checks every master-password slot of `header`.
+    pub fn check_header(&self, header: &VaultHeader) -> Result<(), DomainError> {
+        header.kdfs().try_for_each(|kdf| self.check(kdf))
+    }
+
+    /// Whether any master-password slot of `header` should be upgraded.
+    pub fn header_upgrade_recommended(&self, header: &VaultHeader) -> bool {
+        header.kdfs().any(|kdf| self.upgrade_recommended(kdf))
+    }
+
+    /// Whether the client should re-derive its key with stronger parameters,
+    /// or with an algorithm the policy still allows.
+    pub fn upgrade_recommended(&self, spec: &KdfSpec) -> bool {
+        match self.bounds(spec.alg()) {
+            Some(bounds) => !at_most(&bounds.recommended, &spec.params),
+            None => true,
+        }
+    }
+}
+
+impl Default for KdfPolicy {
+    /// All supported algorithms. Argon2id between 32 MiB and 1 GiB,
+    /// recommending 128 MiB with three passes; scrypt from N = 2^15 with
+    /// r = 8; PBKDF2 from the 600 000 iterations OWASP advises.
+    fn default() -> Self {
+        let argon2id = KdfBounds {
+            minimum: KdfParams::Argon2id {
+                m_kib: 32 * 1024,
+                t: 1,
+                p: 1,
+            },
+            recommended: KdfParams::Argon2id {
+                m_kib: 128 * 1024,
+                t: 3,
+                p: 1,
+            },
+            maximum: KdfParams::Argon2id {
+                m_kib: 1024 * 1024,
+                t: 10,
+                p: 16,
+            },
+        };
+        let scrypt = KdfBounds {
+            minimum: KdfParams::Scrypt {
+                log_n: 15,
+                r: 8,
+                p: 1,
+            },
+            recommended: KdfParams::Scrypt {
+                log_n: 17,
+                r: 8,
+                p: 1,
+            },
+            maximum: KdfParams::Scrypt {
+                log_n: 20,
+                r: 16,
+                p: 16,
+            },
+        };
+        let pbkdf2 = KdfBounds {
+            minimum: KdfParams::Pbkdf2HmacSha256 {
+                iterations: 600_000,
+            },
+            recommended: KdfParams::Pbkdf2HmacSha256 {
+                iterations: 600_000,
+            },
+            maximum: KdfParams::Pbkdf2HmacSha256 {
+                iterations: 10_000_000,
+            },
+        };
+
+        Self {
+            allowed: [argon2id, scrypt, pbkdf2]
+                .into_iter()
+                .map(|bounds| (bounds.alg(), bounds))
+                .collect(),
+        }
+    }
+}
+
+/// Same algorithm, and every cost of `lower` is at most the matching cost of
+/// `upper`.
+fn at_most(lower: &KdfParams, upper: &KdfParams) -> bool {
+    lower.alg() == upper.alg()
+        && lower
+            .costs()
+            .into_iter()
+            .zip(upper.costs())
+            .all(|((_, low), (_, high))| low <= high)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        shared::errors::DomainError,
+        vault::{
+            header::{KdfAlg, KdfParams, KdfSpec},
+            kdf_policy::{KdfBounds, KdfPolicy},
+        },
+    };
+
+    fn spec(params: KdfParams) -> KdfSpec {
+        KdfSpec {
+            salt: vec![1; 16],
+            params,
+        }
+    }
+
+    fn argon2id(m_kib: u32, t: u32, p: u32) -> KdfSpec {
+        spec(KdfParams::Argon2id { m_kib, t, p })
+    }
+
+    #[test]
+    fn memory_must_be_sufficient() {
+        let result = KdfPolicy::default().check(&argon2id(1024, 3, 1));
+
+        assert!(matches!(
+            result,
+            Err(DomainError::Validation {
+                field: "kdf.params.m_kib",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn costs_above_the_maximum_are_refused() {
+        let result = KdfPolicy::default().check(&argon2id(131_072, 3, 64));
+
+        assert!(matches!(
+            result,
+            Err(DomainError::Validation {
+                field: "kdf.params.p",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn upgrade_is_recommended_below_the_recommended_level() {
+        let policy = KdfPolicy::default();
+
+        assert!(policy.check(&argon2id(65_536, 3, 1)).is_ok());
+        assert!(policy.upgrade_recommended(&argon2id(65_536, 3, 1)));
+        assert!(policy.upgrade_recommended(&argon2id(131_072, 2, 1)));
+        assert!(!policy.upgrade_recommended(&argon2id(131_072, 3, 1)));
+        assert!(!policy.upgrade_recommended(&argon2id(262_144, 4, 2)));
+    }
+
+    #[test]
+    fn each_algorithm_has_its_own_bounds() {
+        let policy = KdfPolicy::default();
+
+        let scrypt = |log_n| spec(KdfParams::Scrypt { log_n, r: 8, p: 1 });
+        assert!(policy.check(&scrypt(17)).is_ok());
+        assert!(matches!(
+            policy.check(&scrypt(14)),
+            Err(DomainError::Validation {
+                field: "kdf.params.log_n",
+                ..
+            })
+        ));
+
+        let pbkdf2 = |iterations| spec(KdfParams::Pbkdf2HmacSha256 { iterations });
+        assert!(policy.check(&pbkdf2(600_000)).is_ok());
+        assert!(policy.check(&pbkdf2(100_000)).is_err());
+    }
+
+    #[test]
+    fn disallowed_algorithms_are_refused_and_flagged() {
+        let argon2id_only =
+            KdfPolicy::new(KdfPolicy::default().bounds(KdfAlg::Argon2id).cloned()).unwrap();
+        let pbkdf2 = spec(KdfParams::Pbkdf2HmacSha256 {
+            iterations: 600_000,
+        });
+
+        assert!(matches!(
+            argon2id_only.check(&pbkdf2),
+            Err(DomainError::Validation {
+                field: "kdf.alg",
+                ..
+            })
+        ));
+        assert!(argon2id_only.upgrade_recommended(&pbkdf2));
+    }
+
+    #[test]
+    fn bounds_must_be_ordered_and_of_one_algorithm() {
+        let low = KdfParams::Argon2id {
+            m_kib: 32 * 1024,
+            t: 1,
+            p: 1,
+        };
+        let high = KdfParams::Argon2id {
+            m_kib: 256 * 1024,
+            t: 4,
+            p: 4,
+        };
+        let other = KdfParams::Pbkdf2HmacSha256 {
+            iterations: 600_000,
+        };
+
+        assert!(KdfBounds::new(low.clone(), high.clone(), high.clone()).is_ok());
+        assert!(KdfBounds::new(high.clone(), low.clone(), high.clone()).is_err());
+        assert!(KdfBounds::new(low.clone(), high.clone(), low.clone()).is_err());
+        assert!(KdfBounds::new(low, high, other).is_err());
+    }
+
+    #[test]
+    fn policy_needs_an_algorithm() {
+        assert!(KdfPolicy::new([]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{shared::errors::DomainError, vault::header::KdfSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeySlotId(pub Uuid);
+
+/// How the client obtains the key that unwraps a slot, with what it needs
+/// to do so again on another device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum KeySlotKind {
+    /// Key derived from the master password.
+    MasterPassword { kdf: KdfSpec },
+    /// Key derived with HKDF from a high-entropy recovery key printed for the
+    /// user; no password hashing cost is needed.
+    RecoveryKey { salt: Vec<u8> },
+    /// Key held in the keystore of one device, named by the client.
+    Device { device_id: String },
+    /// Output of the WebAuthn PRF extension for one passkey.
+    PasskeyPrf {
+        credential_id: Vec<u8>,
+        prf_salt: Vec<u8>,
+    },
+}
+
+/// One copy of the vault key, wrapped under a key obtained by one unlock
+/// method. Every slot wraps the same vault key, so any of them opens the
+/// vault.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySlot {
+    pub id: KeySlotId,
+    #[serde(flatten)]
+    pub kind: KeySlotKind,
+    pub wrapped_vault_key: Vec<u8>,
+}
+
+impl KeySlot {
+    /// Structural checks of the unlock metadata; the wrapped key length
+    /// depends on the crypto version and is checked with it.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        match &self.kind {
+            KeySlotKind::MasterPassword { kdf } => kdf.validate(),
+            KeySlotKind::RecoveryKey { salt } if salt.len() < 16 => Err(DomainError::Validation {
+                field: "key_slots.salt",
+                message: "must be at least 16 bytes".into(),
+            }),
+            KeySlotKind::Device { device_id } if device_id.trim().is_empty() => {
+                Err(DomainError::Validation {
+                    field: "key_slots.device_id",
+                    message: "must not be empty".into(),
+                })
+            }
+            KeySlotKind::PasskeyPrf { credential_id, .. } if credential_id.is_empty() => {
+                Err(DomainError::Validation {
+                    field: "key_slots.credential_id",
+                    message: "must not be empty".into(),
+                })
+            }
+            KeySlotKind::PasskeyPrf { prf_salt, .. } if prf_salt.len() < 16 => {
+                Err(DomainError::Validation {
+                    field: "key_slots.prf_salt",
+                    message: "must be at least 16 bytes".into(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn kdf(&self) -> Option<&KdfSpec> {
+        match &self.kind {
+            KeySlotKind::MasterPassword { kdf } => Some(kdf),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::vault::{
+        header::{KdfParams, KdfSpec},
+        key_slot::{KeySlot, KeySlotId, KeySlotKind},
+    };
+
+    fn slot(kind: KeySlotKind) -> KeySlot {
+        KeySlot {
+            id: KeySlotId(Uuid::nil()),
+            kind,
+            wrapped_vault_key: vec![2; 72],
+        }
+    }
+
+    #[test]
+    fn wire_format_tags_the_kind() {
+        let master = slot(KeySlotKind::MasterPassword {
+            kdf: KdfSpec {
+                salt: vec![1; 16],
+                params: KdfParams::Pbkdf2HmacSha256 {
+                    iterations: 600_000,
+                },
+            },
+        });
+
+        let json = serde_json::to_value(&master).unwrap();
+        assert_eq!(json["kind"], "MasterPassword");
+        assert_eq!(json["kdf"]["alg"], "Pbkdf2HmacSha256");
+        assert_eq!(serde_json::from_value::<KeySlot>(json).unwrap(), master);
+    }
+
+    #[test]
+    fn unlock_metadata_is_checked() {
+        assert!(
+            slot(KeySlotKind::RecoveryKey { salt: vec![1; 16] })
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            slot(KeySlotKind::RecoveryKey { salt: vec![1; 8] })
+                .validate()
+                .is_err()
+        );
+        assert!(
+            slot(KeySlotKind::Device {
+                device_id: " ".into()
+            })
+            .validate()
+            .is_err()
+        );
+        assert!(
+            slot(KeySlotKind::PasskeyPrf {
+                credential_id: vec![],
+                prf_salt: vec![1; 32],
+            })
+            .validate()
+            .is_err()
+        );
+        assert!(
+            slot(KeySlotKind::PasskeyPrf {
+                credential_id: vec![7; 16],
+                prf_salt: vec![1; 32],
+            })
+            .validate()
+            .is_ok()
+        );
+    }
+}
@@ -1,9 +1,19 @@
 pub mod aggregate;
+pub mod canonical;
+pub mod crypto_suite;
 pub mod header;
+pub mod history;
+pub mod kdf_policy;
+pub mod key_slot;
 pub mod package;
 pub mod value_objects;
 
 pub use aggregate::*;
+pub use canonical::*;
+pub use crypto_suite::*;
 pub use header::*;
+pub use history::*;
+pub use kdf_policy::*;
+pub use key_slot::*;
 pub use package::*;
 pub use value_objects::*;
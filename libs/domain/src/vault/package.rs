@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{shared::errors::DomainError, vault::header::VaultHeader};
+use crate::{attachment::AttachmentId, shared::errors::DomainError, vault::header::VaultHeader};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Most attachments one package may reference.
+pub const MAX_ATTACHMENTS: usize = 1024;
+
+/// Length of the SHA-256 digest of a stored ciphertext.
+pub const STORED_CIPHERTEXT_SHA256_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CipherBlob {
     pub nonce: Vec<u8>,
     pub aad: Vec<u8>,
@@ -10,36 +16,187 @@ pub struct CipherBlob {
 }
 
 impl CipherBlob {
+    pub fn byte_size(&self) -> u64 {
+        (self.nonce.len() + self.aad.len() + self.ciphertext.len()) as u64
+    }
+}
+
+/// Ciphertext of a blob kept in the blob store, as the chunks of a completed
+/// attachment, instead of inline. Large vaults are streamed this way so that
+/// the server never holds them in memory whole.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCiphertext {
+    pub attachment_id: AttachmentId,
+    pub size: u64,
+    pub sha256: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultPackage {
+    pub header: VaultHeader,
+    pub blob: CipherBlob,
+
+    /// Name and other descriptive fields of the vault, encrypted by the
+    /// client so that the server can list vaults without reading them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CipherBlob>,
+
+    /// Attachments whose keys are held inside the ciphertext. Listed in the
+    /// clear so that attachments no revision references can be collected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AttachmentId>,
+
+    /// Where the blob ciphertext is when it was streamed; `blob.ciphertext`
+    /// is then empty. Only the server sets it, after storing the stream.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub stored_ciphertext: Option<StoredCiphertext>,
+}
+
+impl VaultPackage {
+    /// Encrypted bytes the package stores inline: blob and metadata. Quotas
+    /// count this; the header is small and bounded, so it is left out. A
+    /// stored ciphertext is counted with the attachments instead.
+    pub fn byte_size(&self) -> u64 {
+        self.blob.byte_size() + self.metadata.as_ref().map_or(0, CipherBlob::byte_size)
+    }
+
+    /// [`byte_size`](Self::byte_size) plus the stored ciphertext, if any:
+    /// what the package size limit applies to.
+    pub fn full_byte_size(&self) -> u64 {
+        self.byte_size() + self.stored_ciphertext.as_ref().map_or(0, |s| s.size)
+    }
+
+    /// Listed attachments, then the one holding the stored ciphertext.
+    pub fn referenced_attachments(&self) -> impl Iterator<Item = &AttachmentId> {
+        self.attachments
+            .iter()
+            .chain(self.stored_ciphertext.iter().map(|s| &s.attachment_id))
+    }
+
+    pub fn with_stored_ciphertext(mut self, stored: StoredCiphertext) -> Self {
+        self.blob.ciphertext.clear();
+        self.stored_ciphertext = Some(stored);
+        self
+    }
+
     pub fn validate(&self) -> Result<(), DomainError> {
-        if self.nonce.len() < 12 {
+        let suite = self.header.validate()?;
+        match &self.stored_ciphertext {
+            None => suite.check_blob(&self.blob, "blob.nonce", "blob.ciphertext")?,
+            Some(stored) => {
+                suite.check_nonce(&self.blob.nonce, "blob.nonce")?;
+                if !self.blob.ciphertext.is_empty() {
+                    return Err(DomainError::Validation {
+                        field: "blob.ciphertext",
+                        message: "must be empty when the ciphertext is stored".into(),
+                    });
+                }
+                suite.check_ciphertext_len(stored.size, "stored_ciphertext.size")?;
+                if stored.sha256.len() != STORED_CIPHERTEXT_SHA256_LEN {
+                    return Err(DomainError::Validation {
+                        field: "stored_ciphertext.sha256",
+                        message: format!("must be {STORED_CIPHERTEXT_SHA256_LEN} bytes"),
+                    });
+                }
+            }
+        }
+        if let Some(metadata) = &self.metadata {
+            suite.check_blob(metadata, "metadata.nonce", "metadata.ciphertext")?;
+        }
+
+        if self.attachments.len() > MAX_ATTACHMENTS {
             return Err(DomainError::Validation {
-                field: "blob.nonce",
-                message: "nonce too small (min 12 bytes)".into(),
+                field: "attachments",
+                message: format!("at most {MAX_ATTACHMENTS} attachments"),
             });
         }
 
-        if self.ciphertext.len() < 16 {
+        for (i, id) in self.attachments.iter().enumerate() {
+            if self.attachments[..i].contains(id) {
+                return Err(DomainError::Validation {
+                    field: "attachments",
+                    message: format!("duplicate attachment {}", id.0),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Same ciphertext under a new header, as after a master-password
+    /// change. The blob stays sealed under the vault key, so the crypto
+    /// version it was written with cannot change.
+    pub fn with_header(&self, header: VaultHeader) -> Result<Self, DomainError> {
+        if header.crypto_version != self.header.crypto_version {
             return Err(DomainError::Validation {
-                field: "blob.ciphertext",
-                message: "ciphertext too small (min 16 bytes)".into(),
+                field: "crypto_version",
+                message: "must match the version the blob was encrypted with".into(),
             });
         }
 
-        Ok(())
+        Ok(Self {
+            header,
+            blob: self.blob.clone(),
+            metadata: self.metadata.clone(),
+            attachments: self.attachments.clone(),
+            stored_ciphertext: self.stored_ciphertext.clone(),
+        })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VaultPackage {
-    pub header: VaultHeader,
-    pub blob: CipherBlob,
-}
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
 
-impl VaultPackage {
-    pub fn validate(&self) -> Result<(), DomainError> {
-        self.header.validate()?;
-        self.blob.validate()?;
+    use crate::vault::{
+        header::{KdfParams, KdfSpec, VaultHeader},
+        key_slot::{KeySlot, KeySlotId, KeySlotKind},
+        package::{CipherBlob, VaultPackage},
+        value_objects::CryptoVersion,
+    };
 
-        Ok(())
+    /// Shaped like the packages saved before lengths were fixed per version:
+    /// a 12-byte nonce and a 48-byte wrapped key under version 1.
+    fn baseline_package() -> VaultPackage {
+        VaultPackage {
+            header: VaultHeader {
+                crypto_version: CryptoVersion::V1,
+                key_slots: vec![KeySlot {
+                    id: KeySlotId(Uuid::nil()),
+                    kind: KeySlotKind::MasterPassword {
+                        kdf: KdfSpec {
+                            salt: vec![1; 16],
+                            params: KdfParams::Argon2id {
+                                m_kib: 32 * 1024,
+                                t: 1,
+                                p: 1,
+                            },
+                        },
+                    },
+                    wrapped_vault_key: vec![2; 48],
+                }],
+            },
+            blob: CipherBlob {
+                nonce: vec![3; 12],
+                aad: vec![],
+                ciphertext: vec![4; 16],
+            },
+            metadata: None,
+            attachments: vec![],
+            stored_ciphertext: None,
+        }
+    }
+
+    #[test]
+    fn baseline_v1_package_still_validates() {
+        assert!(baseline_package().validate().is_ok());
+    }
+
+    #[test]
+    fn v2_holds_the_same_shape_to_exact_lengths() {
+        let mut package = baseline_package();
+        package.header.crypto_version = CryptoVersion::V2;
+
+        assert!(package.validate().is_err());
     }
 }
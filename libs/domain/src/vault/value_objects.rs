@@ -4,7 +4,7 @@ use uuid::Uuid;
 
 use crate::shared::errors::DomainError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct VaultId(pub Uuid);
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -23,6 +23,29 @@ impl OwnerSub {
 
         Ok(Self(s))
     }
+
+    /// Owner key for `subject` as issued by `issuer`.
+    ///
+    /// Subjects are only unique within one issuer, so the issuer is part of
+    /// the key. `|` cannot appear unescaped in an issuer URL, which keeps the
+    /// encoding unambiguous.
+    pub fn namespaced(issuer: &str, subject: &str) -> Result<Self, DomainError> {
+        if issuer.trim().is_empty() || issuer.contains('|') {
+            return Err(DomainError::Validation {
+                field: "owner_sub",
+                message: "issuer must be a non-empty URL".into(),
+            });
+        }
+
+        if subject.trim().is_empty() {
+            return Err(DomainError::Validation {
+                field: "owner_sub",
+                message: "subject must not be empty".into(),
+            });
+        }
+
+        Ok(Self(format!("{issuer}|{subject}")))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -59,6 +82,8 @@ pub struct CryptoVersion(pub u16);
 
 impl CryptoVersion {
     pub const V1: Self = Self(1);
+    pub const V2: Self = Self(2);
+    pub const V3: Self = Self(3);
 
     pub fn new(v: u16) -> Result<Self, DomainError> {
         if v == 0 {
@@ -84,6 +109,29 @@ mod tests {
         assert!(OwnerSub::new("").is_err());
     }
 
+    #[test]
+    fn namespaced_owner_separates_issuers() {
+        let a = OwnerSub::namespaced("https://idp.example/realms/a", "user1").unwrap();
+        let b = OwnerSub::namespaced("https://idp.example/realms/b", "user1").unwrap();
+
+        assert_ne!(a, b);
+        assert_eq!(a.0, "https://idp.example/realms/a|user1");
+    }
+
+    #[test]
+    fn namespaced_owner_rejects_ambiguous_issuer() {
+        assert!(OwnerSub::namespaced("https://a|b", "c").is_err());
+        assert!(OwnerSub::namespaced("https://a", " ").is_err());
+    }
+
+    #[test]
+    fn namespaced_owners_cannot_collide_across_the_separator() {
+        let subject_with_bar = OwnerSub::namespaced("https://a", "b|c").unwrap();
+
+        assert_eq!(subject_with_bar.0, "https://a|b|c");
+        assert!(OwnerSub::namespaced("https://a|b", "c").is_err());
+    }
+
     #[test]
     fn revision_next_increments() {
         let r = Revision(5);
@@ -0,0 +1,206 @@
+use domain::vault::{Etag, Revision, VaultPackage};
+use ports::etag::EtagGenerator;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    fn prefix(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    fn digest(self, bytes: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(bytes).into(),
+            HashAlgorithm::Blake3 => *blake3::hash(bytes).as_bytes(),
+        }
+    }
+}
+
+/// Derives the etag from the package content: identical packages get the
+/// same etag, so unchanged uploads can be recognised.
+///
+/// The etag is the algorithm name and the hex digest of
+/// [`VaultPackage::canonical_bytes`], e.g. `sha256-9f86…`. The revision is
+/// not part of it. Etags only hold as long as the encoding does: a server
+/// with a new [`CANONICAL_TAG`](domain::vault::CANONICAL_TAG) answers the
+/// etags clients kept with `412 Precondition Failed`, and they fetch the
+/// vault again.
+#[derive(Debug, Clone, Copy)]
+pub struct ContentHashEtagGenerator {
+    algorithm: HashAlgorithm,
+}
+
+impl ContentHashEtagGenerator {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self { algorithm }
+    }
+
+    pub fn sha256() -> Self {
+        Self::new(HashAlgorithm::Sha256)
+    }
+
+    pub fn blake3() -> Self {
+        Self::new(HashAlgorithm::Blake3)
+    }
+}
+
+impl EtagGenerator for ContentHashEtagGenerator {
+    fn generate(&self, _revision: Revision, package: &VaultPackage) -> Etag {
+        let digest = self.algorithm.digest(&package.canonical_bytes());
+
+        Etag(format!(
+            "{}-{}",
+            self.algorithm.prefix(),
+            hex::encode(digest)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{
+        attachment::AttachmentId,
+        vault::{
+            CipherBlob, KdfParams, KeySlotId, KeySlotKind, Revision, StoredCiphertext, VaultPackage,
+        },
+    };
+    use ports::{conformance::valid_package, etag::EtagGenerator};
+    use uuid::Uuid;
+
+    use crate::ContentHashEtagGenerator;
+
+    /// The shared package without its optional sections.
+    fn bare_package() -> VaultPackage {
+        VaultPackage {
+            metadata: None,
+            attachments: vec![],
+            ..valid_package()
+        }
+    }
+
+    fn metadata() -> CipherBlob {
+        CipherBlob {
+            nonce: vec![5; 24],
+            aad: vec![],
+            ciphertext: vec![6; 32],
+        }
+    }
+
+    fn stored() -> StoredCiphertext {
+        StoredCiphertext {
+            attachment_id: AttachmentId(Uuid::from_u128(9)),
+            size: 32,
+            sha256: vec![7; 32],
+        }
+    }
+
+    // Pinned digests: a change here means every stored etag changes.
+    #[test]
+    fn digests_are_pinned() {
+        let package = valid_package();
+
+        assert_eq!(
+            ContentHashEtagGenerator::sha256()
+                .generate(Revision(0), &package)
+                .0,
+            "sha256-b97978d0d2b2fa2a2ce66728e86c775093c329ff59de1f87ad8a5f7a7972f93e"
+        );
+        assert_eq!(
+            ContentHashEtagGenerator::blake3()
+                .generate(Revision(0), &package)
+                .0,
+            "blake3-cb2f470b11a185c025238b3a1859c723202261a5bad761266c1d32e7d0e8e57b"
+        );
+    }
+
+    #[test]
+    fn same_content_same_etag_at_any_revision() {
+        let generator = ContentHashEtagGenerator::sha256();
+
+        assert_eq!(
+            generator.generate(Revision(0), &valid_package()),
+            generator.generate(Revision(7), &valid_package())
+        );
+    }
+
+    #[test]
+    fn every_field_contributes() {
+        let generator = ContentHashEtagGenerator::blake3();
+        let base = generator.generate(Revision(0), &bare_package());
+
+        let changes: [fn(&mut VaultPackage); 12] = [
+            |p| p.header.key_slots[0].id = KeySlotId(Uuid::from_u128(9)),
+            |p| {
+                if let KeySlotKind::MasterPassword { kdf } = &mut p.header.key_slots[0].kind {
+                    kdf.salt[0] ^= 1;
+                }
+            },
+            |p| {
+                if let KeySlotKind::MasterPassword { kdf } = &mut p.header.key_slots[0].kind {
+                    kdf.params = KdfParams::Pbkdf2HmacSha256 {
+                        iterations: 600_000,
+                    };
+                }
+            },
+            |p| {
+                p.header.key_slots[0].kind = KeySlotKind::RecoveryKey { salt: vec![1; 16] };
+            },
+            |p| p.header.key_slots[0].wrapped_vault_key[0] ^= 1,
+            |p| {
+                let mut slot = p.header.key_slots[0].clone();
+                slot.id = KeySlotId(Uuid::from_u128(9));
+                p.header.key_slots.push(slot);
+            },
+            |p| p.blob.nonce[0] ^= 1,
+            |p| p.blob.aad.push(0),
+            |p| p.blob.ciphertext[0] ^= 1,
+            |p| p.metadata = Some(metadata()),
+            |p| p.attachments.push(AttachmentId(Uuid::from_u128(1))),
+            |p| *p = p.clone().with_stored_ciphertext(stored()),
+        ];
+
+        for change in changes {
+            let mut package = bare_package();
+            change(&mut package);
+
+            assert_ne!(generator.generate(Revision(0), &package), base);
+        }
+    }
+
+    #[test]
+    fn every_optional_field_contributes() {
+        let full = || {
+            let mut package = valid_package().with_stored_ciphertext(stored());
+            package.metadata = Some(metadata());
+            package.attachments = vec![AttachmentId(Uuid::from_u128(1))];
+            package
+        };
+        let generator = ContentHashEtagGenerator::blake3();
+        let base = generator.generate(Revision(0), &full());
+
+        let changes: [fn(&mut VaultPackage); 7] = [
+            |p| p.metadata.as_mut().unwrap().nonce[0] ^= 1,
+            |p| p.metadata.as_mut().unwrap().aad.push(0),
+            |p| p.metadata.as_mut().unwrap().ciphertext[0] ^= 1,
+            |p| p.attachments[0] = AttachmentId(Uuid::from_u128(2)),
+            |p| p.attachments.push(AttachmentId(Uuid::from_u128(2))),
+            |p| p.stored_ciphertext.as_mut().unwrap().size += 1,
+            |p| p.stored_ciphertext.as_mut().unwrap().sha256[0] ^= 1,
+        ];
+
+        for change in changes {
+            let mut package = full();
+            change(&mut package);
+
+            assert_ne!(generator.generate(Revision(0), &package), base);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod content;
+mod revision;
+
+pub use content::{ContentHashEtagGenerator, HashAlgorithm};
+pub use revision::RevisionEtagGenerator;
@@ -0,0 +1,139 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use domain::attachment::AttachmentId;
+use ports::{BlobStoreError, blob_store::BlobStore};
+use tokio::fs;
+use tracing::error;
+use uuid::Uuid;
+
+/// Chunks stored as files under a root directory, one directory per
+/// attachment: `attachments/{id}/{index}`, with the index zero-padded so that
+/// file names sort like indices.
+#[derive(Debug, Clone)]
+pub struct FilesystemBlobStore {
+    root: PathBuf,
+}
+
+impl FilesystemBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn attachment_dir(&self, attachment_id: &AttachmentId) -> PathBuf {
+        self.root
+            .join("attachments")
+            .join(attachment_id.0.to_string())
+    }
+}
+
+fn chunk_name(index: u32) -> String {
+    format!("{index:010}")
+}
+
+fn unavailable(context: &str, path: &Path, e: std::io::Error) -> BlobStoreError {
+    error!("{} {}: {:?}", context, path.display(), e);
+    BlobStoreError::Unavailable {
+        message: format!("{context} {}: {e}", path.display()),
+    }
+}
+
+impl BlobStore for FilesystemBlobStore {
+    /// Writes to a temporary file first and renames it into place, so that
+    /// a chunk is never seen half-written.
+    async fn put_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+        bytes: Vec<u8>,
+    ) -> Result<(), BlobStoreError> {
+        let dir = self.attachment_dir(attachment_id);
+        fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| unavailable("failed to create", &dir, e))?;
+
+        let path = dir.join(chunk_name(index));
+        let partial = dir.join(format!(
+            ".{}.{}",
+            chunk_name(index),
+            Uuid::new_v4().simple()
+        ));
+
+        fs::write(&partial, bytes)
+            .await
+            .map_err(|e| unavailable("failed to write", &partial, e))?;
+
+        if let Err(e) = fs::rename(&partial, &path).await {
+            let _ = fs::remove_file(&partial).await;
+            return Err(unavailable("failed to write", &path, e));
+        }
+
+        Ok(())
+    }
+
+    async fn get_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+    ) -> Result<Option<Vec<u8>>, BlobStoreError> {
+        let path = self.attachment_dir(attachment_id).join(chunk_name(index));
+
+        match fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(unavailable("failed to read", &path, e)),
+        }
+    }
+
+    async fn list_chunks(&self, attachment_id: &AttachmentId) -> Result<Vec<u32>, BlobStoreError> {
+        let dir = self.attachment_dir(attachment_id);
+
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(unavailable("failed to list", &dir, e)),
+        };
+
+        let mut indices = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| unavailable("failed to list", &dir, e))?
+        {
+            // Temporary files of in-flight writes start with a dot.
+            if let Some(index) = entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+                indices.push(index);
+            }
+        }
+        indices.sort_unstable();
+
+        Ok(indices)
+    }
+
+    async fn delete(&self, attachment_id: &AttachmentId) -> Result<(), BlobStoreError> {
+        let dir = self.attachment_dir(attachment_id);
+
+        match fs::remove_dir_all(&dir).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(unavailable("failed to delete", &dir, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::FilesystemBlobStore;
+
+    async fn store() -> (FilesystemBlobStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+
+        (FilesystemBlobStore::new(dir.path()), dir)
+    }
+
+    ports::blob_store_conformance!(store());
+}
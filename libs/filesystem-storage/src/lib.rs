@@ -0,0 +1,3 @@
+mod blob_store;
+
+pub use blob_store::FilesystemBlobStore;
@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    vault::OwnerSub,
+};
+use ports::{RepositoryError, attachment_repository::AttachmentRepository};
+
+use crate::vault_repository::poisoned;
+
+/// Process-local attachment records. Data is lost on restart.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryAttachmentRepository {
+    attachments: Arc<RwLock<HashMap<AttachmentId, Attachment>>>,
+}
+
+impl InMemoryAttachmentRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declared sizes of the attachments of `owner_id`.
+    pub(crate) fn bytes_of(&self, owner_id: &OwnerSub) -> Result<u64, RepositoryError> {
+        let attachments = self.attachments.read().map_err(poisoned)?;
+
+        Ok(attachments
+            .values()
+            .filter(|attachment| &attachment.owner_id == owner_id)
+            .map(|attachment| attachment.size)
+            .sum())
+    }
+}
+
+impl AttachmentRepository for InMemoryAttachmentRepository {
+    async fn create(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        let mut attachments = self.attachments.write().map_err(poisoned)?;
+
+        if attachments.contains_key(&attachment.id) {
+            return Err(RepositoryError::Database {
+                message: format!("attachment {} already exists", attachment.id.0),
+            });
+        }
+        attachments.insert(attachment.id, attachment.clone());
+
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        attachment_id: &AttachmentId,
+    ) -> Result<Option<Attachment>, RepositoryError> {
+        let attachments = self.attachments.read().map_err(poisoned)?;
+
+        Ok(attachments
+            .get(attachment_id)
+            .filter(|attachment| &attachment.owner_id == owner_id)
+            .cloned())
+    }
+
+    async fn mark_completed(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        let mut attachments = self.attachments.write().map_err(poisoned)?;
+
+        let stored = attachments
+            .get_mut(&attachment.id)
+            .filter(|stored| stored.owner_id == attachment.owner_id)
+            .ok_or_else(|| RepositoryError::AttachmentNotFound {
+                attachment_id: attachment.id.0.to_string(),
+            })?;
+        stored.completed_at = attachment.completed_at;
+
+        Ok(())
+    }
+
+    async fn list_idle_before(
+        &self,
+        idle_before: DateTime<Utc>,
+    ) -> Result<Vec<AttachmentId>, RepositoryError> {
+        let attachments = self.attachments.read().map_err(poisoned)?;
+
+        Ok(attachments
+            .values()
+            .filter(|attachment| {
+                attachment.completed_at.unwrap_or(attachment.created_at) < idle_before
+            })
+            .map(|attachment| attachment.id)
+            .collect())
+    }
+
+    async fn delete(&self, attachment_ids: &[AttachmentId]) -> Result<(), RepositoryError> {
+        let mut attachments = self.attachments.write().map_err(poisoned)?;
+
+        for id in attachment_ids {
+            attachments.remove(id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InMemoryAttachmentRepository;
+
+    ports::attachment_repository_conformance!(async { (InMemoryAttachmentRepository::new(), ()) });
+}
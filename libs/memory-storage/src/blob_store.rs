@@ -0,0 +1,83 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use domain::attachment::AttachmentId;
+use ports::{BlobStoreError, blob_store::BlobStore};
+
+/// Chunks of one attachment, by index.
+type Chunks = BTreeMap<u32, Vec<u8>>;
+
+/// Process-local chunk storage. Data is lost on restart.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBlobStore {
+    chunks: Arc<RwLock<HashMap<AttachmentId, Chunks>>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn poisoned<T>(_: T) -> BlobStoreError {
+    BlobStoreError::Unavailable {
+        message: "in-memory store lock poisoned".into(),
+    }
+}
+
+impl BlobStore for InMemoryBlobStore {
+    async fn put_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+        bytes: Vec<u8>,
+    ) -> Result<(), BlobStoreError> {
+        let mut chunks = self.chunks.write().map_err(poisoned)?;
+
+        chunks
+            .entry(*attachment_id)
+            .or_default()
+            .insert(index, bytes);
+
+        Ok(())
+    }
+
+    async fn get_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+    ) -> Result<Option<Vec<u8>>, BlobStoreError> {
+        let chunks = self.chunks.read().map_err(poisoned)?;
+
+        Ok(chunks
+            .get(attachment_id)
+            .and_then(|attachment| attachment.get(&index))
+            .cloned())
+    }
+
+    async fn list_chunks(&self, attachment_id: &AttachmentId) -> Result<Vec<u32>, BlobStoreError> {
+        let chunks = self.chunks.read().map_err(poisoned)?;
+
+        Ok(chunks
+            .get(attachment_id)
+            .map(|attachment| attachment.keys().copied().collect())
+            .unwrap_or_default())
+    }
+
+    async fn delete(&self, attachment_id: &AttachmentId) -> Result<(), BlobStoreError> {
+        let mut chunks = self.chunks.write().map_err(poisoned)?;
+
+        chunks.remove(attachment_id);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InMemoryBlobStore;
+
+    ports::blob_store_conformance!(async { (InMemoryBlobStore::new(), ()) });
+}
@@ -0,0 +1,9 @@
+mod attachment_repository;
+mod blob_store;
+mod usage_repository;
+mod vault_repository;
+
+pub use attachment_repository::InMemoryAttachmentRepository;
+pub use blob_store::InMemoryBlobStore;
+pub use usage_repository::InMemoryUsageRepository;
+pub use vault_repository::InMemoryVaultRepository;
@@ -0,0 +1,43 @@
+use domain::{quota::StorageUsage, vault::OwnerSub};
+use ports::{RepositoryError, usage_repository::UsageRepository};
+
+use crate::{InMemoryAttachmentRepository, InMemoryVaultRepository};
+
+/// Usage measured over in-memory vaults and attachments; give it clones of
+/// the repositories the server writes to.
+#[derive(Debug, Clone)]
+pub struct InMemoryUsageRepository {
+    vaults: InMemoryVaultRepository,
+    attachments: InMemoryAttachmentRepository,
+}
+
+impl InMemoryUsageRepository {
+    pub fn new(vaults: InMemoryVaultRepository, attachments: InMemoryAttachmentRepository) -> Self {
+        Self {
+            vaults,
+            attachments,
+        }
+    }
+}
+
+impl UsageRepository for InMemoryUsageRepository {
+    async fn usage(&self, owner_id: &OwnerSub) -> Result<StorageUsage, RepositoryError> {
+        Ok(StorageUsage {
+            attachment_bytes: self.attachments.bytes_of(owner_id)?,
+            ..self.vaults.usage_of(owner_id)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InMemoryAttachmentRepository, InMemoryUsageRepository, InMemoryVaultRepository};
+
+    ports::usage_repository_conformance!(async {
+        let vaults = InMemoryVaultRepository::new();
+        let attachments = InMemoryAttachmentRepository::new();
+        let usage = InMemoryUsageRepository::new(vaults.clone(), attachments.clone());
+
+        ((vaults, attachments, usage), ())
+    });
+}
@@ -0,0 +1,337 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::AttachmentId,
+    quota::StorageUsage,
+    vault::{
+        Etag, OwnerSub, Revision, RevisionSummary, Vault, VaultId, VaultRevision, VaultSummary,
+        VaultVersion,
+    },
+};
+use ports::{RepositoryError, vault_repository::VaultRepository};
+
+/// Process-local vault storage. Data is lost on restart.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryVaultRepository {
+    state: Arc<RwLock<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    vaults: HashMap<VaultId, Vault>,
+    by_owner: HashMap<OwnerSub, BTreeSet<VaultId>>,
+    history: HashMap<VaultId, BTreeMap<Revision, VaultRevision>>,
+}
+
+impl State {
+    /// The vault `vault_id` if it belongs to `owner_id`, in any state.
+    fn owned(&self, owner_id: &OwnerSub, vault_id: &VaultId) -> Option<&Vault> {
+        self.vaults
+            .get(vault_id)
+            .filter(|vault| &vault.owner_id == owner_id)
+    }
+
+    fn owned_mut(&mut self, vault: &Vault) -> Result<&mut Vault, RepositoryError> {
+        self.vaults
+            .get_mut(&vault.id)
+            .filter(|current| current.owner_id == vault.owner_id)
+            .ok_or_else(|| not_found(vault))
+    }
+}
+
+impl InMemoryVaultRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Vaults and their history held by `owner_id`; attachments are left to
+    /// the caller.
+    pub(crate) fn usage_of(&self, owner_id: &OwnerSub) -> Result<StorageUsage, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+        let mut usage = StorageUsage::default();
+
+        for vault_id in state.by_owner.get(owner_id).into_iter().flatten() {
+            let Some(vault) = state.vaults.get(vault_id) else {
+                continue;
+            };
+            usage.vaults += 1;
+            usage.vault_bytes += vault.package.byte_size();
+            usage.history_bytes += state
+                .history
+                .get(vault_id)
+                .into_iter()
+                .flat_map(BTreeMap::values)
+                .map(|revision| revision.package.byte_size())
+                .sum::<u64>();
+        }
+
+        Ok(usage)
+    }
+}
+
+pub(crate) fn poisoned<T>(_: T) -> RepositoryError {
+    RepositoryError::Database {
+        message: "in-memory store lock poisoned".into(),
+    }
+}
+
+fn not_found(vault: &Vault) -> RepositoryError {
+    RepositoryError::VaultNotFound {
+        vault_id: vault.id.0.to_string(),
+    }
+}
+
+fn conflict(vault: &Vault) -> RepositoryError {
+    RepositoryError::ConcurrencyConflict {
+        vault_id: vault.id.0.to_string(),
+    }
+}
+
+impl VaultRepository for InMemoryVaultRepository {
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        Ok(state
+            .owned(owner_id, vault_id)
+            .filter(|vault| !vault.is_deleted())
+            .cloned())
+    }
+
+    async fn find_version(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<VaultVersion>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        Ok(state
+            .owned(owner_id, vault_id)
+            .filter(|vault| !vault.is_deleted())
+            .map(Vault::version))
+    }
+
+    async fn find_deleted(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        Ok(state
+            .owned(owner_id, vault_id)
+            .filter(|vault| vault.is_deleted())
+            .cloned())
+    }
+
+    async fn list_by_owner(
+        &self,
+        owner_id: &OwnerSub,
+    ) -> Result<Vec<VaultSummary>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        let mut vaults: Vec<&Vault> = state
+            .by_owner
+            .get(owner_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| state.vaults.get(id))
+            .filter(|vault| !vault.is_deleted())
+            .collect();
+        vaults.sort_by_key(|vault| (vault.created_at, vault.id));
+
+        Ok(vaults.into_iter().map(Vault::summary).collect())
+    }
+
+    async fn create(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        if state.vaults.contains_key(&vault.id) {
+            return Err(RepositoryError::AlreadyExists {
+                vault_id: vault.id.0.to_string(),
+            });
+        }
+
+        state.vaults.insert(vault.id, vault.clone());
+        state
+            .by_owner
+            .entry(vault.owner_id.clone())
+            .or_default()
+            .insert(vault.id);
+
+        Ok(())
+    }
+
+    async fn update_if_match(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        let current = state.owned_mut(vault)?;
+        if current.is_deleted() || &current.etag != expected_etag {
+            return Err(conflict(vault));
+        }
+
+        let archived = VaultRevision::archive(current, vault.updated_at);
+        *current = vault.clone();
+        state
+            .history
+            .entry(vault.id)
+            .or_default()
+            .insert(archived.revision, archived);
+
+        Ok(())
+    }
+
+    async fn mark_deleted(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        let current = state.owned_mut(vault)?;
+        if current.is_deleted() || &current.etag != expected_etag {
+            return Err(conflict(vault));
+        }
+
+        current.deleted_at = vault.deleted_at;
+
+        Ok(())
+    }
+
+    async fn restore_deleted(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        let current = state.owned_mut(vault)?;
+        if !current.is_deleted() {
+            return Err(not_found(vault));
+        }
+
+        current.deleted_at = None;
+
+        Ok(())
+    }
+
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+        let State {
+            vaults,
+            by_owner,
+            history,
+        } = &mut *state;
+
+        let mut purged = 0;
+        vaults.retain(|id, vault| {
+            let expired = vault.deleted_at.is_some_and(|at| at < deleted_before);
+            if expired {
+                if let Some(ids) = by_owner.get_mut(&vault.owner_id) {
+                    ids.remove(id);
+                }
+                history.remove(id);
+                purged += 1;
+            }
+            !expired
+        });
+        by_owner.retain(|_, ids| !ids.is_empty());
+
+        Ok(purged)
+    }
+
+    async fn list_revisions(
+        &self,
+        vault_id: &VaultId,
+    ) -> Result<Vec<RevisionSummary>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        Ok(state
+            .history
+            .get(vault_id)
+            .map(|revisions| {
+                revisions
+                    .values()
+                    .rev()
+                    .map(VaultRevision::summary)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn find_revision(
+        &self,
+        vault_id: &VaultId,
+        revision: Revision,
+    ) -> Result<Option<VaultRevision>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        Ok(state
+            .history
+            .get(vault_id)
+            .and_then(|revisions| revisions.get(&revision))
+            .cloned())
+    }
+
+    async fn delete_revisions(
+        &self,
+        vault_id: &VaultId,
+        revisions: &[Revision],
+    ) -> Result<(), RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        if let Some(history) = state.history.get_mut(vault_id) {
+            for revision in revisions {
+                history.remove(revision);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn purge_revisions(
+        &self,
+        archived_before: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let mut state = self.state.write().map_err(poisoned)?;
+
+        let mut purged = 0;
+        for revisions in state.history.values_mut() {
+            let before = revisions.len();
+            revisions.retain(|_, revision| revision.archived_at >= archived_before);
+            purged += (before - revisions.len()) as u64;
+        }
+
+        Ok(purged)
+    }
+
+    async fn referenced_attachments(&self) -> Result<HashSet<AttachmentId>, RepositoryError> {
+        let state = self.state.read().map_err(poisoned)?;
+
+        let live = state.vaults.values().map(|vault| &vault.package);
+        let archived = state
+            .history
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(|revision| &revision.package);
+
+        Ok(live
+            .chain(archived)
+            .flat_map(|package| package.referenced_attachments().copied())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InMemoryVaultRepository;
+
+    ports::vault_repository_conformance!(async { (InMemoryVaultRepository::new(), ()) });
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    vault::OwnerSub,
+};
+
+use crate::RepositoryError;
+
+/// Records of attachment uploads; the chunks themselves live in a
+/// [`BlobStore`](crate::blob_store::BlobStore).
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait AttachmentRepository: Send + Sync {
+    fn create(
+        &self,
+        attachment: &Attachment,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Loads an attachment of `owner_id`, complete or not.
+    fn find(
+        &self,
+        owner_id: &OwnerSub,
+        attachment_id: &AttachmentId,
+    ) -> impl Future<Output = Result<Option<Attachment>, RepositoryError>> + Send;
+
+    /// Stores the completion time of an upload.
+    fn mark_completed(
+        &self,
+        attachment: &Attachment,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Attachments of every owner idle since before `idle_before`: completed
+    /// before it, or started before it and never completed.
+    fn list_idle_before(
+        &self,
+        idle_before: DateTime<Utc>,
+    ) -> impl Future<Output = Result<Vec<AttachmentId>, RepositoryError>> + Send;
+
+    /// Drops the records; unknown ids are ignored.
+    fn delete(
+        &self,
+        attachment_ids: &[AttachmentId],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+}
@@ -0,0 +1,34 @@
+use domain::attachment::AttachmentId;
+
+use crate::BlobStoreError;
+
+/// Storage for the encrypted chunks of attachments, kept out of the
+/// database.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait BlobStore: Send + Sync {
+    /// Stores chunk `index`, replacing any previous upload of it.
+    fn put_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+        bytes: Vec<u8>,
+    ) -> impl Future<Output = Result<(), BlobStoreError>> + Send;
+
+    fn get_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, BlobStoreError>> + Send;
+
+    /// Indices of the stored chunks, in ascending order.
+    fn list_chunks(
+        &self,
+        attachment_id: &AttachmentId,
+    ) -> impl Future<Output = Result<Vec<u32>, BlobStoreError>> + Send;
+
+    /// Removes every chunk of an attachment; unknown ids are ignored.
+    fn delete(
+        &self,
+        attachment_id: &AttachmentId,
+    ) -> impl Future<Output = Result<(), BlobStoreError>> + Send;
+}
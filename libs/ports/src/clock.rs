@@ -0,0 +1,82 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Source of the current time for use cases.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Always returns the same instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Starts at a given instant and moves forward by `step` on every read, so
+/// consecutive events get distinct, predictable timestamps.
+#[derive(Debug)]
+pub struct SteppingClock {
+    next: Mutex<DateTime<Utc>>,
+    step: Duration,
+}
+
+impl SteppingClock {
+    pub fn new(start: DateTime<Utc>, step: Duration) -> Self {
+        Self {
+            next: Mutex::new(start),
+            step,
+        }
+    }
+}
+
+impl Clock for SteppingClock {
+    fn now(&self) -> DateTime<Utc> {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let now = *next;
+        *next = now + self.step;
+        now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration};
+
+    use crate::clock::{Clock, FixedClock, SteppingClock};
+
+    fn epoch() -> DateTime<chrono::Utc> {
+        DateTime::from_timestamp(1_770_000_000, 0).unwrap()
+    }
+
+    #[test]
+    fn fixed_clock_never_moves() {
+        let clock = FixedClock(epoch());
+
+        assert_eq!(clock.now(), clock.now());
+    }
+
+    #[test]
+    fn stepping_clock_advances_on_each_read() {
+        let clock = SteppingClock::new(epoch(), Duration::seconds(5));
+
+        assert_eq!(clock.now(), epoch());
+        assert_eq!(clock.now(), epoch() + Duration::seconds(5));
+        assert_eq!(clock.now(), epoch() + Duration::seconds(10));
+    }
+}
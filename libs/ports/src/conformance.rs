@@ -0,0 +1,945 @@
+//! Behaviour every [`VaultRepository`] adapter must share.
+//!
+//! Adapters run the suite from their own tests with
+//! [`vault_repository_conformance!`](crate::vault_repository_conformance),
+//! giving it an async setup that yields a fresh, empty repository. The
+//! submodules hold the suites of the other storage ports.
+
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use domain::{
+    attachment::AttachmentId,
+    vault::{
+        CipherBlob, CryptoVersion, Etag, KdfParams, KdfSpec, KeySlot, KeySlotId, KeySlotKind,
+        OwnerSub, Revision, StoredCiphertext, Vault, VaultHeader, VaultId, VaultPackage,
+        VaultRevision,
+    },
+};
+use uuid::Uuid;
+
+use crate::{RepositoryError, vault_repository::VaultRepository};
+
+pub mod attachment_repository;
+pub mod blob_store;
+pub mod usage_repository;
+
+/// Generates one multi-threaded `#[tokio::test]` per conformance case.
+///
+/// `$setup` is evaluated in every test and must be a future resolving to
+/// `(repository, guard)`; the guard is kept alive for the whole test.
+/// An attribute written before `$setup`, e.g. `#[ignore = "..."]` for
+/// suites that need a database server, is put on every test.
+#[macro_export]
+macro_rules! vault_repository_conformance {
+    (#[$attr:meta] $setup:expr) => {
+        $crate::vault_repository_conformance!(@all [#[$attr]] $setup);
+    };
+    ($setup:expr) => {
+        $crate::vault_repository_conformance!(@all [] $setup);
+    };
+    (@all $attrs:tt $setup:expr) => {
+        $crate::vault_repository_conformance!(@cases $attrs $setup;
+            create_then_find_roundtrips,
+            every_kdf_roundtrips,
+            key_slot_changes_keep_order,
+            find_unknown_vault_returns_none,
+            find_version_matches_vault,
+            duplicate_id_already_exists,
+            owner_holds_several_vaults,
+            find_is_scoped_to_owner,
+            update_if_match_replaces_vault,
+            update_if_match_rejects_stale_etag,
+            update_of_missing_vault_fails,
+            racing_updates_have_one_winner,
+            update_archives_previous_revision,
+            failed_update_does_not_archive,
+            delete_revisions_drops_only_listed,
+            history_is_kept_per_vault,
+            purge_revisions_drops_only_old_archives,
+            deleted_vault_is_hidden,
+            mark_deleted_rejects_stale_etag,
+            deleted_vault_rejects_updates,
+            restore_deleted_makes_vault_visible,
+            purge_removes_only_expired_tombstones,
+            referenced_attachments_cover_history_and_trash,
+            stored_ciphertext_roundtrips_and_is_referenced,
+        );
+    };
+    (@cases $attrs:tt $setup:expr; $($case:ident),* $(,)?) => {
+        $(
+            $crate::vault_repository_conformance!(@case $attrs $setup; $case);
+        )*
+    };
+    (@case [$(#[$attr:meta])*] $setup:expr; $case:ident) => {
+        $(#[$attr])*
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn $case() {
+            let (repository, _guard) = $setup.await;
+            $crate::conformance::$case(&repository).await;
+        }
+    };
+}
+
+pub fn valid_package() -> VaultPackage {
+    VaultPackage {
+        header: VaultHeader {
+            crypto_version: CryptoVersion::V1,
+            key_slots: vec![
+                KeySlot {
+                    id: KeySlotId(Uuid::from_u128(1)),
+                    kind: KeySlotKind::MasterPassword {
+                        kdf: KdfSpec {
+                            salt: vec![1; 16],
+                            params: KdfParams::Argon2id {
+                                m_kib: 131_072,
+                                t: 3,
+                                p: 1,
+                            },
+                        },
+                    },
+                    wrapped_vault_key: vec![2; 72],
+                },
+                KeySlot {
+                    id: KeySlotId(Uuid::from_u128(2)),
+                    kind: KeySlotKind::RecoveryKey { salt: vec![8; 16] },
+                    wrapped_vault_key: vec![9; 72],
+                },
+                KeySlot {
+                    id: KeySlotId(Uuid::from_u128(3)),
+                    kind: KeySlotKind::Device {
+                        device_id: "laptop".into(),
+                    },
+                    wrapped_vault_key: vec![10; 72],
+                },
+                KeySlot {
+                    id: KeySlotId(Uuid::from_u128(4)),
+                    kind: KeySlotKind::PasskeyPrf {
+                        credential_id: vec![11; 20],
+                        prf_salt: vec![12; 32],
+                    },
+                    wrapped_vault_key: vec![13; 72],
+                },
+            ],
+        },
+        blob: CipherBlob {
+            nonce: vec![3; 24],
+            aad: vec![5; 8],
+            ciphertext: vec![4; 32],
+        },
+        metadata: Some(CipherBlob {
+            nonce: vec![6; 24],
+            aad: vec![],
+            ciphertext: vec![7; 24],
+        }),
+        attachments: vec![
+            AttachmentId(Uuid::from_u128(1)),
+            AttachmentId(Uuid::from_u128(2)),
+        ],
+        stored_ciphertext: None,
+    }
+}
+
+/// Storage engines are only required to keep microseconds.
+pub fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+pub fn vault(owner: &str) -> Vault {
+    Vault::new(
+        VaultId(Uuid::new_v4()),
+        OwnerSub::new(owner).unwrap(),
+        now(),
+        Etag::new("etag-1").unwrap(),
+        valid_package(),
+    )
+    .unwrap()
+}
+
+/// Next revision of `vault` with a different ciphertext.
+pub fn updated(vault: &Vault, etag: &str) -> Vault {
+    let mut package = valid_package();
+    package.blob.ciphertext = vec![9; 48];
+
+    vault
+        .update(&vault.etag, now(), Etag::new(etag).unwrap(), package)
+        .unwrap()
+}
+
+fn assert_same(found: &Vault, expected: &Vault) {
+    assert_eq!(found.id, expected.id);
+    assert_eq!(found.owner_id, expected.owner_id);
+    assert_eq!(found.revision, expected.revision);
+    assert_eq!(found.etag, expected.etag);
+    assert_eq!(found.created_at, expected.created_at);
+    assert_eq!(found.updated_at, expected.updated_at);
+    assert_eq!(found.deleted_at, expected.deleted_at);
+    assert_same_package(&found.package, &expected.package);
+}
+
+fn assert_same_package(f: &VaultPackage, e: &VaultPackage) {
+    assert_eq!(f.header.crypto_version, e.header.crypto_version);
+    assert_eq!(f.header.key_slots, e.header.key_slots);
+    assert_eq!(f.blob.nonce, e.blob.nonce);
+    assert_eq!(f.blob.aad, e.blob.aad);
+    assert_eq!(f.blob.ciphertext, e.blob.ciphertext);
+    assert_eq!(f.metadata, e.metadata);
+    assert_eq!(f.attachments, e.attachments);
+    assert_eq!(f.stored_ciphertext, e.stored_ciphertext);
+}
+
+pub async fn create_then_find_roundtrips<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+
+    repository.create(&vault).await.unwrap();
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .expect("created vault not found");
+
+    assert_same(&found, &vault);
+}
+
+pub async fn every_kdf_roundtrips<R: VaultRepository>(repository: &R) {
+    let mut package = valid_package();
+    package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+        kdf: KdfSpec {
+            salt: vec![1; 16],
+            params: KdfParams::Scrypt {
+                log_n: 17,
+                r: 8,
+                p: 1,
+            },
+        },
+    };
+    let vault = Vault::new(
+        VaultId(Uuid::new_v4()),
+        OwnerSub::new("user1").unwrap(),
+        now(),
+        Etag::new("etag-1").unwrap(),
+        package,
+    )
+    .unwrap();
+    repository.create(&vault).await.unwrap();
+
+    let mut package = valid_package();
+    package.header.key_slots[0].kind = KeySlotKind::MasterPassword {
+        kdf: KdfSpec {
+            salt: vec![1; 16],
+            params: KdfParams::Pbkdf2HmacSha256 {
+                iterations: 600_000,
+            },
+        },
+    };
+    let updated = vault
+        .update(&vault.etag, now(), Etag::new("etag-2").unwrap(), package)
+        .unwrap();
+    repository
+        .update_if_match(&updated, &vault.etag)
+        .await
+        .unwrap();
+
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .expect("updated vault not found");
+    assert_same(&found, &updated);
+
+    let archived = repository
+        .find_revision(&vault.id, vault.revision)
+        .await
+        .unwrap()
+        .expect("scrypt revision not archived");
+    assert_same_package(&archived.package, &vault.package);
+}
+
+/// Slots come back in header order after one is revoked and the rest are
+/// reordered, and history keeps the slots of the replaced revision.
+pub async fn key_slot_changes_keep_order<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let mut package = valid_package();
+    package.header.key_slots.remove(1);
+    package.header.key_slots.reverse();
+    let updated = vault
+        .update(&vault.etag, now(), Etag::new("etag-2").unwrap(), package)
+        .unwrap();
+    repository
+        .update_if_match(&updated, &vault.etag)
+        .await
+        .unwrap();
+
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .expect("updated vault not found");
+    assert_same(&found, &updated);
+
+    let archived = repository
+        .find_revision(&vault.id, vault.revision)
+        .await
+        .unwrap()
+        .expect("previous revision not archived");
+    assert_same_package(&archived.package, &vault.package);
+}
+
+pub async fn find_unknown_vault_returns_none<R: VaultRepository>(repository: &R) {
+    let owner = OwnerSub::new("nobody").unwrap();
+    let id = VaultId(Uuid::new_v4());
+
+    assert!(repository.find(&owner, &id).await.unwrap().is_none());
+    assert!(
+        repository
+            .find_version(&owner, &id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(repository.list_by_owner(&owner).await.unwrap().is_empty());
+}
+
+pub async fn find_version_matches_vault<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+
+    repository.create(&vault).await.unwrap();
+
+    assert_eq!(
+        repository
+            .find_version(&vault.owner_id, &vault.id)
+            .await
+            .unwrap(),
+        Some(vault.version())
+    );
+}
+
+pub async fn duplicate_id_already_exists<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let mut duplicate = self::vault("user2");
+    duplicate.id = vault.id;
+    let result = repository.create(&duplicate).await;
+
+    assert!(matches!(result, Err(RepositoryError::AlreadyExists { .. })));
+}
+
+pub async fn owner_holds_several_vaults<R: VaultRepository>(repository: &R) {
+    let personal = vault("user1");
+    let mut work = vault("user1");
+    work.created_at = personal.created_at + Duration::seconds(1);
+    work.package.metadata = None;
+    repository.create(&work).await.unwrap();
+    repository.create(&personal).await.unwrap();
+    repository.create(&vault("user2")).await.unwrap();
+
+    let listed = repository.list_by_owner(&personal.owner_id).await.unwrap();
+
+    let ids: Vec<_> = listed.iter().map(|summary| summary.id).collect();
+    assert_eq!(ids, vec![personal.id, work.id]);
+    assert_eq!(listed[0].etag, personal.etag);
+    assert_eq!(listed[0].revision, personal.revision);
+    assert_eq!(listed[0].updated_at, personal.updated_at);
+    assert_eq!(listed[0].metadata, personal.package.metadata);
+    assert_eq!(listed[1].metadata, None);
+}
+
+pub async fn find_is_scoped_to_owner<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+    let intruder = OwnerSub::new("user2").unwrap();
+
+    assert!(
+        repository
+            .find(&intruder, &vault.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .find_version(&intruder, &vault.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let mut hijack = updated(&vault, "etag-2");
+    hijack.owner_id = intruder;
+    let result = repository.update_if_match(&hijack, &vault.etag).await;
+
+    assert!(matches!(result, Err(RepositoryError::VaultNotFound { .. })));
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_same(&found, &vault);
+}
+
+pub async fn update_if_match_replaces_vault<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let next = updated(&vault, "etag-2");
+    repository
+        .update_if_match(&next, &vault.etag)
+        .await
+        .unwrap();
+
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_same(&found, &next);
+    assert_eq!(found.revision, Revision(1));
+}
+
+pub async fn update_if_match_rejects_stale_etag<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let result = repository
+        .update_if_match(&updated(&vault, "etag-2"), &Etag::new("stale").unwrap())
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(RepositoryError::ConcurrencyConflict { .. })
+    ));
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_same(&found, &vault);
+}
+
+/// A vault that is not there is reported missing, not as a lost race.
+pub async fn update_of_missing_vault_fails<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+
+    let result = repository
+        .update_if_match(&updated(&vault, "etag-2"), &vault.etag)
+        .await;
+    assert!(matches!(result, Err(RepositoryError::VaultNotFound { .. })));
+
+    let deleted = vault.delete(&vault.etag, now()).unwrap();
+    let result = repository.mark_deleted(&deleted, &vault.etag).await;
+    assert!(matches!(result, Err(RepositoryError::VaultNotFound { .. })));
+    assert!(
+        repository
+            .find(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// Writers that all read the same etag race to replace it: exactly one
+/// wins, every other one sees a conflict, and the winner's data is stored.
+pub async fn racing_updates_have_one_winner<R>(repository: &R)
+where
+    R: VaultRepository + Clone + 'static,
+{
+    const WRITERS: usize = 16;
+
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let tasks: Vec<_> = (0..WRITERS)
+        .map(|i| {
+            let repository = repository.clone();
+            let next = updated(&vault, &format!("etag-racer-{i}"));
+            let expected = vault.etag.clone();
+
+            tokio::spawn(async move {
+                repository
+                    .update_if_match(&next, &expected)
+                    .await
+                    .map(|()| next.etag)
+            })
+        })
+        .collect();
+
+    let mut winners = Vec::new();
+    for task in tasks {
+        match task.await.unwrap() {
+            Ok(etag) => winners.push(etag),
+            Err(RepositoryError::ConcurrencyConflict { .. }) => {}
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    assert_eq!(winners.len(), 1, "expected exactly one winner");
+    let found = repository
+        .find(&vault.owner_id, &vault.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.etag, winners[0]);
+    assert_eq!(found.revision, Revision(1));
+}
+
+/// Applies two updates and returns every stored state, oldest first.
+pub(crate) async fn vault_with_history<R: VaultRepository>(
+    repository: &R,
+    owner: &str,
+) -> [Vault; 3] {
+    let first = vault(owner);
+    repository.create(&first).await.unwrap();
+
+    let second = updated(&first, "etag-2");
+    repository
+        .update_if_match(&second, &first.etag)
+        .await
+        .unwrap();
+
+    let mut package = valid_package();
+    package.blob.ciphertext = vec![7; 64];
+    let third = second
+        .update(&second.etag, now(), Etag::new("etag-3").unwrap(), package)
+        .unwrap();
+    repository
+        .update_if_match(&third, &second.etag)
+        .await
+        .unwrap();
+
+    [first, second, third]
+}
+
+pub async fn update_archives_previous_revision<R: VaultRepository>(repository: &R) {
+    let [first, second, third] = vault_with_history(repository, "user1").await;
+
+    let summaries = repository.list_revisions(&first.id).await.unwrap();
+    assert_eq!(
+        summaries,
+        vec![
+            VaultRevision::archive(&second, third.updated_at).summary(),
+            VaultRevision::archive(&first, second.updated_at).summary(),
+        ]
+    );
+
+    let archived = repository
+        .find_revision(&first.id, Revision(0))
+        .await
+        .unwrap()
+        .expect("first revision not archived");
+    assert_eq!(archived.vault_id, first.id);
+    assert_eq!(archived.etag, first.etag);
+    assert_eq!(archived.saved_at, first.updated_at);
+    assert_eq!(archived.archived_at, second.updated_at);
+    assert_same_package(&archived.package, &first.package);
+
+    // The current revision is not part of the history.
+    assert!(
+        repository
+            .find_revision(&first.id, third.revision)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+pub async fn failed_update_does_not_archive<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let _ = repository
+        .update_if_match(&updated(&vault, "etag-2"), &Etag::new("stale").unwrap())
+        .await;
+
+    assert!(
+        repository
+            .list_revisions(&vault.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+pub async fn delete_revisions_drops_only_listed<R: VaultRepository>(repository: &R) {
+    let [first, ..] = vault_with_history(repository, "user1").await;
+
+    repository
+        .delete_revisions(&first.id, &[Revision(0), Revision(42)])
+        .await
+        .unwrap();
+
+    let remaining: Vec<_> = repository
+        .list_revisions(&first.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|summary| summary.revision)
+        .collect();
+    assert_eq!(remaining, vec![Revision(1)]);
+    assert!(
+        repository
+            .find_revision(&first.id, Revision(0))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+pub async fn history_is_kept_per_vault<R: VaultRepository>(repository: &R) {
+    let [first, ..] = vault_with_history(repository, "user1").await;
+    let other = vault("user2");
+    repository.create(&other).await.unwrap();
+
+    assert!(
+        repository
+            .list_revisions(&other.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    repository
+        .delete_revisions(&other.id, &[Revision(0)])
+        .await
+        .unwrap();
+    assert_eq!(repository.list_revisions(&first.id).await.unwrap().len(), 2);
+}
+
+pub async fn purge_revisions_drops_only_old_archives<R: VaultRepository>(repository: &R) {
+    let cutoff = now() - Duration::days(1);
+    let at = |days| now() - Duration::days(days);
+    let save = |vault: &Vault, at, etag| {
+        vault
+            .update(&vault.etag, at, Etag::new(etag).unwrap(), valid_package())
+            .unwrap()
+    };
+
+    let first = Vault::new(
+        VaultId(Uuid::new_v4()),
+        OwnerSub::new("user1").unwrap(),
+        at(10),
+        Etag::new("etag-1").unwrap(),
+        valid_package(),
+    )
+    .unwrap();
+    repository.create(&first).await.unwrap();
+    let second = save(&first, at(5), "etag-2");
+    repository
+        .update_if_match(&second, &first.etag)
+        .await
+        .unwrap();
+    let third = save(&second, now(), "etag-3");
+    repository
+        .update_if_match(&third, &second.etag)
+        .await
+        .unwrap();
+
+    let other = Vault::new(
+        VaultId(Uuid::new_v4()),
+        OwnerSub::new("user2").unwrap(),
+        at(10),
+        Etag::new("etag-1").unwrap(),
+        valid_package(),
+    )
+    .unwrap();
+    repository.create(&other).await.unwrap();
+    repository
+        .update_if_match(&save(&other, at(3), "etag-2"), &other.etag)
+        .await
+        .unwrap();
+
+    assert_eq!(repository.purge_revisions(cutoff).await.unwrap(), 2);
+
+    let remaining: Vec<_> = repository
+        .list_revisions(&first.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|summary| summary.revision)
+        .collect();
+    assert_eq!(remaining, vec![Revision(1)]);
+    assert!(
+        repository
+            .list_revisions(&other.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(repository.purge_revisions(cutoff).await.unwrap(), 0);
+}
+
+/// Stores `vault` and moves it to the trash at `deleted_at`.
+pub(crate) async fn trashed<R: VaultRepository>(
+    repository: &R,
+    vault: &Vault,
+    deleted_at: DateTime<Utc>,
+) -> Vault {
+    repository.create(vault).await.unwrap();
+
+    let deleted = vault.delete(&vault.etag, deleted_at).unwrap();
+    repository
+        .mark_deleted(&deleted, &vault.etag)
+        .await
+        .unwrap();
+
+    deleted
+}
+
+pub async fn deleted_vault_is_hidden<R: VaultRepository>(repository: &R) {
+    let deleted = trashed(repository, &vault("user1"), now()).await;
+
+    assert!(
+        repository
+            .find(&deleted.owner_id, &deleted.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .find_version(&deleted.owner_id, &deleted.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let found = repository
+        .find_deleted(&deleted.owner_id, &deleted.id)
+        .await
+        .unwrap()
+        .expect("deleted vault not in trash");
+    assert_same(&found, &deleted);
+}
+
+pub async fn mark_deleted_rejects_stale_etag<R: VaultRepository>(repository: &R) {
+    let vault = vault("user1");
+    repository.create(&vault).await.unwrap();
+
+    let deleted = vault.delete(&vault.etag, now()).unwrap();
+    let result = repository
+        .mark_deleted(&deleted, &Etag::new("stale").unwrap())
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(RepositoryError::ConcurrencyConflict { .. })
+    ));
+    assert!(
+        repository
+            .find_deleted(&vault.owner_id, &vault.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// A writer that read the vault before it was deleted must not bring it
+/// back to life.
+pub async fn deleted_vault_rejects_updates<R: VaultRepository>(repository: &R) {
+    let live = vault("user1");
+    let deleted = trashed(repository, &live, now()).await;
+
+    let result = repository
+        .update_if_match(&updated(&live, "etag-2"), &live.etag)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(RepositoryError::ConcurrencyConflict { .. })
+    ));
+    let found = repository
+        .find_deleted(&live.owner_id, &live.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_same(&found, &deleted);
+    assert!(
+        repository
+            .list_revisions(&live.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+pub async fn restore_deleted_makes_vault_visible<R: VaultRepository>(repository: &R) {
+    let deleted = trashed(repository, &vault("user1"), now()).await;
+
+    let restored = deleted.restore(now(), Duration::days(1)).unwrap();
+    repository.restore_deleted(&restored).await.unwrap();
+
+    let found = repository
+        .find(&deleted.owner_id, &deleted.id)
+        .await
+        .unwrap()
+        .expect("restored vault not found");
+    assert_same(&found, &restored);
+    assert!(
+        repository
+            .find_deleted(&deleted.owner_id, &deleted.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Restoring twice finds nothing left in the trash.
+    assert!(matches!(
+        repository.restore_deleted(&restored).await,
+        Err(RepositoryError::VaultNotFound { .. })
+    ));
+}
+
+pub async fn purge_removes_only_expired_tombstones<R: VaultRepository>(repository: &R) {
+    let cutoff = now() - Duration::days(30);
+
+    let [expired, ..] = vault_with_history(repository, "user1").await;
+    let current = repository
+        .find(&expired.owner_id, &expired.id)
+        .await
+        .unwrap()
+        .unwrap();
+    repository
+        .mark_deleted(
+            &current
+                .delete(&current.etag, cutoff - Duration::days(1))
+                .unwrap(),
+            &current.etag,
+        )
+        .await
+        .unwrap();
+    let recent = trashed(repository, &vault("user2"), cutoff + Duration::days(1)).await;
+    let live = vault("user3");
+    repository.create(&live).await.unwrap();
+
+    assert_eq!(repository.purge_deleted(cutoff).await.unwrap(), 1);
+
+    assert!(
+        repository
+            .find_deleted(&expired.owner_id, &expired.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .list_revisions(&expired.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        repository
+            .find_deleted(&recent.owner_id, &recent.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        repository
+            .find(&live.owner_id, &live.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        repository
+            .list_by_owner(&expired.owner_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+pub async fn referenced_attachments_cover_history_and_trash<R: VaultRepository>(repository: &R) {
+    let cutoff = now() - Duration::days(30);
+    let attachment = |n: u128| AttachmentId(Uuid::from_u128(100 + n));
+    let package = |ids: Vec<AttachmentId>| VaultPackage {
+        attachments: ids,
+        ..valid_package()
+    };
+    let vault_with = |owner: &str, ids: Vec<AttachmentId>| {
+        let mut vault = vault(owner);
+        vault.package = package(ids);
+        vault
+    };
+
+    let live = vault_with("user1", vec![attachment(1)]);
+    repository.create(&live).await.unwrap();
+    let current = live
+        .update(
+            &live.etag,
+            now(),
+            Etag::new("etag-2").unwrap(),
+            package(vec![attachment(2)]),
+        )
+        .unwrap();
+    repository
+        .update_if_match(&current, &live.etag)
+        .await
+        .unwrap();
+
+    trashed(repository, &vault_with("user2", vec![attachment(3)]), now()).await;
+    trashed(
+        repository,
+        &vault_with("user3", vec![attachment(4)]),
+        cutoff - Duration::days(1),
+    )
+    .await;
+    repository.purge_deleted(cutoff).await.unwrap();
+
+    let referenced = repository.referenced_attachments().await.unwrap();
+
+    assert!(referenced.contains(&attachment(1)), "archived revision");
+    assert!(referenced.contains(&attachment(2)), "live vault");
+    assert!(referenced.contains(&attachment(3)), "vault in the trash");
+    assert!(!referenced.contains(&attachment(4)), "purged vault");
+}
+
+pub async fn stored_ciphertext_roundtrips_and_is_referenced<R: VaultRepository>(repository: &R) {
+    let stored = StoredCiphertext {
+        attachment_id: AttachmentId(Uuid::from_u128(200)),
+        size: 1 << 33,
+        sha256: vec![11; 32],
+    };
+    let mut streamed = vault("user1");
+    streamed.package = valid_package().with_stored_ciphertext(stored.clone());
+
+    repository.create(&streamed).await.unwrap();
+    let found = repository
+        .find(&streamed.owner_id, &streamed.id)
+        .await
+        .unwrap()
+        .expect("created vault not found");
+    assert_same(&found, &streamed);
+
+    let inline = streamed
+        .update(
+            &streamed.etag,
+            now(),
+            Etag::new("etag-2").unwrap(),
+            valid_package(),
+        )
+        .unwrap();
+    repository
+        .update_if_match(&inline, &streamed.etag)
+        .await
+        .unwrap();
+
+    let archived = repository
+        .find_revision(&streamed.id, streamed.revision)
+        .await
+        .unwrap()
+        .expect("streamed revision not archived");
+    assert_eq!(archived.package.stored_ciphertext, Some(stored.clone()));
+
+    let current = repository
+        .find(&inline.owner_id, &inline.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current.package.stored_ciphertext, None);
+
+    let referenced = repository.referenced_attachments().await.unwrap();
+    assert!(
+        referenced.contains(&stored.attachment_id),
+        "archived revision"
+    );
+}
@@ -0,0 +1,178 @@
+//! Behaviour every [`AttachmentRepository`] adapter must share, run with
+//! [`attachment_repository_conformance!`](crate::attachment_repository_conformance).
+
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    vault::OwnerSub,
+};
+use uuid::Uuid;
+
+use crate::{RepositoryError, attachment_repository::AttachmentRepository, conformance::now};
+
+/// Same contract as [`vault_repository_conformance!`](crate::vault_repository_conformance).
+#[macro_export]
+macro_rules! attachment_repository_conformance {
+    (#[$attr:meta] $setup:expr) => {
+        $crate::attachment_repository_conformance!(@all [#[$attr]] $setup);
+    };
+    ($setup:expr) => {
+        $crate::attachment_repository_conformance!(@all [] $setup);
+    };
+    (@all $attrs:tt $setup:expr) => {
+        $crate::attachment_repository_conformance!(@cases $attrs $setup;
+            create_then_find_roundtrips,
+            find_is_scoped_to_owner,
+            mark_completed_persists,
+            mark_completed_of_missing_attachment_fails,
+            list_idle_before_filters_by_last_activity,
+            delete_drops_only_listed,
+        );
+    };
+    (@cases $attrs:tt $setup:expr; $($case:ident),* $(,)?) => {
+        $(
+            $crate::attachment_repository_conformance!(@case $attrs $setup; $case);
+        )*
+    };
+    (@case [$(#[$attr:meta])*] $setup:expr; $case:ident) => {
+        $(#[$attr])*
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn $case() {
+            let (repository, _guard) = $setup.await;
+            $crate::conformance::attachment_repository::$case(&repository).await;
+        }
+    };
+}
+
+pub fn attachment(owner: &str, created_at: DateTime<Utc>) -> Attachment {
+    Attachment::new(
+        AttachmentId(Uuid::new_v4()),
+        OwnerSub::new(owner).unwrap(),
+        10 * 1024 + 7,
+        1024,
+        created_at,
+    )
+    .unwrap()
+}
+
+pub async fn create_then_find_roundtrips<R: AttachmentRepository>(repository: &R) {
+    let attachment = attachment("user1", now());
+    repository.create(&attachment).await.unwrap();
+
+    let found = repository
+        .find(&attachment.owner_id, &attachment.id)
+        .await
+        .unwrap()
+        .expect("attachment not found");
+
+    assert_eq!(found, attachment);
+}
+
+pub async fn find_is_scoped_to_owner<R: AttachmentRepository>(repository: &R) {
+    let attachment = attachment("user1", now());
+    repository.create(&attachment).await.unwrap();
+
+    let other = OwnerSub::new("user2").unwrap();
+    assert!(
+        repository
+            .find(&other, &attachment.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .find(&attachment.owner_id, &AttachmentId(Uuid::new_v4()))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+pub async fn mark_completed_persists<R: AttachmentRepository>(repository: &R) {
+    let attachment = attachment("user1", now());
+    repository.create(&attachment).await.unwrap();
+
+    let received: Vec<u32> = (0..attachment.chunk_count()).collect();
+    let completed = attachment.complete(&received, now()).unwrap();
+    repository.mark_completed(&completed).await.unwrap();
+
+    let found = repository
+        .find(&attachment.owner_id, &attachment.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found, completed);
+}
+
+pub async fn mark_completed_of_missing_attachment_fails<R: AttachmentRepository>(repository: &R) {
+    let attachment = attachment("user1", now());
+    let received: Vec<u32> = (0..attachment.chunk_count()).collect();
+    let completed = attachment.complete(&received, now()).unwrap();
+
+    assert!(matches!(
+        repository.mark_completed(&completed).await,
+        Err(RepositoryError::AttachmentNotFound { .. })
+    ));
+}
+
+/// An upload started long ago but completed recently is not idle.
+pub async fn list_idle_before_filters_by_last_activity<R: AttachmentRepository>(repository: &R) {
+    let cutoff = now() - Duration::days(1);
+    let old = attachment("user1", cutoff - Duration::hours(1));
+    let other_owner = attachment("user2", cutoff - Duration::hours(2));
+    let recent = attachment("user1", cutoff + Duration::hours(1));
+    let completed_long_ago = attachment("user1", cutoff - Duration::hours(3));
+    let completed_recently = attachment("user1", cutoff - Duration::hours(3));
+    for attachment in [
+        &old,
+        &other_owner,
+        &recent,
+        &completed_long_ago,
+        &completed_recently,
+    ] {
+        repository.create(attachment).await.unwrap();
+    }
+    for (attachment, completed_at) in [
+        (&completed_long_ago, cutoff - Duration::hours(2)),
+        (&completed_recently, cutoff + Duration::hours(2)),
+    ] {
+        let received: Vec<u32> = (0..attachment.chunk_count()).collect();
+        let completed = attachment.complete(&received, completed_at).unwrap();
+        repository.mark_completed(&completed).await.unwrap();
+    }
+
+    let mut listed = repository.list_idle_before(cutoff).await.unwrap();
+    listed.sort();
+    let mut expected = vec![old.id, other_owner.id, completed_long_ago.id];
+    expected.sort();
+
+    assert_eq!(listed, expected);
+}
+
+pub async fn delete_drops_only_listed<R: AttachmentRepository>(repository: &R) {
+    let first = attachment("user1", now());
+    let second = attachment("user1", now());
+    repository.create(&first).await.unwrap();
+    repository.create(&second).await.unwrap();
+
+    repository
+        .delete(&[first.id, AttachmentId(Uuid::new_v4())])
+        .await
+        .unwrap();
+
+    assert!(
+        repository
+            .find(&first.owner_id, &first.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repository
+            .find(&second.owner_id, &second.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
@@ -0,0 +1,105 @@
+//! Behaviour every [`BlobStore`] adapter must share, run with
+//! [`blob_store_conformance!`](crate::blob_store_conformance).
+
+use domain::attachment::AttachmentId;
+use uuid::Uuid;
+
+use crate::blob_store::BlobStore;
+
+/// Same contract as [`vault_repository_conformance!`](crate::vault_repository_conformance).
+#[macro_export]
+macro_rules! blob_store_conformance {
+    (#[$attr:meta] $setup:expr) => {
+        $crate::blob_store_conformance!(@all [#[$attr]] $setup);
+    };
+    ($setup:expr) => {
+        $crate::blob_store_conformance!(@all [] $setup);
+    };
+    (@all $attrs:tt $setup:expr) => {
+        $crate::blob_store_conformance!(@cases $attrs $setup;
+            put_then_get_roundtrips,
+            put_replaces_chunk,
+            get_missing_chunk_returns_none,
+            list_chunks_is_ordered_and_scoped,
+            delete_removes_every_chunk,
+        );
+    };
+    (@cases $attrs:tt $setup:expr; $($case:ident),* $(,)?) => {
+        $(
+            $crate::blob_store_conformance!(@case $attrs $setup; $case);
+        )*
+    };
+    (@case [$(#[$attr:meta])*] $setup:expr; $case:ident) => {
+        $(#[$attr])*
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn $case() {
+            let (store, _guard) = $setup.await;
+            $crate::conformance::blob_store::$case(&store).await;
+        }
+    };
+}
+
+fn attachment_id() -> AttachmentId {
+    AttachmentId(Uuid::new_v4())
+}
+
+pub async fn put_then_get_roundtrips<B: BlobStore>(store: &B) {
+    let id = attachment_id();
+    let bytes: Vec<u8> = (0..=255).collect();
+
+    store.put_chunk(&id, 0, bytes.clone()).await.unwrap();
+
+    assert_eq!(store.get_chunk(&id, 0).await.unwrap(), Some(bytes));
+}
+
+pub async fn put_replaces_chunk<B: BlobStore>(store: &B) {
+    let id = attachment_id();
+
+    store.put_chunk(&id, 3, vec![1; 16]).await.unwrap();
+    store.put_chunk(&id, 3, vec![2; 8]).await.unwrap();
+
+    assert_eq!(store.get_chunk(&id, 3).await.unwrap(), Some(vec![2; 8]));
+    assert_eq!(store.list_chunks(&id).await.unwrap(), vec![3]);
+}
+
+pub async fn get_missing_chunk_returns_none<B: BlobStore>(store: &B) {
+    let id = attachment_id();
+    store.put_chunk(&id, 0, vec![1; 4]).await.unwrap();
+
+    assert_eq!(store.get_chunk(&id, 1).await.unwrap(), None);
+    assert_eq!(store.get_chunk(&attachment_id(), 0).await.unwrap(), None);
+}
+
+pub async fn list_chunks_is_ordered_and_scoped<B: BlobStore>(store: &B) {
+    let id = attachment_id();
+    let other = attachment_id();
+    for index in [10, 2, 0, 1_000] {
+        store.put_chunk(&id, index, vec![1; 4]).await.unwrap();
+    }
+    store.put_chunk(&other, 5, vec![1; 4]).await.unwrap();
+
+    assert_eq!(store.list_chunks(&id).await.unwrap(), vec![0, 2, 10, 1_000]);
+    assert!(
+        store
+            .list_chunks(&attachment_id())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+pub async fn delete_removes_every_chunk<B: BlobStore>(store: &B) {
+    let id = attachment_id();
+    let other = attachment_id();
+    for index in 0..3 {
+        store.put_chunk(&id, index, vec![1; 4]).await.unwrap();
+    }
+    store.put_chunk(&other, 0, vec![2; 4]).await.unwrap();
+
+    store.delete(&id).await.unwrap();
+    store.delete(&attachment_id()).await.unwrap();
+
+    assert!(store.list_chunks(&id).await.unwrap().is_empty());
+    assert_eq!(store.get_chunk(&id, 0).await.unwrap(), None);
+    assert_eq!(store.get_chunk(&other, 0).await.unwrap(), Some(vec![2; 4]));
+}
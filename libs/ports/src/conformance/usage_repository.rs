@@ -0,0 +1,141 @@
+//! Behaviour every [`UsageRepository`] adapter must share, run with
+//! [`usage_repository_conformance!`](crate::usage_repository_conformance).
+
+use domain::{quota::StorageUsage, vault::OwnerSub};
+
+use crate::{
+    attachment_repository::AttachmentRepository,
+    conformance::{attachment_repository::attachment, now, trashed, vault, vault_with_history},
+    usage_repository::UsageRepository,
+    vault_repository::VaultRepository,
+};
+
+/// Like [`vault_repository_conformance!`](crate::vault_repository_conformance),
+/// except that `$setup` yields `((vaults, attachments, usage), guard)`
+/// over the same storage, since usage is measured across all of it.
+#[macro_export]
+macro_rules! usage_repository_conformance {
+    (#[$attr:meta] $setup:expr) => {
+        $crate::usage_repository_conformance!(@all [#[$attr]] $setup);
+    };
+    ($setup:expr) => {
+        $crate::usage_repository_conformance!(@all [] $setup);
+    };
+    (@all $attrs:tt $setup:expr) => {
+        $crate::usage_repository_conformance!(@cases $attrs $setup;
+            owner_without_data_uses_nothing,
+            counts_vaults_and_current_bytes,
+            history_bytes_cover_archived_revisions,
+            trashed_vaults_still_count,
+            attachment_bytes_are_declared_sizes,
+        );
+    };
+    (@cases $attrs:tt $setup:expr; $($case:ident),* $(,)?) => {
+        $(
+            $crate::usage_repository_conformance!(@case $attrs $setup; $case);
+        )*
+    };
+    (@case [$(#[$attr:meta])*] $setup:expr; $case:ident) => {
+        $(#[$attr])*
+        #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+        async fn $case() {
+            let ((vaults, attachments, usage), _guard) = $setup.await;
+            $crate::conformance::usage_repository::$case(&vaults, &attachments, &usage).await;
+        }
+    };
+}
+
+fn owner(name: &str) -> OwnerSub {
+    OwnerSub::new(name).unwrap()
+}
+
+pub async fn owner_without_data_uses_nothing<R, T, U>(_: &R, _: &T, usage: &U)
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    U: UsageRepository,
+{
+    assert_eq!(
+        usage.usage(&owner("user1")).await.unwrap(),
+        StorageUsage::default()
+    );
+}
+
+pub async fn counts_vaults_and_current_bytes<R, T, U>(vaults: &R, _: &T, usage: &U)
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    U: UsageRepository,
+{
+    let first = vault("user1");
+    let mut second = vault("user1");
+    second.package.metadata = None;
+    for v in [&first, &second, &vault("user2")] {
+        vaults.create(v).await.unwrap();
+    }
+
+    let found = usage.usage(&owner("user1")).await.unwrap();
+
+    assert_eq!(found.vaults, 2);
+    assert_eq!(
+        found.vault_bytes,
+        first.package.byte_size() + second.package.byte_size()
+    );
+    assert_eq!(found.history_bytes, 0);
+}
+
+pub async fn history_bytes_cover_archived_revisions<R, T, U>(vaults: &R, _: &T, usage: &U)
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    U: UsageRepository,
+{
+    let [first, second, third] = vault_with_history(vaults, "user1").await;
+    vault_with_history(vaults, "user2").await;
+
+    let found = usage.usage(&owner("user1")).await.unwrap();
+
+    assert_eq!(found.vaults, 1);
+    assert_eq!(found.vault_bytes, third.package.byte_size());
+    assert_eq!(
+        found.history_bytes,
+        first.package.byte_size() + second.package.byte_size()
+    );
+}
+
+pub async fn trashed_vaults_still_count<R, T, U>(vaults: &R, _: &T, usage: &U)
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    U: UsageRepository,
+{
+    let deleted = trashed(vaults, &vault("user1"), now()).await;
+
+    let found = usage.usage(&owner("user1")).await.unwrap();
+
+    assert_eq!(found.vaults, 1);
+    assert_eq!(found.vault_bytes, deleted.package.byte_size());
+}
+
+pub async fn attachment_bytes_are_declared_sizes<R, T, U>(_: &R, attachments: &T, usage: &U)
+where
+    R: VaultRepository,
+    T: AttachmentRepository,
+    U: UsageRepository,
+{
+    let complete = attachment("user1", now())
+        .complete(&(0..11).collect::<Vec<_>>(), now())
+        .unwrap();
+    for a in [
+        &complete,
+        &attachment("user1", now()),
+        &attachment("user2", now()),
+    ] {
+        attachments.create(a).await.unwrap();
+    }
+
+    let found = usage.usage(&owner("user1")).await.unwrap();
+
+    assert_eq!(found.attachment_bytes, 2 * complete.size);
+    assert_eq!(found.total_bytes(), 2 * complete.size);
+}
@@ -1,6 +1,7 @@
-use domain::vault::{Etag, VaultPackage};
+use domain::vault::{Etag, Revision, VaultPackage};
 
 #[cfg_attr(any(test, feature = "testing"), mockall::automock)]
 pub trait EtagGenerator: Send + Sync {
-    fn generate(&self, package: &VaultPackage) -> Etag;
+    /// Etag for `package` stored as `revision`.
+    fn generate(&self, revision: Revision, package: &VaultPackage) -> Etag;
 }
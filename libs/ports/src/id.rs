@@ -0,0 +1,48 @@
+use domain::{
+    attachment::AttachmentId,
+    vault::{KeySlotId, VaultId},
+};
+use uuid::Uuid;
+
+/// Source of identifiers for new aggregates.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait IdGenerator: Send + Sync {
+    fn vault_id(&self) -> VaultId;
+
+    fn key_slot_id(&self) -> KeySlotId;
+
+    fn attachment_id(&self) -> AttachmentId;
+}
+
+/// UUIDv7 ids: time-ordered, so new rows land at the end of B-tree indexes
+/// instead of at random pages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV7Generator;
+
+impl IdGenerator for UuidV7Generator {
+    fn vault_id(&self) -> VaultId {
+        VaultId(Uuid::now_v7())
+    }
+
+    fn key_slot_id(&self) -> KeySlotId {
+        KeySlotId(Uuid::now_v7())
+    }
+
+    fn attachment_id(&self) -> AttachmentId {
+        AttachmentId(Uuid::now_v7())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::id::{IdGenerator, UuidV7Generator};
+
+    #[test]
+    fn ids_are_v7_and_increasing() {
+        let a = UuidV7Generator.vault_id();
+        let b = UuidV7Generator.vault_id();
+
+        assert_eq!(a.0.get_version_num(), 7);
+        assert!(a.0 < b.0);
+    }
+}
@@ -1,16 +1,35 @@
 use thiserror::Error;
 
+pub mod attachment_repository;
+pub mod blob_store;
+pub mod clock;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod etag;
+pub mod id;
+pub mod usage_repository;
 pub mod vault_repository;
 
 #[derive(Debug, Error)]
 pub enum RepositoryError {
-    #[error("vault not found for owner {owner}")]
-    VaultNotFound { owner: String },
+    #[error("vault {vault_id} not found")]
+    VaultNotFound { vault_id: String },
+
+    #[error("vault {vault_id} already exists")]
+    AlreadyExists { vault_id: String },
 
     #[error("concurrency conflict for vault {vault_id}")]
     ConcurrencyConflict { vault_id: String },
 
+    #[error("attachment {attachment_id} not found")]
+    AttachmentNotFound { attachment_id: String },
+
     #[error("database error: {message}")]
     Database { message: String },
 }
+
+#[derive(Debug, Error)]
+pub enum BlobStoreError {
+    #[error("blob store error: {message}")]
+    Unavailable { message: String },
+}
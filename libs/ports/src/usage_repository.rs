@@ -0,0 +1,12 @@
+use domain::{quota::StorageUsage, vault::OwnerSub};
+
+use crate::RepositoryError;
+
+/// What an owner stores across vaults, history and attachments, for quotas.
+#[cfg_attr(any(test, feature = "testing"), mockall::automock)]
+pub trait UsageRepository: Send + Sync {
+    fn usage(
+        &self,
+        owner_id: &OwnerSub,
+    ) -> impl Future<Output = Result<StorageUsage, RepositoryError>> + Send;
+}
@@ -1,19 +1,106 @@
-use domain::vault::{Etag, OwnerSub, Vault};
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::AttachmentId,
+    vault::{
+        Etag, OwnerSub, Revision, RevisionSummary, Vault, VaultId, VaultRevision, VaultSummary,
+        VaultVersion,
+    },
+};
 
 use crate::RepositoryError;
 
 #[cfg_attr(any(test, feature = "testing"), mockall::automock)]
 pub trait VaultRepository: Send + Sync {
-    fn find_by_owner(
+    /// Loads a live vault of `owner_id`; vaults in the trash and vaults of
+    /// other owners are not returned.
+    fn find(
         &self,
         owner_id: &OwnerSub,
+        vault_id: &VaultId,
     ) -> impl Future<Output = Result<Option<Vault>, RepositoryError>> + Send;
 
+    /// Loads only the version metadata, leaving the ciphertext in storage.
+    fn find_version(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> impl Future<Output = Result<Option<VaultVersion>, RepositoryError>> + Send;
+
+    /// Loads a vault of `owner_id` only if it is in the trash.
+    fn find_deleted(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> impl Future<Output = Result<Option<Vault>, RepositoryError>> + Send;
+
+    /// Live vaults of `owner_id`, oldest first, without their content.
+    fn list_by_owner(
+        &self,
+        owner_id: &OwnerSub,
+    ) -> impl Future<Output = Result<Vec<VaultSummary>, RepositoryError>> + Send;
+
     fn create(&self, vault: &Vault) -> impl Future<Output = Result<(), RepositoryError>> + Send;
 
+    /// Replaces the stored vault if its etag still matches and it is not in
+    /// the trash, archiving the replaced revision into the vault's history in
+    /// the same write.
     fn update_if_match(
         &self,
         vault: &Vault,
         expected_etag: &Etag,
     ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Stores the tombstone of `vault` if the live vault's etag still matches.
+    fn mark_deleted(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Clears the tombstone of a vault in the trash.
+    fn restore_deleted(
+        &self,
+        vault: &Vault,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Hard-deletes every vault deleted before `deleted_before`, with its
+    /// history, and returns how many were removed.
+    fn purge_deleted(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, RepositoryError>> + Send;
+
+    /// Archived revisions of a vault, newest first.
+    fn list_revisions(
+        &self,
+        vault_id: &VaultId,
+    ) -> impl Future<Output = Result<Vec<RevisionSummary>, RepositoryError>> + Send;
+
+    fn find_revision(
+        &self,
+        vault_id: &VaultId,
+        revision: Revision,
+    ) -> impl Future<Output = Result<Option<VaultRevision>, RepositoryError>> + Send;
+
+    /// Drops archived revisions; unknown revisions are ignored.
+    fn delete_revisions(
+        &self,
+        vault_id: &VaultId,
+        revisions: &[Revision],
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Drops every archived revision, of any vault, archived before
+    /// `archived_before`, and returns how many were removed.
+    fn purge_revisions(
+        &self,
+        archived_before: DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, RepositoryError>> + Send;
+
+    /// Every attachment referenced by a vault, live or in the trash, or by
+    /// an archived revision; the others can be collected.
+    fn referenced_attachments(
+        &self,
+    ) -> impl Future<Output = Result<HashSet<AttachmentId>, RepositoryError>> + Send;
 }
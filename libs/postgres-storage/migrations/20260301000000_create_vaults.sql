@@ -0,0 +1,26 @@
+-- One vault per owner; the package is stored in typed columns so that the
+-- header can be inspected and migrated without decoding an opaque blob.
+CREATE TABLE vaults (
+    id                UUID        PRIMARY KEY,
+    owner_id          TEXT        NOT NULL,
+
+    revision          BIGINT      NOT NULL CHECK (revision >= 0),
+    etag              TEXT        NOT NULL CHECK (etag <> ''),
+
+    crypto_version    INTEGER     NOT NULL CHECK (crypto_version > 0),
+    kdf_alg           TEXT        NOT NULL,
+    kdf_salt          BYTEA       NOT NULL,
+    kdf_m_kib         BIGINT      NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t             BIGINT      NOT NULL CHECK (kdf_t >= 0),
+    kdf_p             BIGINT      NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key BYTEA       NOT NULL,
+
+    nonce             BYTEA       NOT NULL,
+    aad               BYTEA       NOT NULL,
+    ciphertext        BYTEA       NOT NULL,
+
+    created_at        TIMESTAMPTZ NOT NULL,
+    updated_at        TIMESTAMPTZ NOT NULL,
+
+    CONSTRAINT vaults_owner_id_key UNIQUE (owner_id)
+);
@@ -0,0 +1,24 @@
+-- Revisions replaced by a newer write, kept for restore until the retention
+-- policy prunes them. Package columns mirror `vaults`.
+CREATE TABLE vault_revisions (
+    vault_id          UUID        NOT NULL REFERENCES vaults (id) ON DELETE CASCADE,
+    revision          BIGINT      NOT NULL CHECK (revision >= 0),
+    etag              TEXT        NOT NULL CHECK (etag <> ''),
+
+    crypto_version    INTEGER     NOT NULL CHECK (crypto_version > 0),
+    kdf_alg           TEXT        NOT NULL,
+    kdf_salt          BYTEA       NOT NULL,
+    kdf_m_kib         BIGINT      NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t             BIGINT      NOT NULL CHECK (kdf_t >= 0),
+    kdf_p             BIGINT      NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key BYTEA       NOT NULL,
+
+    nonce             BYTEA       NOT NULL,
+    aad               BYTEA       NOT NULL,
+    ciphertext        BYTEA       NOT NULL,
+
+    saved_at          TIMESTAMPTZ NOT NULL,
+    archived_at       TIMESTAMPTZ NOT NULL,
+
+    PRIMARY KEY (vault_id, revision)
+);
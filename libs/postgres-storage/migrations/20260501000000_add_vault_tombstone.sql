@@ -0,0 +1,5 @@
+-- Soft delete: a vault with a tombstone sits in the trash until it is
+-- restored or purged. The partial index serves the purge job.
+ALTER TABLE vaults ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX vaults_deleted_at_idx ON vaults (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,20 @@
+-- Owners may hold several vaults, addressed by id. The unique constraint
+-- becomes a plain index serving owner-scoped lookups and listings.
+ALTER TABLE vaults DROP CONSTRAINT vaults_owner_id_key;
+
+CREATE INDEX vaults_owner_id_idx ON vaults (owner_id, created_at, id);
+
+-- Vault name and other descriptive fields, encrypted by the client.
+ALTER TABLE vaults
+    ADD COLUMN metadata_nonce      BYTEA,
+    ADD COLUMN metadata_aad        BYTEA,
+    ADD COLUMN metadata_ciphertext BYTEA,
+    ADD CONSTRAINT vaults_metadata_complete
+        CHECK (num_nulls(metadata_nonce, metadata_aad, metadata_ciphertext) IN (0, 3));
+
+ALTER TABLE vault_revisions
+    ADD COLUMN metadata_nonce      BYTEA,
+    ADD COLUMN metadata_aad        BYTEA,
+    ADD COLUMN metadata_ciphertext BYTEA,
+    ADD CONSTRAINT vault_revisions_metadata_complete
+        CHECK (num_nulls(metadata_nonce, metadata_aad, metadata_ciphertext) IN (0, 3));
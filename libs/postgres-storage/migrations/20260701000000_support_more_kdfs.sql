@@ -0,0 +1,39 @@
+-- scrypt and PBKDF2 alongside Argon2id. Each algorithm has its own cost
+-- columns; those of other algorithms stay null.
+ALTER TABLE vaults
+    ALTER COLUMN kdf_m_kib DROP NOT NULL,
+    ALTER COLUMN kdf_t     DROP NOT NULL,
+    ALTER COLUMN kdf_p     DROP NOT NULL,
+    ADD COLUMN kdf_log_n      BIGINT CHECK (kdf_log_n >= 0),
+    ADD COLUMN kdf_r          BIGINT CHECK (kdf_r >= 0),
+    ADD COLUMN kdf_iterations BIGINT CHECK (kdf_iterations >= 0),
+    ADD CONSTRAINT vaults_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN num_nonnulls(kdf_m_kib, kdf_t, kdf_p) = 3
+                AND num_nonnulls(kdf_log_n, kdf_r, kdf_iterations) = 0
+            WHEN 'scrypt' THEN num_nonnulls(kdf_log_n, kdf_r, kdf_p) = 3
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_iterations) = 0
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r) = 0
+            ELSE FALSE
+        END
+    );
+
+ALTER TABLE vault_revisions
+    ALTER COLUMN kdf_m_kib DROP NOT NULL,
+    ALTER COLUMN kdf_t     DROP NOT NULL,
+    ALTER COLUMN kdf_p     DROP NOT NULL,
+    ADD COLUMN kdf_log_n      BIGINT CHECK (kdf_log_n >= 0),
+    ADD COLUMN kdf_r          BIGINT CHECK (kdf_r >= 0),
+    ADD COLUMN kdf_iterations BIGINT CHECK (kdf_iterations >= 0),
+    ADD CONSTRAINT vault_revisions_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN num_nonnulls(kdf_m_kib, kdf_t, kdf_p) = 3
+                AND num_nonnulls(kdf_log_n, kdf_r, kdf_iterations) = 0
+            WHEN 'scrypt' THEN num_nonnulls(kdf_log_n, kdf_r, kdf_p) = 3
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_iterations) = 0
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r) = 0
+            ELSE FALSE
+        END
+    );
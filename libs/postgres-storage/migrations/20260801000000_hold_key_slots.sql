@@ -0,0 +1,66 @@
+-- Headers hold several key slots, each wrapping the vault key for one unlock
+-- method. The slots move into a JSON array; the existing master-password
+-- columns become its first entry, with a fresh slot id.
+ALTER TABLE vaults ADD COLUMN key_slots JSONB;
+
+UPDATE vaults SET key_slots = jsonb_build_array(jsonb_strip_nulls(jsonb_build_object(
+    'id', gen_random_uuid(),
+    'kind', 'master_password',
+    'kdf_alg', kdf_alg,
+    'kdf_salt', encode(kdf_salt, 'hex'),
+    'kdf_m_kib', kdf_m_kib,
+    'kdf_t', kdf_t,
+    'kdf_p', kdf_p,
+    'kdf_log_n', kdf_log_n,
+    'kdf_r', kdf_r,
+    'kdf_iterations', kdf_iterations,
+    'wrapped_vault_key', encode(wrapped_vault_key, 'hex')
+)));
+
+ALTER TABLE vaults
+    ALTER COLUMN key_slots SET NOT NULL,
+    ADD CONSTRAINT vaults_key_slots CHECK (
+        jsonb_typeof(key_slots) = 'array' AND jsonb_array_length(key_slots) > 0
+    ),
+    DROP CONSTRAINT vaults_kdf_params,
+    DROP COLUMN kdf_alg,
+    DROP COLUMN kdf_salt,
+    DROP COLUMN kdf_m_kib,
+    DROP COLUMN kdf_t,
+    DROP COLUMN kdf_p,
+    DROP COLUMN kdf_log_n,
+    DROP COLUMN kdf_r,
+    DROP COLUMN kdf_iterations,
+    DROP COLUMN wrapped_vault_key;
+
+ALTER TABLE vault_revisions ADD COLUMN key_slots JSONB;
+
+UPDATE vault_revisions SET key_slots = jsonb_build_array(jsonb_strip_nulls(jsonb_build_object(
+    'id', gen_random_uuid(),
+    'kind', 'master_password',
+    'kdf_alg', kdf_alg,
+    'kdf_salt', encode(kdf_salt, 'hex'),
+    'kdf_m_kib', kdf_m_kib,
+    'kdf_t', kdf_t,
+    'kdf_p', kdf_p,
+    'kdf_log_n', kdf_log_n,
+    'kdf_r', kdf_r,
+    'kdf_iterations', kdf_iterations,
+    'wrapped_vault_key', encode(wrapped_vault_key, 'hex')
+)));
+
+ALTER TABLE vault_revisions
+    ALTER COLUMN key_slots SET NOT NULL,
+    ADD CONSTRAINT vault_revisions_key_slots CHECK (
+        jsonb_typeof(key_slots) = 'array' AND jsonb_array_length(key_slots) > 0
+    ),
+    DROP CONSTRAINT vault_revisions_kdf_params,
+    DROP COLUMN kdf_alg,
+    DROP COLUMN kdf_salt,
+    DROP COLUMN kdf_m_kib,
+    DROP COLUMN kdf_t,
+    DROP COLUMN kdf_p,
+    DROP COLUMN kdf_log_n,
+    DROP COLUMN kdf_r,
+    DROP COLUMN kdf_iterations,
+    DROP COLUMN wrapped_vault_key;
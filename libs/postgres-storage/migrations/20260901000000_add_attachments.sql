@@ -0,0 +1,19 @@
+-- Client-encrypted attachments, uploaded in chunks to the blob store. Only
+-- the upload records live here. Packages list the attachments they reference
+-- so that those no vault or revision points at can be collected.
+CREATE TABLE attachments (
+    id           UUID        PRIMARY KEY,
+    owner_id     TEXT        NOT NULL,
+
+    size         BIGINT      NOT NULL CHECK (size > 0),
+    chunk_size   BIGINT      NOT NULL CHECK (chunk_size > 0),
+
+    created_at   TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX attachments_created_at_idx ON attachments (created_at);
+
+ALTER TABLE vaults ADD COLUMN attachment_ids UUID[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE vault_revisions ADD COLUMN attachment_ids UUID[] NOT NULL DEFAULT '{}';
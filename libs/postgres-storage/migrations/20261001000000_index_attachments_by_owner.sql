@@ -0,0 +1,2 @@
+-- Quotas sum the attachments of one owner on every write.
+CREATE INDEX attachments_owner_id_idx ON attachments (owner_id);
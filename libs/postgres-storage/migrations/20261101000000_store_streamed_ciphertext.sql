@@ -0,0 +1,18 @@
+-- Ciphertexts streamed by clients are kept in the blob store, as the chunks
+-- of an attachment, instead of the ciphertext column, which is then empty.
+-- The size and digest are part of the package's content hash.
+ALTER TABLE vaults
+    ADD COLUMN stored_ciphertext_id     UUID,
+    ADD COLUMN stored_ciphertext_size   BIGINT CHECK (stored_ciphertext_size >= 0),
+    ADD COLUMN stored_ciphertext_sha256 BYTEA,
+    ADD CONSTRAINT vaults_stored_ciphertext_complete CHECK (
+        num_nulls(stored_ciphertext_id, stored_ciphertext_size, stored_ciphertext_sha256) IN (0, 3)
+    );
+
+ALTER TABLE vault_revisions
+    ADD COLUMN stored_ciphertext_id     UUID,
+    ADD COLUMN stored_ciphertext_size   BIGINT CHECK (stored_ciphertext_size >= 0),
+    ADD COLUMN stored_ciphertext_sha256 BYTEA,
+    ADD CONSTRAINT vault_revisions_stored_ciphertext_complete CHECK (
+        num_nulls(stored_ciphertext_id, stored_ciphertext_size, stored_ciphertext_sha256) IN (0, 3)
+    );
@@ -0,0 +1,156 @@
+-- Key slots leave the JSON column for typed rows, one per slot, in the order
+-- the header lists them. Archived revisions keep theirs in a table of their
+-- own, removed with the revision.
+CREATE TABLE vault_key_slots (
+    vault_id          UUID     NOT NULL REFERENCES vaults (id) ON DELETE CASCADE,
+    slot_id           UUID     NOT NULL,
+    position          SMALLINT NOT NULL CHECK (position >= 0),
+
+    kind              TEXT     NOT NULL,
+
+    kdf_alg           TEXT,
+    kdf_salt          BYTEA,
+    kdf_m_kib         BIGINT CHECK (kdf_m_kib >= 0),
+    kdf_t             BIGINT CHECK (kdf_t >= 0),
+    kdf_p             BIGINT CHECK (kdf_p >= 0),
+    kdf_log_n         BIGINT CHECK (kdf_log_n >= 0),
+    kdf_r             BIGINT CHECK (kdf_r >= 0),
+    kdf_iterations    BIGINT CHECK (kdf_iterations >= 0),
+
+    salt              BYTEA,
+    device_id         TEXT,
+    credential_id     BYTEA,
+    prf_salt          BYTEA,
+
+    wrapped_vault_key BYTEA    NOT NULL,
+
+    PRIMARY KEY (vault_id, slot_id),
+    CONSTRAINT vault_key_slots_position UNIQUE (vault_id, position),
+    CONSTRAINT vault_key_slots_kind CHECK (
+        CASE kind
+            WHEN 'master_password' THEN num_nonnulls(kdf_alg, kdf_salt) = 2
+                AND num_nonnulls(salt, device_id, credential_id, prf_salt) = 0
+            WHEN 'recovery_key' THEN salt IS NOT NULL
+                AND num_nonnulls(kdf_alg, kdf_salt, device_id, credential_id, prf_salt) = 0
+            WHEN 'device' THEN device_id IS NOT NULL
+                AND num_nonnulls(kdf_alg, kdf_salt, salt, credential_id, prf_salt) = 0
+            WHEN 'passkey_prf' THEN num_nonnulls(credential_id, prf_salt) = 2
+                AND num_nonnulls(kdf_alg, kdf_salt, salt, device_id) = 0
+            ELSE FALSE
+        END
+    ),
+    CONSTRAINT vault_key_slots_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN num_nonnulls(kdf_m_kib, kdf_t, kdf_p) = 3
+                AND num_nonnulls(kdf_log_n, kdf_r, kdf_iterations) = 0
+            WHEN 'scrypt' THEN num_nonnulls(kdf_log_n, kdf_r, kdf_p) = 3
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_iterations) = 0
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r) = 0
+            ELSE kdf_alg IS NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r, kdf_iterations) = 0
+        END
+    )
+);
+
+CREATE TABLE vault_revision_key_slots (
+    vault_id          UUID     NOT NULL,
+    revision          BIGINT   NOT NULL,
+    slot_id           UUID     NOT NULL,
+    position          SMALLINT NOT NULL CHECK (position >= 0),
+
+    kind              TEXT     NOT NULL,
+
+    kdf_alg           TEXT,
+    kdf_salt          BYTEA,
+    kdf_m_kib         BIGINT CHECK (kdf_m_kib >= 0),
+    kdf_t             BIGINT CHECK (kdf_t >= 0),
+    kdf_p             BIGINT CHECK (kdf_p >= 0),
+    kdf_log_n         BIGINT CHECK (kdf_log_n >= 0),
+    kdf_r             BIGINT CHECK (kdf_r >= 0),
+    kdf_iterations    BIGINT CHECK (kdf_iterations >= 0),
+
+    salt              BYTEA,
+    device_id         TEXT,
+    credential_id     BYTEA,
+    prf_salt          BYTEA,
+
+    wrapped_vault_key BYTEA    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision, slot_id),
+    FOREIGN KEY (vault_id, revision)
+        REFERENCES vault_revisions (vault_id, revision) ON DELETE CASCADE,
+    CONSTRAINT vault_revision_key_slots_position UNIQUE (vault_id, revision, position),
+    CONSTRAINT vault_revision_key_slots_kind CHECK (
+        CASE kind
+            WHEN 'master_password' THEN num_nonnulls(kdf_alg, kdf_salt) = 2
+                AND num_nonnulls(salt, device_id, credential_id, prf_salt) = 0
+            WHEN 'recovery_key' THEN salt IS NOT NULL
+                AND num_nonnulls(kdf_alg, kdf_salt, device_id, credential_id, prf_salt) = 0
+            WHEN 'device' THEN device_id IS NOT NULL
+                AND num_nonnulls(kdf_alg, kdf_salt, salt, credential_id, prf_salt) = 0
+            WHEN 'passkey_prf' THEN num_nonnulls(credential_id, prf_salt) = 2
+                AND num_nonnulls(kdf_alg, kdf_salt, salt, device_id) = 0
+            ELSE FALSE
+        END
+    ),
+    CONSTRAINT vault_revision_key_slots_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN num_nonnulls(kdf_m_kib, kdf_t, kdf_p) = 3
+                AND num_nonnulls(kdf_log_n, kdf_r, kdf_iterations) = 0
+            WHEN 'scrypt' THEN num_nonnulls(kdf_log_n, kdf_r, kdf_p) = 3
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_iterations) = 0
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r) = 0
+            ELSE kdf_alg IS NULL
+                AND num_nonnulls(kdf_m_kib, kdf_t, kdf_p, kdf_log_n, kdf_r, kdf_iterations) = 0
+        END
+    )
+);
+
+INSERT INTO vault_key_slots
+SELECT
+    v.id,
+    (slot ->> 'id')::uuid,
+    position - 1,
+    slot ->> 'kind',
+    slot ->> 'kdf_alg',
+    decode(slot ->> 'kdf_salt', 'hex'),
+    (slot ->> 'kdf_m_kib')::bigint,
+    (slot ->> 'kdf_t')::bigint,
+    (slot ->> 'kdf_p')::bigint,
+    (slot ->> 'kdf_log_n')::bigint,
+    (slot ->> 'kdf_r')::bigint,
+    (slot ->> 'kdf_iterations')::bigint,
+    decode(slot ->> 'salt', 'hex'),
+    slot ->> 'device_id',
+    decode(slot ->> 'credential_id', 'hex'),
+    decode(slot ->> 'prf_salt', 'hex'),
+    decode(slot ->> 'wrapped_vault_key', 'hex')
+FROM vaults v, jsonb_array_elements(v.key_slots) WITH ORDINALITY AS slots (slot, position);
+
+INSERT INTO vault_revision_key_slots
+SELECT
+    r.vault_id,
+    r.revision,
+    (slot ->> 'id')::uuid,
+    position - 1,
+    slot ->> 'kind',
+    slot ->> 'kdf_alg',
+    decode(slot ->> 'kdf_salt', 'hex'),
+    (slot ->> 'kdf_m_kib')::bigint,
+    (slot ->> 'kdf_t')::bigint,
+    (slot ->> 'kdf_p')::bigint,
+    (slot ->> 'kdf_log_n')::bigint,
+    (slot ->> 'kdf_r')::bigint,
+    (slot ->> 'kdf_iterations')::bigint,
+    decode(slot ->> 'salt', 'hex'),
+    slot ->> 'device_id',
+    decode(slot ->> 'credential_id', 'hex'),
+    decode(slot ->> 'prf_salt', 'hex'),
+    decode(slot ->> 'wrapped_vault_key', 'hex')
+FROM vault_revisions r, jsonb_array_elements(r.key_slots) WITH ORDINALITY AS slots (slot, position);
+
+ALTER TABLE vaults DROP COLUMN key_slots;
+
+ALTER TABLE vault_revisions DROP COLUMN key_slots;
@@ -0,0 +1,3 @@
+-- Revisions older than the retention age are purged across all vaults by
+-- when they were archived.
+CREATE INDEX vault_revisions_archived_at_idx ON vault_revisions (archived_at);
@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    vault::OwnerSub,
+};
+use ports::{RepositoryError, attachment_repository::AttachmentRepository};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{errors::database, rows::AttachmentRow};
+
+/// Attachment records backed by PostgreSQL; see
+/// [`PostgresVaultRepository::attachments`](crate::PostgresVaultRepository::attachments).
+#[derive(Debug, Clone)]
+pub struct PostgresAttachmentRepository {
+    pool: PgPool,
+}
+
+impl PostgresAttachmentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl AttachmentRepository for PostgresAttachmentRepository {
+    async fn create(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO attachments (id, owner_id, size, chunk_size, created_at, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(attachment.id.0)
+        .bind(&attachment.owner_id.0)
+        .bind(size_column(attachment.size)?)
+        .bind(i64::from(attachment.chunk_size))
+        .bind(attachment.created_at)
+        .bind(attachment.completed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to create attachment", e))?;
+
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        attachment_id: &AttachmentId,
+    ) -> Result<Option<Attachment>, RepositoryError> {
+        let row: Option<AttachmentRow> = sqlx::query_as(
+            "SELECT id, owner_id, size, chunk_size, created_at, completed_at FROM attachments \
+             WHERE id = $1 AND owner_id = $2",
+        )
+        .bind(attachment_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| database("failed to load attachment", e))?;
+
+        row.map(Attachment::try_from).transpose()
+    }
+
+    async fn mark_completed(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        let result =
+            sqlx::query("UPDATE attachments SET completed_at = $3 WHERE id = $1 AND owner_id = $2")
+                .bind(attachment.id.0)
+                .bind(&attachment.owner_id.0)
+                .bind(attachment.completed_at)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| database("failed to complete attachment", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::AttachmentNotFound {
+                attachment_id: attachment.id.0.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn list_idle_before(
+        &self,
+        idle_before: DateTime<Utc>,
+    ) -> Result<Vec<AttachmentId>, RepositoryError> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM attachments WHERE coalesce(completed_at, created_at) < $1",
+        )
+        .bind(idle_before)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list attachments", e))?;
+
+        Ok(ids.into_iter().map(AttachmentId).collect())
+    }
+
+    async fn delete(&self, attachment_ids: &[AttachmentId]) -> Result<(), RepositoryError> {
+        let ids: Vec<Uuid> = attachment_ids.iter().map(|id| id.0).collect();
+
+        sqlx::query("DELETE FROM attachments WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to delete attachments", e))?;
+
+        Ok(())
+    }
+}
+
+/// Size as stored: Postgres has no unsigned integers.
+fn size_column(size: u64) -> Result<i64, RepositoryError> {
+    i64::try_from(size).map_err(|_| RepositoryError::Database {
+        message: format!("attachment size {size} exceeds BIGINT"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ephemeral;
+
+    ports::attachment_repository_conformance!(
+        #[ignore = "needs PostgreSQL; run with --ignored, see ephemeral"]
+        async {
+            let (repository, guard) = ephemeral::database().await;
+            (repository.attachments(), guard)
+        }
+    );
+}
@@ -0,0 +1,138 @@
+//! Throwaway databases for the adapter tests.
+//!
+//! The tests that need a server are ignored by default, so that a plain
+//! `cargo test --workspace` passes on machines without PostgreSQL; run them
+//! with `cargo test -p postgres-storage -- --ignored`.
+//!
+//! `TEST_DATABASE_URL` points the tests at an existing server, where each
+//! test gets a fresh database. Otherwise a private cluster is started from
+//! the local PostgreSQL binaries (`initdb`, `postgres`) in a temporary
+//! directory and stopped when the test ends. Ignored tests that are asked
+//! for fail when neither is available, rather than passing without having
+//! run.
+
+use std::{
+    path::Path,
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    time::Duration,
+};
+
+use sqlx::{
+    Connection, PgConnection, PgPool,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::PostgresVaultRepository;
+
+/// Keeps the cluster alive, if we started one.
+pub(crate) struct EphemeralDatabase {
+    _cluster: Option<Cluster>,
+}
+
+struct Cluster {
+    server: Child,
+    _dir: TempDir,
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        let _ = self.server.kill();
+        let _ = self.server.wait();
+    }
+}
+
+/// A repository over a migrated, empty database.
+pub(crate) async fn database() -> (PostgresVaultRepository, EphemeralDatabase) {
+    let (admin, cluster) = match std::env::var("TEST_DATABASE_URL") {
+        Ok(url) => (
+            PgConnectOptions::from_str(&url).expect("invalid TEST_DATABASE_URL"),
+            None,
+        ),
+        Err(_) => {
+            let (options, cluster) = start_cluster()
+                .await
+                .expect("no PostgreSQL: set TEST_DATABASE_URL or install initdb and postgres");
+            (options, Some(cluster))
+        }
+    };
+
+    let name = format!("vault_test_{}", Uuid::new_v4().simple());
+
+    let mut conn = PgConnection::connect_with(&admin)
+        .await
+        .expect("failed to connect to test server");
+    sqlx::query(&format!("CREATE DATABASE {name}"))
+        .execute(&mut conn)
+        .await
+        .expect("failed to create test database");
+    conn.close().await.ok();
+
+    let pool: PgPool = PgPoolOptions::new()
+        .max_connections(8)
+        .connect_with(admin.database(&name))
+        .await
+        .expect("failed to connect to test database");
+
+    let repository = PostgresVaultRepository::new(pool);
+    repository.migrate().await.expect("migrations failed");
+
+    (repository, EphemeralDatabase { _cluster: cluster })
+}
+
+async fn start_cluster() -> Option<(PgConnectOptions, Cluster)> {
+    let dir = TempDir::new().ok()?;
+    let data = dir.path().join("data");
+
+    let initdb = Command::new("initdb")
+        .arg("-D")
+        .arg(&data)
+        .args(["-U", "postgres", "--auth=trust", "-E", "UTF8"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+
+    if !initdb.success() {
+        return None;
+    }
+
+    // Unix socket only, inside the temp dir: no port clashes between tests.
+    let server = Command::new("postgres")
+        .arg("-D")
+        .arg(&data)
+        .arg("-k")
+        .arg(dir.path())
+        .args(["-c", "listen_addresses=", "-c", "fsync=off"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let cluster = Cluster { server, _dir: dir };
+    let options = PgConnectOptions::new()
+        .socket(cluster._dir.path())
+        .username("postgres")
+        .database("postgres");
+
+    wait_until_ready(&options, cluster._dir.path()).await?;
+
+    Some((options, cluster))
+}
+
+async fn wait_until_ready(options: &PgConnectOptions, dir: &Path) -> Option<()> {
+    for _ in 0..100 {
+        if dir.join(".s.PGSQL.5432").exists()
+            && let Ok(conn) = PgConnection::connect_with(options).await
+        {
+            conn.close().await.ok();
+            return Some(());
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    None
+}
@@ -0,0 +1,28 @@
+use ports::RepositoryError;
+use tracing::error;
+
+/// Primary key of `vaults`: a clash means the vault id is already taken.
+pub(crate) const ID_CONSTRAINT: &str = "vaults_pkey";
+
+pub(crate) fn database(context: &str, e: sqlx::Error) -> RepositoryError {
+    error!("{}: {:?}", context, e);
+    RepositoryError::Database {
+        message: format!("{context}: {e}"),
+    }
+}
+
+pub(crate) fn is_id_conflict(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db) => {
+            db.is_unique_violation() && db.constraint() == Some(ID_CONSTRAINT)
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn corrupt(column: &str, message: impl std::fmt::Display) -> RepositoryError {
+    error!("corrupt vault row, column {}: {}", column, message);
+    RepositoryError::Database {
+        message: format!("corrupt vault row, column {column}: {message}"),
+    }
+}
@@ -0,0 +1,15 @@
+use sqlx::migrate::Migrator;
+
+mod attachment_repository;
+mod errors;
+mod rows;
+mod vault_repository;
+
+#[cfg(test)]
+mod ephemeral;
+
+pub use attachment_repository::PostgresAttachmentRepository;
+pub use vault_repository::PostgresVaultRepository;
+
+/// Schema migrations embedded at compile time from `migrations/`.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
@@ -0,0 +1,495 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    quota::StorageUsage,
+    vault::{
+        CipherBlob, CryptoVersion, Etag, KdfAlg, KdfParams, KdfSpec, KeySlot, KeySlotId,
+        KeySlotKind, OwnerSub, Revision, RevisionSummary, StoredCiphertext, Vault, VaultHeader,
+        VaultId, VaultPackage, VaultRevision, VaultSummary, VaultVersion,
+    },
+};
+use ports::RepositoryError;
+use uuid::Uuid;
+
+use crate::errors::corrupt;
+
+fn kdf_alg_name(alg: &KdfAlg) -> &'static str {
+    match alg {
+        KdfAlg::Argon2id => "argon2id",
+        KdfAlg::Scrypt => "scrypt",
+        KdfAlg::Pbkdf2HmacSha256 => "pbkdf2-hmac-sha256",
+    }
+}
+
+fn parse_kdf_alg(name: &str) -> Result<KdfAlg, RepositoryError> {
+    match name {
+        "argon2id" => Ok(KdfAlg::Argon2id),
+        "scrypt" => Ok(KdfAlg::Scrypt),
+        "pbkdf2-hmac-sha256" => Ok(KdfAlg::Pbkdf2HmacSha256),
+        other => Err(corrupt("kdf_alg", format!("unknown algorithm {other}"))),
+    }
+}
+
+fn unsigned<T: TryFrom<i64>>(column: &str, value: i64) -> Result<T, RepositoryError> {
+    T::try_from(value).map_err(|_| corrupt(column, format!("{value} out of range")))
+}
+
+fn cost<T: TryFrom<i64>>(column: &str, value: Option<i64>) -> Result<T, RepositoryError> {
+    let value = value.ok_or_else(|| corrupt(column, "missing for the algorithm"))?;
+    unsigned(column, value)
+}
+
+fn required<T>(column: &str, value: Option<T>) -> Result<T, RepositoryError> {
+    value.ok_or_else(|| corrupt(column, "missing for the slot kind"))
+}
+
+/// KDF costs of a master-password slot; only those of the algorithm in use
+/// are set.
+#[derive(Debug, Default, sqlx::FromRow)]
+pub(crate) struct KdfColumns {
+    #[sqlx(rename = "kdf_m_kib")]
+    pub m_kib: Option<i64>,
+    #[sqlx(rename = "kdf_t")]
+    pub t: Option<i64>,
+    #[sqlx(rename = "kdf_p")]
+    pub p: Option<i64>,
+    #[sqlx(rename = "kdf_log_n")]
+    pub log_n: Option<i64>,
+    #[sqlx(rename = "kdf_r")]
+    pub r: Option<i64>,
+    #[sqlx(rename = "kdf_iterations")]
+    pub iterations: Option<i64>,
+}
+
+impl From<&KdfParams> for KdfColumns {
+    fn from(params: &KdfParams) -> Self {
+        match *params {
+            KdfParams::Argon2id { m_kib, t, p } => Self {
+                m_kib: Some(m_kib.into()),
+                t: Some(t.into()),
+                p: Some(p.into()),
+                ..Self::default()
+            },
+            KdfParams::Scrypt { log_n, r, p } => Self {
+                log_n: Some(log_n.into()),
+                r: Some(r.into()),
+                p: Some(p.into()),
+                ..Self::default()
+            },
+            KdfParams::Pbkdf2HmacSha256 { iterations } => Self {
+                iterations: Some(iterations.into()),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+impl KdfColumns {
+    fn params(self, alg: KdfAlg) -> Result<KdfParams, RepositoryError> {
+        Ok(match alg {
+            KdfAlg::Argon2id => KdfParams::Argon2id {
+                m_kib: cost("kdf_m_kib", self.m_kib)?,
+                t: cost("kdf_t", self.t)?,
+                p: cost("kdf_p", self.p)?,
+            },
+            KdfAlg::Scrypt => KdfParams::Scrypt {
+                log_n: cost("kdf_log_n", self.log_n)?,
+                r: cost("kdf_r", self.r)?,
+                p: cost("kdf_p", self.p)?,
+            },
+            KdfAlg::Pbkdf2HmacSha256 => KdfParams::Pbkdf2HmacSha256 {
+                iterations: cost("kdf_iterations", self.iterations)?,
+            },
+        })
+    }
+}
+
+/// One row of `vault_key_slots` or `vault_revision_key_slots`; only the
+/// columns of the slot's kind are set.
+#[derive(Debug, Default, sqlx::FromRow)]
+pub(crate) struct KeySlotRow {
+    pub slot_id: Uuid,
+    pub kind: String,
+    pub kdf_alg: Option<String>,
+    pub kdf_salt: Option<Vec<u8>>,
+    #[sqlx(flatten)]
+    pub kdf: KdfColumns,
+    pub salt: Option<Vec<u8>>,
+    pub device_id: Option<String>,
+    pub credential_id: Option<Vec<u8>>,
+    pub prf_salt: Option<Vec<u8>>,
+    pub wrapped_vault_key: Vec<u8>,
+}
+
+impl From<&KeySlot> for KeySlotRow {
+    fn from(slot: &KeySlot) -> Self {
+        let row = Self {
+            slot_id: slot.id.0,
+            wrapped_vault_key: slot.wrapped_vault_key.clone(),
+            ..Self::default()
+        };
+
+        match &slot.kind {
+            KeySlotKind::MasterPassword { kdf } => Self {
+                kind: "master_password".into(),
+                kdf_alg: Some(kdf_alg_name(&kdf.alg()).into()),
+                kdf_salt: Some(kdf.salt.clone()),
+                kdf: KdfColumns::from(&kdf.params),
+                ..row
+            },
+            KeySlotKind::RecoveryKey { salt } => Self {
+                kind: "recovery_key".into(),
+                salt: Some(salt.clone()),
+                ..row
+            },
+            KeySlotKind::Device { device_id } => Self {
+                kind: "device".into(),
+                device_id: Some(device_id.clone()),
+                ..row
+            },
+            KeySlotKind::PasskeyPrf {
+                credential_id,
+                prf_salt,
+            } => Self {
+                kind: "passkey_prf".into(),
+                credential_id: Some(credential_id.clone()),
+                prf_salt: Some(prf_salt.clone()),
+                ..row
+            },
+        }
+    }
+}
+
+impl TryFrom<KeySlotRow> for KeySlot {
+    type Error = RepositoryError;
+
+    fn try_from(row: KeySlotRow) -> Result<Self, Self::Error> {
+        let kind = match row.kind.as_str() {
+            "master_password" => {
+                let alg = parse_kdf_alg(row.kdf_alg.as_deref().unwrap_or_default())?;
+                KeySlotKind::MasterPassword {
+                    kdf: KdfSpec {
+                        salt: required("key_slots.kdf_salt", row.kdf_salt)?,
+                        params: row.kdf.params(alg)?,
+                    },
+                }
+            }
+            "recovery_key" => KeySlotKind::RecoveryKey {
+                salt: required("key_slots.salt", row.salt)?,
+            },
+            "device" => KeySlotKind::Device {
+                device_id: required("key_slots.device_id", row.device_id)?,
+            },
+            "passkey_prf" => KeySlotKind::PasskeyPrf {
+                credential_id: required("key_slots.credential_id", row.credential_id)?,
+                prf_salt: required("key_slots.prf_salt", row.prf_salt)?,
+            },
+            other => return Err(corrupt("key_slots.kind", format!("unknown kind {other}"))),
+        };
+
+        Ok(KeySlot {
+            id: KeySlotId(row.slot_id),
+            kind,
+            wrapped_vault_key: row.wrapped_vault_key,
+        })
+    }
+}
+
+/// Ids of the attachments a package references, for the `attachment_ids`
+/// column.
+pub(crate) fn attachment_ids(package: &VaultPackage) -> Vec<Uuid> {
+    package.attachments.iter().map(|id| id.0).collect()
+}
+
+/// Columns holding a `VaultPackage`, shared by `vaults` and `vault_revisions`.
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct PackageRow {
+    pub crypto_version: i32,
+    pub nonce: Vec<u8>,
+    pub aad: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    #[sqlx(flatten)]
+    pub metadata: MetadataRow,
+    pub attachment_ids: Vec<Uuid>,
+    #[sqlx(flatten)]
+    pub stored_ciphertext: StoredCiphertextRow,
+}
+
+/// Encrypted metadata columns: all set or all null.
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct MetadataRow {
+    pub metadata_nonce: Option<Vec<u8>>,
+    pub metadata_aad: Option<Vec<u8>>,
+    pub metadata_ciphertext: Option<Vec<u8>>,
+}
+
+impl TryFrom<MetadataRow> for Option<CipherBlob> {
+    type Error = RepositoryError;
+
+    fn try_from(row: MetadataRow) -> Result<Self, Self::Error> {
+        match (
+            row.metadata_nonce,
+            row.metadata_aad,
+            row.metadata_ciphertext,
+        ) {
+            (Some(nonce), Some(aad), Some(ciphertext)) => Ok(Some(CipherBlob {
+                nonce,
+                aad,
+                ciphertext,
+            })),
+            (None, None, None) => Ok(None),
+            _ => Err(corrupt("metadata", "partially set")),
+        }
+    }
+}
+
+/// Stored ciphertext columns: all set or all null.
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct StoredCiphertextRow {
+    pub stored_ciphertext_id: Option<Uuid>,
+    pub stored_ciphertext_size: Option<i64>,
+    pub stored_ciphertext_sha256: Option<Vec<u8>>,
+}
+
+impl TryFrom<StoredCiphertextRow> for Option<StoredCiphertext> {
+    type Error = RepositoryError;
+
+    fn try_from(row: StoredCiphertextRow) -> Result<Self, Self::Error> {
+        match (
+            row.stored_ciphertext_id,
+            row.stored_ciphertext_size,
+            row.stored_ciphertext_sha256,
+        ) {
+            (Some(id), Some(size), Some(sha256)) => Ok(Some(StoredCiphertext {
+                attachment_id: AttachmentId(id),
+                size: unsigned("stored_ciphertext_size", size)?,
+                sha256,
+            })),
+            (None, None, None) => Ok(None),
+            _ => Err(corrupt("stored_ciphertext", "partially set")),
+        }
+    }
+}
+
+impl PackageRow {
+    /// The package, with the key slots read from their own table in order.
+    pub(crate) fn into_package(
+        self,
+        key_slots: Vec<KeySlotRow>,
+    ) -> Result<VaultPackage, RepositoryError> {
+        let crypto_version = unsigned::<u16>("crypto_version", self.crypto_version.into())?;
+
+        Ok(VaultPackage {
+            header: VaultHeader {
+                crypto_version: CryptoVersion::new(crypto_version)
+                    .map_err(|e| corrupt("crypto_version", e))?,
+                key_slots: key_slots
+                    .into_iter()
+                    .map(KeySlot::try_from)
+                    .collect::<Result<_, _>>()?,
+            },
+            blob: CipherBlob {
+                nonce: self.nonce,
+                aad: self.aad,
+                ciphertext: self.ciphertext,
+            },
+            metadata: self.metadata.try_into()?,
+            attachments: self.attachment_ids.into_iter().map(AttachmentId).collect(),
+            stored_ciphertext: self.stored_ciphertext.try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct VaultRow {
+    pub id: Uuid,
+    pub owner_id: String,
+    pub revision: i64,
+    pub etag: String,
+    #[sqlx(flatten)]
+    pub package: PackageRow,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl VaultRow {
+    pub(crate) fn into_vault(self, key_slots: Vec<KeySlotRow>) -> Result<Vault, RepositoryError> {
+        Ok(Vault {
+            id: VaultId(self.id),
+            owner_id: OwnerSub::new(self.owner_id).map_err(|e| corrupt("owner_id", e))?,
+            package: self.package.into_package(key_slots)?,
+            revision: Revision(unsigned("revision", self.revision)?),
+            etag: Etag::new(self.etag).map_err(|e| corrupt("etag", e))?,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct VersionRow {
+    pub id: Uuid,
+    pub revision: i64,
+    pub etag: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<VersionRow> for VaultVersion {
+    type Error = RepositoryError;
+
+    fn try_from(row: VersionRow) -> Result<Self, Self::Error> {
+        Ok(VaultVersion {
+            id: VaultId(row.id),
+            revision: Revision(unsigned("revision", row.revision)?),
+            etag: Etag::new(row.etag).map_err(|e| corrupt("etag", e))?,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct SummaryRow {
+    pub id: Uuid,
+    pub revision: i64,
+    pub etag: String,
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(flatten)]
+    pub metadata: MetadataRow,
+}
+
+impl TryFrom<SummaryRow> for VaultSummary {
+    type Error = RepositoryError;
+
+    fn try_from(row: SummaryRow) -> Result<Self, Self::Error> {
+        Ok(VaultSummary {
+            id: VaultId(row.id),
+            revision: Revision(unsigned("revision", row.revision)?),
+            etag: Etag::new(row.etag).map_err(|e| corrupt("etag", e))?,
+            updated_at: row.updated_at,
+            metadata: row.metadata.try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct RevisionRow {
+    pub vault_id: Uuid,
+    pub revision: i64,
+    pub etag: String,
+    #[sqlx(flatten)]
+    pub package: PackageRow,
+    pub saved_at: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+}
+
+impl RevisionRow {
+    pub(crate) fn into_revision(
+        self,
+        key_slots: Vec<KeySlotRow>,
+    ) -> Result<VaultRevision, RepositoryError> {
+        Ok(VaultRevision {
+            vault_id: VaultId(self.vault_id),
+            revision: Revision(unsigned("revision", self.revision)?),
+            etag: Etag::new(self.etag).map_err(|e| corrupt("etag", e))?,
+            package: self.package.into_package(key_slots)?,
+            saved_at: self.saved_at,
+            archived_at: self.archived_at,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct RevisionSummaryRow {
+    pub revision: i64,
+    pub etag: String,
+    pub saved_at: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+}
+
+impl TryFrom<RevisionSummaryRow> for RevisionSummary {
+    type Error = RepositoryError;
+
+    fn try_from(row: RevisionSummaryRow) -> Result<Self, Self::Error> {
+        Ok(RevisionSummary {
+            revision: Revision(unsigned("revision", row.revision)?),
+            etag: Etag::new(row.etag).map_err(|e| corrupt("etag", e))?,
+            saved_at: row.saved_at,
+            archived_at: row.archived_at,
+        })
+    }
+}
+
+/// Revision as stored: Postgres has no unsigned integers.
+pub(crate) fn revision_column(revision: Revision) -> Result<i64, RepositoryError> {
+    i64::try_from(revision.0).map_err(|_| RepositoryError::Database {
+        message: format!("revision {} exceeds BIGINT", revision.0),
+    })
+}
+
+/// Size of a stored ciphertext as stored, for the same reason.
+pub(crate) fn stored_size_column(package: &VaultPackage) -> Result<Option<i64>, RepositoryError> {
+    package
+        .stored_ciphertext
+        .as_ref()
+        .map(|stored| {
+            i64::try_from(stored.size).map_err(|_| RepositoryError::Database {
+                message: format!("stored ciphertext size {} exceeds BIGINT", stored.size),
+            })
+        })
+        .transpose()
+}
+
+/// Position of a key slot in its header, as stored.
+pub(crate) fn slot_position(index: usize) -> Result<i16, RepositoryError> {
+    i16::try_from(index).map_err(|_| RepositoryError::Database {
+        message: format!("key slot position {index} exceeds SMALLINT"),
+    })
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct AttachmentRow {
+    pub id: Uuid,
+    pub owner_id: String,
+    pub size: i64,
+    pub chunk_size: i64,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<AttachmentRow> for Attachment {
+    type Error = RepositoryError;
+
+    fn try_from(row: AttachmentRow) -> Result<Self, Self::Error> {
+        Ok(Attachment {
+            id: AttachmentId(row.id),
+            owner_id: OwnerSub::new(row.owner_id).map_err(|e| corrupt("owner_id", e))?,
+            size: unsigned("size", row.size)?,
+            chunk_size: unsigned("chunk_size", row.chunk_size)?,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct UsageRow {
+    pub vaults: i64,
+    pub vault_bytes: i64,
+    pub history_bytes: i64,
+    pub attachment_bytes: i64,
+}
+
+impl TryFrom<UsageRow> for StorageUsage {
+    type Error = RepositoryError;
+
+    fn try_from(row: UsageRow) -> Result<Self, Self::Error> {
+        Ok(StorageUsage {
+            vaults: unsigned("vaults", row.vaults)?,
+            vault_bytes: unsigned("vault_bytes", row.vault_bytes)?,
+            history_bytes: unsigned("history_bytes", row.history_bytes)?,
+            attachment_bytes: unsigned("attachment_bytes", row.attachment_bytes)?,
+        })
+    }
+}
@@ -0,0 +1,597 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::AttachmentId,
+    quota::StorageUsage,
+    vault::{
+        Etag, OwnerSub, Revision, RevisionSummary, Vault, VaultHeader, VaultId, VaultRevision,
+        VaultSummary, VaultVersion,
+    },
+};
+use ports::{
+    RepositoryError, usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use sqlx::{PgConnection, PgExecutor, PgPool, Postgres, Transaction, postgres::PgPoolOptions};
+use uuid::Uuid;
+
+use crate::{
+    MIGRATOR, PostgresAttachmentRepository,
+    errors::{database, is_id_conflict},
+    rows::{
+        KeySlotRow, RevisionRow, RevisionSummaryRow, SummaryRow, UsageRow, VaultRow, VersionRow,
+        attachment_ids, revision_column, slot_position, stored_size_column,
+    },
+};
+
+const VAULT_COLUMNS: &str = "id, owner_id, revision, etag, crypto_version, nonce, aad, ciphertext, \
+     metadata_nonce, metadata_aad, metadata_ciphertext, attachment_ids, \
+     stored_ciphertext_id, stored_ciphertext_size, stored_ciphertext_sha256, created_at, \
+     updated_at, deleted_at";
+
+const PACKAGE_COLUMNS: &str = "crypto_version, nonce, aad, ciphertext, metadata_nonce, \
+     metadata_aad, metadata_ciphertext, attachment_ids, stored_ciphertext_id, \
+     stored_ciphertext_size, stored_ciphertext_sha256";
+
+/// Columns of a key slot, shared by `vault_key_slots` and
+/// `vault_revision_key_slots`.
+const KEY_SLOT_COLUMNS: &str = "slot_id, kind, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p, \
+     kdf_log_n, kdf_r, kdf_iterations, salt, device_id, credential_id, prf_salt, \
+     wrapped_vault_key";
+
+/// Encrypted bytes of a package row, as counted by
+/// [`VaultPackage::byte_size`](domain::vault::VaultPackage::byte_size).
+const PACKAGE_BYTES: &str = "octet_length(nonce) + octet_length(aad) + octet_length(ciphertext) \
+     + coalesce(octet_length(metadata_nonce) + octet_length(metadata_aad) + octet_length(metadata_ciphertext), 0)";
+
+/// Vault storage backed by PostgreSQL.
+#[derive(Debug, Clone)]
+pub struct PostgresVaultRepository {
+    pool: PgPool,
+}
+
+impl PostgresVaultRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn connect(url: &str) -> Result<Self, RepositoryError> {
+        let pool = PgPoolOptions::new()
+            .connect(url)
+            .await
+            .map_err(|e| database("failed to connect to postgres", e))?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// Applies pending schema migrations.
+    pub async fn migrate(&self) -> Result<(), RepositoryError> {
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .map_err(|e| database("failed to run migrations", e.into()))
+    }
+
+    /// Attachment records in the same database, sharing the pool.
+    pub fn attachments(&self) -> PostgresAttachmentRepository {
+        PostgresAttachmentRepository::new(self.pool.clone())
+    }
+
+    /// A package is read from two tables; a repeatable-read transaction sees
+    /// both as of the same snapshot.
+    async fn snapshot(&self) -> Result<Transaction<'static, Postgres>, RepositoryError> {
+        self.pool
+            .begin_with("BEGIN ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .await
+            .map_err(|e| database("failed to begin transaction", e))
+    }
+}
+
+impl VaultRepository for PostgresVaultRepository {
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let mut tx = self.snapshot().await?;
+
+        let row: Option<VaultRow> = sqlx::query_as(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults \
+             WHERE id = $1 AND owner_id = $2 AND deleted_at IS NULL"
+        ))
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load vault", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots = key_slots(&mut tx, vault_id).await?;
+
+        row.into_vault(key_slots).map(Some)
+    }
+
+    async fn find_version(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<VaultVersion>, RepositoryError> {
+        let row: Option<VersionRow> = sqlx::query_as(
+            "SELECT id, revision, etag, updated_at FROM vaults \
+             WHERE id = $1 AND owner_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| database("failed to load vault version", e))?;
+
+        row.map(VaultVersion::try_from).transpose()
+    }
+
+    async fn find_deleted(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let mut tx = self.snapshot().await?;
+
+        let row: Option<VaultRow> = sqlx::query_as(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults \
+             WHERE id = $1 AND owner_id = $2 AND deleted_at IS NOT NULL"
+        ))
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load deleted vault", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots = key_slots(&mut tx, vault_id).await?;
+
+        row.into_vault(key_slots).map(Some)
+    }
+
+    async fn list_by_owner(
+        &self,
+        owner_id: &OwnerSub,
+    ) -> Result<Vec<VaultSummary>, RepositoryError> {
+        let rows: Vec<SummaryRow> = sqlx::query_as(
+            "SELECT id, revision, etag, updated_at, \
+                 metadata_nonce, metadata_aad, metadata_ciphertext \
+             FROM vaults WHERE owner_id = $1 AND deleted_at IS NULL \
+             ORDER BY created_at, id",
+        )
+        .bind(&owner_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list vaults", e))?;
+
+        rows.into_iter().map(VaultSummary::try_from).collect()
+    }
+
+    async fn create(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let header = &vault.package.header;
+        let blob = &vault.package.blob;
+        let metadata = vault.package.metadata.as_ref();
+        let stored = vault.package.stored_ciphertext.as_ref();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        sqlx::query(&format!(
+            "INSERT INTO vaults ({VAULT_COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+                 $18)"
+        ))
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .bind(revision_column(vault.revision)?)
+        .bind(&vault.etag.0)
+        .bind(i32::from(header.crypto_version.0))
+        .bind(&blob.nonce)
+        .bind(&blob.aad)
+        .bind(&blob.ciphertext)
+        .bind(metadata.map(|m| &m.nonce))
+        .bind(metadata.map(|m| &m.aad))
+        .bind(metadata.map(|m| &m.ciphertext))
+        .bind(attachment_ids(&vault.package))
+        .bind(stored.map(|s| s.attachment_id.0))
+        .bind(stored_size_column(&vault.package)?)
+        .bind(stored.map(|s| &s.sha256))
+        .bind(vault.created_at)
+        .bind(vault.updated_at)
+        .bind(vault.deleted_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            if is_id_conflict(&e) {
+                RepositoryError::AlreadyExists {
+                    vault_id: vault.id.0.to_string(),
+                }
+            } else {
+                database("failed to create vault", e)
+            }
+        })?;
+
+        insert_key_slots(&mut tx, &vault.id, header).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit vault creation", e))
+    }
+
+    /// Compare-and-swap: the row is only archived and rewritten if its etag
+    /// is still the one the caller read, so no lock is held across the
+    /// read-modify-write done by the use case. The archiving `SELECT` takes
+    /// the row lock, which a racing writer waits on and then re-checks the
+    /// etag against. The key slots follow the row into history and are then
+    /// replaced.
+    async fn update_if_match(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let header = &vault.package.header;
+        let blob = &vault.package.blob;
+        let metadata = vault.package.metadata.as_ref();
+        let stored = vault.package.stored_ciphertext.as_ref();
+        let conflict = || RepositoryError::ConcurrencyConflict {
+            vault_id: vault.id.0.to_string(),
+        };
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        let archived = sqlx::query(&format!(
+            "INSERT INTO vault_revisions \
+                 (vault_id, revision, etag, {PACKAGE_COLUMNS}, saved_at, archived_at) \
+             SELECT id, revision, etag, {PACKAGE_COLUMNS}, updated_at, $4 FROM vaults \
+             WHERE id = $1 AND owner_id = $2 AND etag = $3 AND deleted_at IS NULL FOR UPDATE"
+        ))
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .bind(&expected_etag.0)
+        .bind(vault.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to archive vault revision", e))?;
+
+        if archived.rows_affected() == 0 {
+            return Err(missed_write(&mut *tx, vault).await);
+        }
+
+        sqlx::query(&format!(
+            "INSERT INTO vault_revision_key_slots \
+                 (vault_id, revision, position, {KEY_SLOT_COLUMNS}) \
+             SELECT vault_key_slots.vault_id, vaults.revision, position, {KEY_SLOT_COLUMNS} \
+             FROM vault_key_slots JOIN vaults ON vaults.id = vault_key_slots.vault_id \
+             WHERE vault_key_slots.vault_id = $1"
+        ))
+        .bind(vault.id.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to archive key slots", e))?;
+
+        let result = sqlx::query(
+            "UPDATE vaults SET \
+                 revision = $3, etag = $4, crypto_version = $5, nonce = $6, aad = $7, \
+                 ciphertext = $8, metadata_nonce = $9, metadata_aad = $10, \
+                 metadata_ciphertext = $11, attachment_ids = $12, stored_ciphertext_id = $13, \
+                 stored_ciphertext_size = $14, stored_ciphertext_sha256 = $15, updated_at = $16 \
+             WHERE id = $1 AND etag = $2 AND deleted_at IS NULL",
+        )
+        .bind(vault.id.0)
+        .bind(&expected_etag.0)
+        .bind(revision_column(vault.revision)?)
+        .bind(&vault.etag.0)
+        .bind(i32::from(header.crypto_version.0))
+        .bind(&blob.nonce)
+        .bind(&blob.aad)
+        .bind(&blob.ciphertext)
+        .bind(metadata.map(|m| &m.nonce))
+        .bind(metadata.map(|m| &m.aad))
+        .bind(metadata.map(|m| &m.ciphertext))
+        .bind(attachment_ids(&vault.package))
+        .bind(stored.map(|s| s.attachment_id.0))
+        .bind(stored_size_column(&vault.package)?)
+        .bind(stored.map(|s| &s.sha256))
+        .bind(vault.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to update vault", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(conflict());
+        }
+
+        sqlx::query("DELETE FROM vault_key_slots WHERE vault_id = $1")
+            .bind(vault.id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database("failed to replace key slots", e))?;
+        insert_key_slots(&mut tx, &vault.id, header).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit vault update", e))
+    }
+
+    async fn mark_deleted(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE vaults SET deleted_at = $4 \
+             WHERE id = $1 AND owner_id = $2 AND etag = $3 AND deleted_at IS NULL",
+        )
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .bind(&expected_etag.0)
+        .bind(vault.deleted_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to delete vault", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(missed_write(&self.pool, vault).await);
+        }
+
+        Ok(())
+    }
+
+    async fn restore_deleted(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE vaults SET deleted_at = NULL \
+             WHERE id = $1 AND owner_id = $2 AND deleted_at IS NOT NULL",
+        )
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to restore vault", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::VaultNotFound {
+                vault_id: vault.id.0.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// History rows go with their vault through `ON DELETE CASCADE`.
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM vaults WHERE deleted_at < $1")
+            .bind(deleted_before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to purge deleted vaults", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_revisions(
+        &self,
+        vault_id: &VaultId,
+    ) -> Result<Vec<RevisionSummary>, RepositoryError> {
+        let rows: Vec<RevisionSummaryRow> = sqlx::query_as(
+            "SELECT revision, etag, saved_at, archived_at FROM vault_revisions \
+             WHERE vault_id = $1 ORDER BY revision DESC",
+        )
+        .bind(vault_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list vault revisions", e))?;
+
+        rows.into_iter().map(RevisionSummary::try_from).collect()
+    }
+
+    async fn find_revision(
+        &self,
+        vault_id: &VaultId,
+        revision: Revision,
+    ) -> Result<Option<VaultRevision>, RepositoryError> {
+        let revision = revision_column(revision)?;
+        let mut tx = self.snapshot().await?;
+
+        let row: Option<RevisionRow> = sqlx::query_as(&format!(
+            "SELECT vault_id, revision, etag, {PACKAGE_COLUMNS}, saved_at, archived_at \
+             FROM vault_revisions WHERE vault_id = $1 AND revision = $2"
+        ))
+        .bind(vault_id.0)
+        .bind(revision)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load vault revision", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots: Vec<KeySlotRow> = sqlx::query_as(&format!(
+            "SELECT {KEY_SLOT_COLUMNS} FROM vault_revision_key_slots \
+             WHERE vault_id = $1 AND revision = $2 ORDER BY position"
+        ))
+        .bind(vault_id.0)
+        .bind(revision)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load key slots", e))?;
+
+        row.into_revision(key_slots).map(Some)
+    }
+
+    async fn delete_revisions(
+        &self,
+        vault_id: &VaultId,
+        revisions: &[Revision],
+    ) -> Result<(), RepositoryError> {
+        let revisions = revisions
+            .iter()
+            .map(|revision| revision_column(*revision))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        sqlx::query("DELETE FROM vault_revisions WHERE vault_id = $1 AND revision = ANY($2)")
+            .bind(vault_id.0)
+            .bind(revisions)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to delete vault revisions", e))?;
+
+        Ok(())
+    }
+
+    async fn purge_revisions(
+        &self,
+        archived_before: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM vault_revisions WHERE archived_at < $1")
+            .bind(archived_before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to purge vault revisions", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn referenced_attachments(&self) -> Result<HashSet<AttachmentId>, RepositoryError> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT unnest(attachment_ids) FROM vaults \
+             UNION SELECT unnest(attachment_ids) FROM vault_revisions \
+             UNION SELECT stored_ciphertext_id FROM vaults WHERE stored_ciphertext_id IS NOT NULL \
+             UNION SELECT stored_ciphertext_id FROM vault_revisions \
+                 WHERE stored_ciphertext_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list referenced attachments", e))?;
+
+        Ok(ids.into_iter().map(AttachmentId).collect())
+    }
+}
+
+/// Vaults, history and attachments share the database, so usage is one
+/// query.
+impl UsageRepository for PostgresVaultRepository {
+    async fn usage(&self, owner_id: &OwnerSub) -> Result<StorageUsage, RepositoryError> {
+        let row: UsageRow = sqlx::query_as(&format!(
+            "SELECT \
+                 (SELECT count(*) FROM vaults WHERE owner_id = $1) AS vaults, \
+                 (SELECT coalesce(sum({PACKAGE_BYTES}), 0)::BIGINT FROM vaults \
+                  WHERE owner_id = $1) AS vault_bytes, \
+                 (SELECT coalesce(sum({PACKAGE_BYTES}), 0)::BIGINT FROM vault_revisions \
+                  WHERE vault_id IN (SELECT id FROM vaults WHERE owner_id = $1)) AS history_bytes, \
+                 (SELECT coalesce(sum(size), 0)::BIGINT FROM attachments \
+                  WHERE owner_id = $1) AS attachment_bytes"
+        ))
+        .bind(&owner_id.0)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| database("failed to measure storage usage", e))?;
+
+        row.try_into()
+    }
+}
+
+/// The key slots of a vault, in header order.
+async fn key_slots(
+    conn: &mut PgConnection,
+    vault_id: &VaultId,
+) -> Result<Vec<KeySlotRow>, RepositoryError> {
+    sqlx::query_as(&format!(
+        "SELECT {KEY_SLOT_COLUMNS} FROM vault_key_slots WHERE vault_id = $1 ORDER BY position"
+    ))
+    .bind(vault_id.0)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| database("failed to load key slots", e))
+}
+
+async fn insert_key_slots(
+    conn: &mut PgConnection,
+    vault_id: &VaultId,
+    header: &VaultHeader,
+) -> Result<(), RepositoryError> {
+    for (index, slot) in header.key_slots.iter().enumerate() {
+        let row = KeySlotRow::from(slot);
+
+        sqlx::query(&format!(
+            "INSERT INTO vault_key_slots (vault_id, position, {KEY_SLOT_COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)"
+        ))
+        .bind(vault_id.0)
+        .bind(slot_position(index)?)
+        .bind(row.slot_id)
+        .bind(row.kind)
+        .bind(row.kdf_alg)
+        .bind(row.kdf_salt)
+        .bind(row.kdf.m_kib)
+        .bind(row.kdf.t)
+        .bind(row.kdf.p)
+        .bind(row.kdf.log_n)
+        .bind(row.kdf.r)
+        .bind(row.kdf.iterations)
+        .bind(row.salt)
+        .bind(row.device_id)
+        .bind(row.credential_id)
+        .bind(row.prf_salt)
+        .bind(row.wrapped_vault_key)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| database("failed to store key slot", e))?;
+    }
+
+    Ok(())
+}
+
+/// Why a conditional write matched no row: the owner has no such vault, or
+/// it changed or moved to the trash since it was read.
+async fn missed_write<'c>(executor: impl PgExecutor<'c>, vault: &Vault) -> RepositoryError {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM vaults WHERE id = $1 AND owner_id = $2)",
+    )
+    .bind(vault.id.0)
+    .bind(&vault.owner_id.0)
+    .fetch_one(executor)
+    .await;
+
+    match exists {
+        Ok(true) => RepositoryError::ConcurrencyConflict {
+            vault_id: vault.id.0.to_string(),
+        },
+        Ok(false) => RepositoryError::VaultNotFound {
+            vault_id: vault.id.0.to_string(),
+        },
+        Err(e) => database("failed to read vault", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ephemeral;
+
+    ports::vault_repository_conformance!(
+        #[ignore = "needs PostgreSQL; run with --ignored, see ephemeral"]
+        ephemeral::database()
+    );
+
+    ports::usage_repository_conformance!(
+        #[ignore = "needs PostgreSQL; run with --ignored, see ephemeral"]
+        async {
+            let (repository, guard) = ephemeral::database().await;
+            let attachments = repository.attachments();
+            ((repository.clone(), attachments, repository), guard)
+        }
+    );
+}
@@ -0,0 +1,280 @@
+use chrono::Utc;
+use domain::attachment::AttachmentId;
+use ports::{BlobStoreError, blob_store::BlobStore};
+use reqwest::{Client, Method, Response, StatusCode};
+use tracing::error;
+use url::Url;
+
+use crate::signing::{Credentials, sign};
+
+/// Where the chunks go. Requests use path-style addressing
+/// (`{endpoint}/{bucket}/{key}`), which MinIO and most S3-compatible stores
+/// accept.
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub endpoint: Url,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// Chunks stored as objects of an S3-compatible bucket, under
+/// `attachments/{id}/{index}` with the index zero-padded so that keys list
+/// in index order.
+#[derive(Debug, Clone)]
+pub struct S3BlobStore {
+    client: Client,
+    endpoint: Url,
+    bucket: String,
+    credentials: Credentials,
+}
+
+impl S3BlobStore {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: config.endpoint,
+            bucket: config.bucket,
+            credentials: Credentials {
+                access_key_id: config.access_key_id,
+                secret_access_key: config.secret_access_key,
+                region: config.region,
+            },
+        }
+    }
+
+    fn prefix(attachment_id: &AttachmentId) -> String {
+        format!("attachments/{}/", attachment_id.0)
+    }
+
+    fn key(attachment_id: &AttachmentId, index: u32) -> String {
+        format!("{}{index:010}", Self::prefix(attachment_id))
+    }
+
+    /// URL of `key` in the bucket, or of the bucket itself for an empty key.
+    fn url(&self, key: &str) -> Result<Url, BlobStoreError> {
+        let mut url = self.endpoint.clone();
+        url.path_segments_mut()
+            .map_err(|_| unavailable("invalid endpoint", self.endpoint.as_str()))?
+            .pop_if_empty()
+            .push(&self.bucket)
+            .extend(key.split('/').filter(|segment| !segment.is_empty()));
+
+        Ok(url)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        body: Vec<u8>,
+    ) -> Result<Response, BlobStoreError> {
+        let signature = sign(
+            &self.credentials,
+            method.as_str(),
+            &url,
+            &[],
+            &body,
+            Utc::now(),
+        );
+
+        self.client
+            .request(method, url.clone())
+            .header("authorization", signature.authorization)
+            .header("x-amz-date", signature.amz_date)
+            .header("x-amz-content-sha256", signature.content_sha256)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| unavailable("request failed", e))
+    }
+
+    /// One page of keys under `prefix`, and the token of the next page.
+    async fn list_page(
+        &self,
+        prefix: &str,
+        continuation: Option<&str>,
+    ) -> Result<(Vec<String>, Option<String>), BlobStoreError> {
+        let mut url = self.url("")?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("list-type", "2");
+            query.append_pair("prefix", prefix);
+            if let Some(token) = continuation {
+                query.append_pair("continuation-token", token);
+            }
+        }
+
+        let response = self.send(Method::GET, url, vec![]).await?;
+        let body = expect_success(response, "failed to list objects")
+            .await?
+            .text()
+            .await
+            .map_err(|e| unavailable("failed to list objects", e))?;
+
+        let keys = elements(&body, "Key").into_iter().map(unescape).collect();
+        let next = (elements(&body, "IsTruncated") == ["true"])
+            .then(|| {
+                elements(&body, "NextContinuationToken")
+                    .first()
+                    .map(|t| unescape(t))
+            })
+            .flatten();
+
+        Ok((keys, next))
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, BlobStoreError> {
+        let mut keys = vec![];
+        let mut continuation = None;
+
+        loop {
+            let (page, next) = self.list_page(prefix, continuation.as_deref()).await?;
+            keys.extend(page);
+
+            match next {
+                Some(token) => continuation = Some(token),
+                None => return Ok(keys),
+            }
+        }
+    }
+}
+
+fn unavailable(context: &str, e: impl std::fmt::Display) -> BlobStoreError {
+    error!("s3 blob store: {}: {}", context, e);
+    BlobStoreError::Unavailable {
+        message: format!("{context}: {e}"),
+    }
+}
+
+async fn expect_success(response: Response, context: &str) -> Result<Response, BlobStoreError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(unavailable(context, format!("{status}: {body}")))
+}
+
+/// Text of each `<name>` element of a response document. S3 list results are
+/// flat enough that no XML parser is needed.
+fn elements<'a>(document: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{name}>");
+    let close = format!("</{name}>");
+
+    document
+        .split(open.as_str())
+        .skip(1)
+        .filter_map(|rest| rest.find(close.as_str()).map(|end| &rest[..end]))
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl BlobStore for S3BlobStore {
+    async fn put_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+        bytes: Vec<u8>,
+    ) -> Result<(), BlobStoreError> {
+        let url = self.url(&Self::key(attachment_id, index))?;
+        let response = self.send(Method::PUT, url, bytes).await?;
+        expect_success(response, "failed to put chunk").await?;
+
+        Ok(())
+    }
+
+    async fn get_chunk(
+        &self,
+        attachment_id: &AttachmentId,
+        index: u32,
+    ) -> Result<Option<Vec<u8>>, BlobStoreError> {
+        let url = self.url(&Self::key(attachment_id, index))?;
+        let response = self.send(Method::GET, url, vec![]).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let bytes = expect_success(response, "failed to get chunk")
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| unavailable("failed to get chunk", e))?;
+
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn list_chunks(&self, attachment_id: &AttachmentId) -> Result<Vec<u32>, BlobStoreError> {
+        let prefix = Self::prefix(attachment_id);
+        let keys = self.list_keys(&prefix).await?;
+
+        let mut indices: Vec<u32> = keys
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.parse().ok())
+            .collect();
+        indices.sort_unstable();
+
+        Ok(indices)
+    }
+
+    /// Deletes the objects one by one: the batch delete API requires a
+    /// `Content-MD5` header that not every compatible store handles alike.
+    async fn delete(&self, attachment_id: &AttachmentId) -> Result<(), BlobStoreError> {
+        for key in self.list_keys(&Self::prefix(attachment_id)).await? {
+            let response = self.send(Method::DELETE, self.url(&key)?, vec![]).await?;
+
+            if response.status() != StatusCode::NOT_FOUND {
+                expect_success(response, "failed to delete chunk").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use crate::{S3BlobStore, S3Config, stub};
+
+    async fn store() -> (S3BlobStore, stub::StubServer) {
+        let server = stub::start().await;
+        let store = S3BlobStore::new(S3Config {
+            endpoint: Url::parse(&server.endpoint()).unwrap(),
+            bucket: stub::BUCKET.into(),
+            region: "us-east-1".into(),
+            access_key_id: stub::ACCESS_KEY_ID.into(),
+            secret_access_key: "stub-secret".into(),
+        });
+
+        (store, server)
+    }
+
+    ports::blob_store_conformance!(store());
+
+    #[test]
+    fn reads_elements_of_a_list_result() {
+        let document = "<ListBucketResult><IsTruncated>true</IsTruncated>\
+            <Contents><Key>a/1</Key></Contents><Contents><Key>a/&amp;2</Key></Contents>\
+            <NextContinuationToken>t0k</NextContinuationToken></ListBucketResult>";
+
+        let keys: Vec<_> = super::elements(document, "Key")
+            .into_iter()
+            .map(super::unescape)
+            .collect();
+
+        assert_eq!(keys, ["a/1", "a/&2"]);
+        assert_eq!(super::elements(document, "NextContinuationToken"), ["t0k"]);
+    }
+}
@@ -0,0 +1,7 @@
+mod blob_store;
+mod signing;
+
+#[cfg(test)]
+mod stub;
+
+pub use blob_store::{S3BlobStore, S3Config};
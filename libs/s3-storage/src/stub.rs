@@ -0,0 +1,174 @@
+//! A minimal S3-compatible server for the adapter tests, in the spirit of a
+//! local MinIO: one bucket held in memory, object PUT/GET/DELETE and
+//! ListObjectsV2 with small pages so that pagination is exercised.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::signing::sha256_hex;
+
+pub(crate) const BUCKET: &str = "attachments-test";
+pub(crate) const ACCESS_KEY_ID: &str = "stub-access-key";
+const PAGE_SIZE: usize = 2;
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// Stops the server when the test ends.
+pub(crate) struct StubServer {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl StubServer {
+    pub(crate) fn endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+pub(crate) async fn start() -> StubServer {
+    let app = Router::new()
+        .route(&format!("/{BUCKET}"), get(list))
+        .route(
+            &format!("/{BUCKET}/{{*key}}"),
+            get(get_object).put(put_object).delete(delete_object),
+        )
+        .with_state(Objects::default());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let task = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    StubServer { port, task }
+}
+
+/// Rejects unsigned requests and payloads that do not match their declared
+/// hash, as a real store would.
+fn check_signed(headers: &HeaderMap, body: &[u8]) -> Result<(), (StatusCode, &'static str)> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let credential = format!("AWS4-HMAC-SHA256 Credential={ACCESS_KEY_ID}/");
+    if !header("authorization").is_some_and(|auth| auth.starts_with(&credential)) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "<Error><Code>AccessDenied</Code></Error>",
+        ));
+    }
+    if header("x-amz-date").is_none() || header("x-amz-content-sha256") != Some(&sha256_hex(body)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "<Error><Code>XAmzContentSHA256Mismatch</Code></Error>",
+        ));
+    }
+
+    Ok(())
+}
+
+async fn put_object(
+    State(objects): State<Objects>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(rejection) = check_signed(&headers, &body) {
+        return rejection.into_response();
+    }
+
+    objects.lock().unwrap().insert(key, body.to_vec());
+    StatusCode::OK.into_response()
+}
+
+async fn get_object(
+    State(objects): State<Objects>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = check_signed(&headers, b"") {
+        return rejection.into_response();
+    }
+
+    match objects.lock().unwrap().get(&key) {
+        Some(bytes) => bytes.clone().into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            "<Error><Code>NoSuchKey</Code></Error>",
+        )
+            .into_response(),
+    }
+}
+
+async fn delete_object(
+    State(objects): State<Objects>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = check_signed(&headers, b"") {
+        return rejection.into_response();
+    }
+
+    objects.lock().unwrap().remove(&key);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// ListObjectsV2; the continuation token is the last key of the previous
+/// page.
+async fn list(
+    State(objects): State<Objects>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(rejection) = check_signed(&headers, b"") {
+        return rejection.into_response();
+    }
+    if query.get("list-type").map(String::as_str) != Some("2") {
+        return StatusCode::NOT_IMPLEMENTED.into_response();
+    }
+
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let after = query.get("continuation-token");
+
+    let objects = objects.lock().unwrap();
+    let mut keys = objects
+        .keys()
+        .filter(|key| key.starts_with(&prefix))
+        .filter(|key| after.is_none_or(|after| key.as_str() > after.as_str()));
+    let page: Vec<&String> = keys.by_ref().take(PAGE_SIZE).collect();
+    let truncated = keys.next().is_some();
+
+    let mut document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ListBucketResult><Name>{BUCKET}</Name><Prefix>{prefix}</Prefix>\
+         <KeyCount>{}</KeyCount><IsTruncated>{truncated}</IsTruncated>",
+        page.len()
+    );
+    for key in &page {
+        document.push_str(&format!("<Contents><Key>{key}</Key></Contents>"));
+    }
+    if truncated && let Some(last) = page.last() {
+        document.push_str(&format!(
+            "<NextContinuationToken>{last}</NextContinuationToken>"
+        ));
+    }
+    document.push_str("</ListBucketResult>");
+
+    ([("content-type", "application/xml")], document).into_response()
+}
@@ -0,0 +1,26 @@
+-- Same layout as the PostgreSQL schema: one vault per owner, the package
+-- split into typed columns. Timestamps are RFC 3339 text in UTC.
+CREATE TABLE vaults (
+    id                BLOB    PRIMARY KEY NOT NULL,
+    owner_id          TEXT    NOT NULL,
+
+    revision          INTEGER NOT NULL CHECK (revision >= 0),
+    etag              TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version    INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg           TEXT    NOT NULL,
+    kdf_salt          BLOB    NOT NULL,
+    kdf_m_kib         INTEGER NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t             INTEGER NOT NULL CHECK (kdf_t >= 0),
+    kdf_p             INTEGER NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key BLOB    NOT NULL,
+
+    nonce             BLOB    NOT NULL,
+    aad               BLOB    NOT NULL,
+    ciphertext        BLOB    NOT NULL,
+
+    created_at        TEXT    NOT NULL,
+    updated_at        TEXT    NOT NULL,
+
+    CONSTRAINT vaults_owner_id_key UNIQUE (owner_id)
+) STRICT;
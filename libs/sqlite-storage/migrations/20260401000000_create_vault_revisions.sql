@@ -0,0 +1,24 @@
+-- Revisions replaced by a newer write, kept for restore until the retention
+-- policy prunes them. Package columns mirror `vaults`.
+CREATE TABLE vault_revisions (
+    vault_id          BLOB    NOT NULL REFERENCES vaults (id) ON DELETE CASCADE,
+    revision          INTEGER NOT NULL CHECK (revision >= 0),
+    etag              TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version    INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg           TEXT    NOT NULL,
+    kdf_salt          BLOB    NOT NULL,
+    kdf_m_kib         INTEGER NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t             INTEGER NOT NULL CHECK (kdf_t >= 0),
+    kdf_p             INTEGER NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key BLOB    NOT NULL,
+
+    nonce             BLOB    NOT NULL,
+    aad               BLOB    NOT NULL,
+    ciphertext        BLOB    NOT NULL,
+
+    saved_at          TEXT    NOT NULL,
+    archived_at       TEXT    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision)
+) STRICT;
@@ -0,0 +1,100 @@
+-- Owners may hold several vaults, addressed by id. SQLite cannot drop a
+-- table constraint, so `vaults` is rebuilt without the owner uniqueness.
+-- Migrations run inside a transaction with foreign keys enforced, so the
+-- history table is rebuilt alongside and pointed at the new table before the
+-- old one is dropped; otherwise the cascade would wipe it.
+CREATE TABLE vaults_new (
+    id                  BLOB    PRIMARY KEY NOT NULL,
+    owner_id            TEXT    NOT NULL,
+
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg             TEXT    NOT NULL,
+    kdf_salt            BLOB    NOT NULL,
+    kdf_m_kib           INTEGER NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t               INTEGER NOT NULL CHECK (kdf_t >= 0),
+    kdf_p               INTEGER NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key   BLOB    NOT NULL,
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    -- Vault name and other descriptive fields, encrypted by the client.
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    created_at          TEXT    NOT NULL,
+    updated_at          TEXT    NOT NULL,
+    deleted_at          TEXT,
+
+    CONSTRAINT vaults_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vaults_new (
+    id, owner_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, created_at, updated_at, deleted_at
+)
+SELECT
+    id, owner_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, created_at, updated_at, deleted_at
+FROM vaults;
+
+CREATE TABLE vault_revisions_new (
+    vault_id            BLOB    NOT NULL REFERENCES vaults_new (id) ON DELETE CASCADE,
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg             TEXT    NOT NULL,
+    kdf_salt            BLOB    NOT NULL,
+    kdf_m_kib           INTEGER NOT NULL CHECK (kdf_m_kib >= 0),
+    kdf_t               INTEGER NOT NULL CHECK (kdf_t >= 0),
+    kdf_p               INTEGER NOT NULL CHECK (kdf_p >= 0),
+    wrapped_vault_key   BLOB    NOT NULL,
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    saved_at            TEXT    NOT NULL,
+    archived_at         TEXT    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision),
+    CONSTRAINT vault_revisions_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vault_revisions_new (
+    vault_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, saved_at, archived_at
+)
+SELECT
+    vault_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, saved_at, archived_at
+FROM vault_revisions;
+
+DROP TABLE vault_revisions;
+
+DROP TABLE vaults;
+
+-- Renaming rewrites the foreign key in vault_revisions_new to `vaults`.
+ALTER TABLE vaults_new RENAME TO vaults;
+
+ALTER TABLE vault_revisions_new RENAME TO vault_revisions;
+
+CREATE INDEX vaults_owner_id_idx ON vaults (owner_id, created_at, id);
+
+CREATE INDEX vaults_deleted_at_idx ON vaults (deleted_at) WHERE deleted_at IS NOT NULL;
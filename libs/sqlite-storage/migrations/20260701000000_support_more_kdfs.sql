@@ -0,0 +1,136 @@
+-- scrypt and PBKDF2 alongside Argon2id. Each algorithm has its own cost
+-- columns; those of other algorithms stay null. SQLite can neither relax
+-- NOT NULL nor add a table constraint in place, so both tables are rebuilt
+-- the same way as when owners gained several vaults.
+CREATE TABLE vaults_new (
+    id                  BLOB    PRIMARY KEY NOT NULL,
+    owner_id            TEXT    NOT NULL,
+
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg             TEXT    NOT NULL,
+    kdf_salt            BLOB    NOT NULL,
+    kdf_m_kib           INTEGER CHECK (kdf_m_kib >= 0),
+    kdf_t               INTEGER CHECK (kdf_t >= 0),
+    kdf_p               INTEGER CHECK (kdf_p >= 0),
+    kdf_log_n           INTEGER CHECK (kdf_log_n >= 0),
+    kdf_r               INTEGER CHECK (kdf_r >= 0),
+    kdf_iterations      INTEGER CHECK (kdf_iterations >= 0),
+    wrapped_vault_key   BLOB    NOT NULL,
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    created_at          TEXT    NOT NULL,
+    updated_at          TEXT    NOT NULL,
+    deleted_at          TEXT,
+
+    CONSTRAINT vaults_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN kdf_m_kib IS NOT NULL AND kdf_t IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'scrypt' THEN kdf_log_n IS NOT NULL AND kdf_r IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND kdf_m_kib IS NULL AND kdf_t IS NULL AND kdf_p IS NULL
+                AND kdf_log_n IS NULL AND kdf_r IS NULL
+            ELSE 0
+        END
+    ),
+    CONSTRAINT vaults_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vaults_new (
+    id, owner_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    created_at, updated_at, deleted_at
+)
+SELECT
+    id, owner_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    created_at, updated_at, deleted_at
+FROM vaults;
+
+CREATE TABLE vault_revisions_new (
+    vault_id            BLOB    NOT NULL REFERENCES vaults_new (id) ON DELETE CASCADE,
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    kdf_alg             TEXT    NOT NULL,
+    kdf_salt            BLOB    NOT NULL,
+    kdf_m_kib           INTEGER CHECK (kdf_m_kib >= 0),
+    kdf_t               INTEGER CHECK (kdf_t >= 0),
+    kdf_p               INTEGER CHECK (kdf_p >= 0),
+    kdf_log_n           INTEGER CHECK (kdf_log_n >= 0),
+    kdf_r               INTEGER CHECK (kdf_r >= 0),
+    kdf_iterations      INTEGER CHECK (kdf_iterations >= 0),
+    wrapped_vault_key   BLOB    NOT NULL,
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    saved_at            TEXT    NOT NULL,
+    archived_at         TEXT    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision),
+    CONSTRAINT vault_revisions_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN kdf_m_kib IS NOT NULL AND kdf_t IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'scrypt' THEN kdf_log_n IS NOT NULL AND kdf_r IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND kdf_m_kib IS NULL AND kdf_t IS NULL AND kdf_p IS NULL
+                AND kdf_log_n IS NULL AND kdf_r IS NULL
+            ELSE 0
+        END
+    ),
+    CONSTRAINT vault_revisions_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vault_revisions_new (
+    vault_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    saved_at, archived_at
+)
+SELECT
+    vault_id, revision, etag, crypto_version, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p,
+    wrapped_vault_key, nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    saved_at, archived_at
+FROM vault_revisions;
+
+DROP TABLE vault_revisions;
+
+DROP TABLE vaults;
+
+-- Renaming rewrites the foreign key in vault_revisions_new to `vaults`.
+ALTER TABLE vaults_new RENAME TO vaults;
+
+ALTER TABLE vault_revisions_new RENAME TO vault_revisions;
+
+CREATE INDEX vaults_owner_id_idx ON vaults (owner_id, created_at, id);
+
+CREATE INDEX vaults_deleted_at_idx ON vaults (deleted_at) WHERE deleted_at IS NOT NULL;
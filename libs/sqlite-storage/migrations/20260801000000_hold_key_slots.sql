@@ -0,0 +1,124 @@
+-- Headers hold several key slots, each wrapping the vault key for one unlock
+-- method. The slots move into a JSON array; the existing master-password
+-- columns become its first entry, with a fresh slot id. Both tables are
+-- rebuilt as when owners gained several vaults.
+CREATE TABLE vaults_new (
+    id                  BLOB    PRIMARY KEY NOT NULL,
+    owner_id            TEXT    NOT NULL,
+
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    key_slots           TEXT    NOT NULL CHECK (
+        json_valid(key_slots) AND json_type(key_slots) = 'array'
+        AND json_array_length(key_slots) > 0
+    ),
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    created_at          TEXT    NOT NULL,
+    updated_at          TEXT    NOT NULL,
+    deleted_at          TEXT,
+
+    CONSTRAINT vaults_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vaults_new (
+    id, owner_id, revision, etag, crypto_version, key_slots, nonce, aad, ciphertext,
+    metadata_nonce, metadata_aad, metadata_ciphertext, created_at, updated_at, deleted_at
+)
+SELECT
+    id, owner_id, revision, etag, crypto_version,
+    json_array(json_patch(json_object(
+        'id', lower(hex(randomblob(16))),
+        'kind', 'master_password',
+        'kdf_alg', kdf_alg,
+        'kdf_salt', lower(hex(kdf_salt)),
+        'wrapped_vault_key', lower(hex(wrapped_vault_key))
+    ), json_object(
+        'kdf_m_kib', kdf_m_kib,
+        'kdf_t', kdf_t,
+        'kdf_p', kdf_p,
+        'kdf_log_n', kdf_log_n,
+        'kdf_r', kdf_r,
+        'kdf_iterations', kdf_iterations
+    ))),
+    nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    created_at, updated_at, deleted_at
+FROM vaults;
+
+CREATE TABLE vault_revisions_new (
+    vault_id            BLOB    NOT NULL REFERENCES vaults_new (id) ON DELETE CASCADE,
+    revision            INTEGER NOT NULL CHECK (revision >= 0),
+    etag                TEXT    NOT NULL CHECK (etag <> ''),
+
+    crypto_version      INTEGER NOT NULL CHECK (crypto_version > 0),
+    key_slots           TEXT    NOT NULL CHECK (
+        json_valid(key_slots) AND json_type(key_slots) = 'array'
+        AND json_array_length(key_slots) > 0
+    ),
+
+    nonce               BLOB    NOT NULL,
+    aad                 BLOB    NOT NULL,
+    ciphertext          BLOB    NOT NULL,
+
+    metadata_nonce      BLOB,
+    metadata_aad        BLOB,
+    metadata_ciphertext BLOB,
+
+    saved_at            TEXT    NOT NULL,
+    archived_at         TEXT    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision),
+    CONSTRAINT vault_revisions_metadata_complete CHECK (
+        (metadata_nonce IS NULL) = (metadata_aad IS NULL)
+        AND (metadata_aad IS NULL) = (metadata_ciphertext IS NULL)
+    )
+) STRICT;
+
+INSERT INTO vault_revisions_new (
+    vault_id, revision, etag, crypto_version, key_slots, nonce, aad, ciphertext,
+    metadata_nonce, metadata_aad, metadata_ciphertext, saved_at, archived_at
+)
+SELECT
+    vault_id, revision, etag, crypto_version,
+    json_array(json_patch(json_object(
+        'id', lower(hex(randomblob(16))),
+        'kind', 'master_password',
+        'kdf_alg', kdf_alg,
+        'kdf_salt', lower(hex(kdf_salt)),
+        'wrapped_vault_key', lower(hex(wrapped_vault_key))
+    ), json_object(
+        'kdf_m_kib', kdf_m_kib,
+        'kdf_t', kdf_t,
+        'kdf_p', kdf_p,
+        'kdf_log_n', kdf_log_n,
+        'kdf_r', kdf_r,
+        'kdf_iterations', kdf_iterations
+    ))),
+    nonce, aad, ciphertext, metadata_nonce, metadata_aad, metadata_ciphertext,
+    saved_at, archived_at
+FROM vault_revisions;
+
+DROP TABLE vault_revisions;
+
+DROP TABLE vaults;
+
+-- Renaming rewrites the foreign key in vault_revisions_new to `vaults`.
+ALTER TABLE vaults_new RENAME TO vaults;
+
+ALTER TABLE vault_revisions_new RENAME TO vault_revisions;
+
+CREATE INDEX vaults_owner_id_idx ON vaults (owner_id, created_at, id);
+
+CREATE INDEX vaults_deleted_at_idx ON vaults (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Client-encrypted attachments, uploaded in chunks to the blob store. Only
+-- the upload records live here. Packages list the attachments they reference,
+-- as a JSON array of ids, so that those no vault or revision points at can be
+-- collected.
+CREATE TABLE attachments (
+    id           BLOB    PRIMARY KEY NOT NULL,
+    owner_id     TEXT    NOT NULL,
+
+    size         INTEGER NOT NULL CHECK (size > 0),
+    chunk_size   INTEGER NOT NULL CHECK (chunk_size > 0),
+
+    created_at   TEXT    NOT NULL,
+    completed_at TEXT
+) STRICT;
+
+CREATE INDEX attachments_created_at_idx ON attachments (created_at);
+
+ALTER TABLE vaults ADD COLUMN attachment_ids TEXT NOT NULL DEFAULT '[]'
+    CHECK (json_valid(attachment_ids) AND json_type(attachment_ids) = 'array');
+
+ALTER TABLE vault_revisions ADD COLUMN attachment_ids TEXT NOT NULL DEFAULT '[]'
+    CHECK (json_valid(attachment_ids) AND json_type(attachment_ids) = 'array');
@@ -0,0 +1,20 @@
+-- Ciphertexts streamed by clients are kept in the blob store, as the chunks
+-- of an attachment, instead of the ciphertext column, which is then empty.
+-- The size and digest are part of the package's content hash. The last
+-- column carries the all-or-none check, as SQLite cannot add table
+-- constraints to an existing table.
+ALTER TABLE vaults ADD COLUMN stored_ciphertext_id BLOB;
+ALTER TABLE vaults ADD COLUMN stored_ciphertext_size INTEGER
+    CHECK (stored_ciphertext_size >= 0);
+ALTER TABLE vaults ADD COLUMN stored_ciphertext_sha256 BLOB CHECK (
+    (stored_ciphertext_id IS NULL) = (stored_ciphertext_size IS NULL)
+    AND (stored_ciphertext_size IS NULL) = (stored_ciphertext_sha256 IS NULL)
+);
+
+ALTER TABLE vault_revisions ADD COLUMN stored_ciphertext_id BLOB;
+ALTER TABLE vault_revisions ADD COLUMN stored_ciphertext_size INTEGER
+    CHECK (stored_ciphertext_size >= 0);
+ALTER TABLE vault_revisions ADD COLUMN stored_ciphertext_sha256 BLOB CHECK (
+    (stored_ciphertext_id IS NULL) = (stored_ciphertext_size IS NULL)
+    AND (stored_ciphertext_size IS NULL) = (stored_ciphertext_sha256 IS NULL)
+);
@@ -0,0 +1,173 @@
+-- Key slots leave the JSON column for typed rows, one per slot, in the order
+-- the header lists them. Archived revisions keep theirs in a table of their
+-- own, removed with the revision. Slot ids written by the application are
+-- hyphenated in the JSON, those written by the earlier migration are not.
+CREATE TABLE vault_key_slots (
+    vault_id          BLOB    NOT NULL REFERENCES vaults (id) ON DELETE CASCADE,
+    slot_id           BLOB    NOT NULL,
+    position          INTEGER NOT NULL CHECK (position >= 0),
+
+    kind              TEXT    NOT NULL,
+
+    kdf_alg           TEXT,
+    kdf_salt          BLOB,
+    kdf_m_kib         INTEGER CHECK (kdf_m_kib >= 0),
+    kdf_t             INTEGER CHECK (kdf_t >= 0),
+    kdf_p             INTEGER CHECK (kdf_p >= 0),
+    kdf_log_n         INTEGER CHECK (kdf_log_n >= 0),
+    kdf_r             INTEGER CHECK (kdf_r >= 0),
+    kdf_iterations    INTEGER CHECK (kdf_iterations >= 0),
+
+    salt              BLOB,
+    device_id         TEXT,
+    credential_id     BLOB,
+    prf_salt          BLOB,
+
+    wrapped_vault_key BLOB    NOT NULL,
+
+    PRIMARY KEY (vault_id, slot_id),
+    CONSTRAINT vault_key_slots_position UNIQUE (vault_id, position),
+    CONSTRAINT vault_key_slots_kind CHECK (
+        CASE kind
+            WHEN 'master_password' THEN kdf_alg IS NOT NULL AND kdf_salt IS NOT NULL
+                AND salt IS NULL AND device_id IS NULL AND credential_id IS NULL
+                AND prf_salt IS NULL
+            WHEN 'recovery_key' THEN salt IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND device_id IS NULL
+                AND credential_id IS NULL AND prf_salt IS NULL
+            WHEN 'device' THEN device_id IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND salt IS NULL
+                AND credential_id IS NULL AND prf_salt IS NULL
+            WHEN 'passkey_prf' THEN credential_id IS NOT NULL AND prf_salt IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND salt IS NULL
+                AND device_id IS NULL
+            ELSE 0
+        END
+    ),
+    CONSTRAINT vault_key_slots_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN kdf_m_kib IS NOT NULL AND kdf_t IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'scrypt' THEN kdf_log_n IS NOT NULL AND kdf_r IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND kdf_m_kib IS NULL AND kdf_t IS NULL AND kdf_p IS NULL
+                AND kdf_log_n IS NULL AND kdf_r IS NULL
+            ELSE kdf_alg IS NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_p IS NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+        END
+    )
+) STRICT;
+
+CREATE TABLE vault_revision_key_slots (
+    vault_id          BLOB    NOT NULL,
+    revision          INTEGER NOT NULL,
+    slot_id           BLOB    NOT NULL,
+    position          INTEGER NOT NULL CHECK (position >= 0),
+
+    kind              TEXT    NOT NULL,
+
+    kdf_alg           TEXT,
+    kdf_salt          BLOB,
+    kdf_m_kib         INTEGER CHECK (kdf_m_kib >= 0),
+    kdf_t             INTEGER CHECK (kdf_t >= 0),
+    kdf_p             INTEGER CHECK (kdf_p >= 0),
+    kdf_log_n         INTEGER CHECK (kdf_log_n >= 0),
+    kdf_r             INTEGER CHECK (kdf_r >= 0),
+    kdf_iterations    INTEGER CHECK (kdf_iterations >= 0),
+
+    salt              BLOB,
+    device_id         TEXT,
+    credential_id     BLOB,
+    prf_salt          BLOB,
+
+    wrapped_vault_key BLOB    NOT NULL,
+
+    PRIMARY KEY (vault_id, revision, slot_id),
+    FOREIGN KEY (vault_id, revision)
+        REFERENCES vault_revisions (vault_id, revision) ON DELETE CASCADE,
+    CONSTRAINT vault_revision_key_slots_position UNIQUE (vault_id, revision, position),
+    CONSTRAINT vault_revision_key_slots_kind CHECK (
+        CASE kind
+            WHEN 'master_password' THEN kdf_alg IS NOT NULL AND kdf_salt IS NOT NULL
+                AND salt IS NULL AND device_id IS NULL AND credential_id IS NULL
+                AND prf_salt IS NULL
+            WHEN 'recovery_key' THEN salt IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND device_id IS NULL
+                AND credential_id IS NULL AND prf_salt IS NULL
+            WHEN 'device' THEN device_id IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND salt IS NULL
+                AND credential_id IS NULL AND prf_salt IS NULL
+            WHEN 'passkey_prf' THEN credential_id IS NOT NULL AND prf_salt IS NOT NULL
+                AND kdf_alg IS NULL AND kdf_salt IS NULL AND salt IS NULL
+                AND device_id IS NULL
+            ELSE 0
+        END
+    ),
+    CONSTRAINT vault_revision_key_slots_kdf_params CHECK (
+        CASE kdf_alg
+            WHEN 'argon2id' THEN kdf_m_kib IS NOT NULL AND kdf_t IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'scrypt' THEN kdf_log_n IS NOT NULL AND kdf_r IS NOT NULL
+                AND kdf_p IS NOT NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_iterations IS NULL
+            WHEN 'pbkdf2-hmac-sha256' THEN kdf_iterations IS NOT NULL
+                AND kdf_m_kib IS NULL AND kdf_t IS NULL AND kdf_p IS NULL
+                AND kdf_log_n IS NULL AND kdf_r IS NULL
+            ELSE kdf_alg IS NULL AND kdf_m_kib IS NULL AND kdf_t IS NULL
+                AND kdf_p IS NULL AND kdf_log_n IS NULL AND kdf_r IS NULL
+                AND kdf_iterations IS NULL
+        END
+    )
+) STRICT;
+
+INSERT INTO vault_key_slots
+SELECT
+    v.id,
+    unhex(replace(slot.value ->> 'id', '-', '')),
+    slot.key,
+    slot.value ->> 'kind',
+    slot.value ->> 'kdf_alg',
+    unhex(slot.value ->> 'kdf_salt'),
+    slot.value ->> 'kdf_m_kib',
+    slot.value ->> 'kdf_t',
+    slot.value ->> 'kdf_p',
+    slot.value ->> 'kdf_log_n',
+    slot.value ->> 'kdf_r',
+    slot.value ->> 'kdf_iterations',
+    unhex(slot.value ->> 'salt'),
+    slot.value ->> 'device_id',
+    unhex(slot.value ->> 'credential_id'),
+    unhex(slot.value ->> 'prf_salt'),
+    unhex(slot.value ->> 'wrapped_vault_key')
+FROM vaults v, json_each(v.key_slots) slot;
+
+INSERT INTO vault_revision_key_slots
+SELECT
+    r.vault_id,
+    r.revision,
+    unhex(replace(slot.value ->> 'id', '-', '')),
+    slot.key,
+    slot.value ->> 'kind',
+    slot.value ->> 'kdf_alg',
+    unhex(slot.value ->> 'kdf_salt'),
+    slot.value ->> 'kdf_m_kib',
+    slot.value ->> 'kdf_t',
+    slot.value ->> 'kdf_p',
+    slot.value ->> 'kdf_log_n',
+    slot.value ->> 'kdf_r',
+    slot.value ->> 'kdf_iterations',
+    unhex(slot.value ->> 'salt'),
+    slot.value ->> 'device_id',
+    unhex(slot.value ->> 'credential_id'),
+    unhex(slot.value ->> 'prf_salt'),
+    unhex(slot.value ->> 'wrapped_vault_key')
+FROM vault_revisions r, json_each(r.key_slots) slot;
+
+ALTER TABLE vaults DROP COLUMN key_slots;
+
+ALTER TABLE vault_revisions DROP COLUMN key_slots;
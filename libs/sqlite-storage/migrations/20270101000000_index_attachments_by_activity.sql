@@ -0,0 +1,5 @@
+-- Orphaned attachments are collected by when they were last active: the
+-- completion of the upload, or its start while it runs.
+DROP INDEX attachments_created_at_idx;
+
+CREATE INDEX attachments_last_active_idx ON attachments ((coalesce(completed_at, created_at)));
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::{Attachment, AttachmentId},
+    vault::OwnerSub,
+};
+use ports::{RepositoryError, attachment_repository::AttachmentRepository};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{errors::database, rows::AttachmentRow};
+
+/// Attachment records in the vault database file; see
+/// [`SqliteVaultRepository::attachments`](crate::SqliteVaultRepository::attachments).
+#[derive(Debug, Clone)]
+pub struct SqliteAttachmentRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteAttachmentRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+impl AttachmentRepository for SqliteAttachmentRepository {
+    async fn create(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO attachments (id, owner_id, size, chunk_size, created_at, completed_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(attachment.id.0)
+        .bind(&attachment.owner_id.0)
+        .bind(size_column(attachment.size)?)
+        .bind(i64::from(attachment.chunk_size))
+        .bind(attachment.created_at)
+        .bind(attachment.completed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to create attachment", e))?;
+
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        attachment_id: &AttachmentId,
+    ) -> Result<Option<Attachment>, RepositoryError> {
+        let row: Option<AttachmentRow> = sqlx::query_as(
+            "SELECT id, owner_id, size, chunk_size, created_at, completed_at FROM attachments \
+             WHERE id = ? AND owner_id = ?",
+        )
+        .bind(attachment_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| database("failed to load attachment", e))?;
+
+        row.map(Attachment::try_from).transpose()
+    }
+
+    async fn mark_completed(&self, attachment: &Attachment) -> Result<(), RepositoryError> {
+        let result =
+            sqlx::query("UPDATE attachments SET completed_at = ? WHERE id = ? AND owner_id = ?")
+                .bind(attachment.completed_at)
+                .bind(attachment.id.0)
+                .bind(&attachment.owner_id.0)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| database("failed to complete attachment", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::AttachmentNotFound {
+                attachment_id: attachment.id.0.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn list_idle_before(
+        &self,
+        idle_before: DateTime<Utc>,
+    ) -> Result<Vec<AttachmentId>, RepositoryError> {
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM attachments WHERE coalesce(completed_at, created_at) < ?",
+        )
+        .bind(idle_before)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list attachments", e))?;
+
+        Ok(ids.into_iter().map(AttachmentId).collect())
+    }
+
+    async fn delete(&self, attachment_ids: &[AttachmentId]) -> Result<(), RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        for id in attachment_ids {
+            sqlx::query("DELETE FROM attachments WHERE id = ?")
+                .bind(id.0)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| database("failed to delete attachment", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit attachment deletion", e))
+    }
+}
+
+/// Size as stored: SQLite integers are signed 64-bit.
+fn size_column(size: u64) -> Result<i64, RepositoryError> {
+    i64::try_from(size).map_err(|_| RepositoryError::Database {
+        message: format!("attachment size {size} exceeds INTEGER"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::{SqliteAttachmentRepository, SqliteVaultRepository};
+
+    async fn database() -> (SqliteAttachmentRepository, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteVaultRepository::open(dir.path().join("vaults.db"))
+            .await
+            .unwrap();
+        repository.migrate().await.unwrap();
+
+        (repository.attachments(), dir)
+    }
+
+    ports::attachment_repository_conformance!(database());
+}
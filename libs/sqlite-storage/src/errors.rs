@@ -0,0 +1,24 @@
+use ports::RepositoryError;
+use tracing::error;
+
+pub(crate) fn database(context: &str, e: sqlx::Error) -> RepositoryError {
+    error!("{}: {:?}", context, e);
+    RepositoryError::Database {
+        message: format!("{context}: {e}"),
+    }
+}
+
+/// SQLite does not report constraint names, only the offending columns.
+pub(crate) fn is_id_conflict(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Database(db) => db.is_unique_violation() && db.message().contains("vaults.id"),
+        _ => false,
+    }
+}
+
+pub(crate) fn corrupt(column: &str, message: impl std::fmt::Display) -> RepositoryError {
+    error!("corrupt vault row, column {}: {}", column, message);
+    RepositoryError::Database {
+        message: format!("corrupt vault row, column {column}: {message}"),
+    }
+}
@@ -0,0 +1,12 @@
+use sqlx::migrate::Migrator;
+
+mod attachment_repository;
+mod errors;
+mod rows;
+mod vault_repository;
+
+pub use attachment_repository::SqliteAttachmentRepository;
+pub use vault_repository::SqliteVaultRepository;
+
+/// Schema migrations embedded at compile time from `migrations/`.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
@@ -0,0 +1,653 @@
+use std::{collections::HashSet, path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use domain::{
+    attachment::AttachmentId,
+    quota::StorageUsage,
+    vault::{
+        Etag, OwnerSub, Revision, RevisionSummary, Vault, VaultHeader, VaultId, VaultRevision,
+        VaultSummary, VaultVersion,
+    },
+};
+use ports::{
+    RepositoryError, usage_repository::UsageRepository, vault_repository::VaultRepository,
+};
+use sqlx::{
+    SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+};
+use uuid::Uuid;
+
+use crate::{
+    MIGRATOR, SqliteAttachmentRepository,
+    errors::{corrupt, database, is_id_conflict},
+    rows::{
+        KeySlotRow, RevisionRow, RevisionSummaryRow, SummaryRow, UsageRow, VaultRow, VersionRow,
+        attachment_ids, revision_column, slot_position, stored_size_column,
+    },
+};
+
+const VAULT_COLUMNS: &str = "id, owner_id, revision, etag, crypto_version, nonce, aad, ciphertext, \
+     metadata_nonce, metadata_aad, metadata_ciphertext, attachment_ids, \
+     stored_ciphertext_id, stored_ciphertext_size, stored_ciphertext_sha256, created_at, \
+     updated_at, deleted_at";
+
+const PACKAGE_COLUMNS: &str = "crypto_version, nonce, aad, ciphertext, metadata_nonce, \
+     metadata_aad, metadata_ciphertext, attachment_ids, stored_ciphertext_id, \
+     stored_ciphertext_size, stored_ciphertext_sha256";
+
+/// Columns of a key slot, shared by `vault_key_slots` and
+/// `vault_revision_key_slots`.
+const KEY_SLOT_COLUMNS: &str = "slot_id, kind, kdf_alg, kdf_salt, kdf_m_kib, kdf_t, kdf_p, \
+     kdf_log_n, kdf_r, kdf_iterations, salt, device_id, credential_id, prf_salt, \
+     wrapped_vault_key";
+
+/// Encrypted bytes of a package row, as counted by
+/// [`VaultPackage::byte_size`](domain::vault::VaultPackage::byte_size).
+const PACKAGE_BYTES: &str = "length(nonce) + length(aad) + length(ciphertext) \
+     + coalesce(length(metadata_nonce) + length(metadata_aad) + length(metadata_ciphertext), 0)";
+
+/// Vault storage in a single SQLite file, for single-node deployments.
+#[derive(Debug, Clone)]
+pub struct SqliteVaultRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteVaultRepository {
+    /// How long a writer waits for the database lock before giving up.
+    pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Opens (or creates) the database file in WAL mode, so readers never
+    /// block the writer.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, RepositoryError> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(Self::BUSY_TIMEOUT)
+            .foreign_keys(true);
+
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .map_err(|e| database("failed to open sqlite database", e))?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// Applies pending schema migrations.
+    pub async fn migrate(&self) -> Result<(), RepositoryError> {
+        MIGRATOR
+            .run(&self.pool)
+            .await
+            .map_err(|e| database("failed to run migrations", e.into()))
+    }
+
+    /// Attachment records in the same database file, sharing the pool.
+    pub fn attachments(&self) -> SqliteAttachmentRepository {
+        SqliteAttachmentRepository::new(self.pool.clone())
+    }
+}
+
+impl VaultRepository for SqliteVaultRepository {
+    async fn find(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        let row: Option<VaultRow> = sqlx::query_as(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults \
+             WHERE id = ? AND owner_id = ? AND deleted_at IS NULL"
+        ))
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load vault", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots = key_slots(&mut tx, vault_id).await?;
+
+        row.into_vault(key_slots).map(Some)
+    }
+
+    async fn find_version(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<VaultVersion>, RepositoryError> {
+        let row: Option<VersionRow> = sqlx::query_as(
+            "SELECT id, revision, etag, updated_at FROM vaults \
+             WHERE id = ? AND owner_id = ? AND deleted_at IS NULL",
+        )
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| database("failed to load vault version", e))?;
+
+        row.map(VaultVersion::try_from).transpose()
+    }
+
+    async fn find_deleted(
+        &self,
+        owner_id: &OwnerSub,
+        vault_id: &VaultId,
+    ) -> Result<Option<Vault>, RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        let row: Option<VaultRow> = sqlx::query_as(&format!(
+            "SELECT {VAULT_COLUMNS} FROM vaults \
+             WHERE id = ? AND owner_id = ? AND deleted_at IS NOT NULL"
+        ))
+        .bind(vault_id.0)
+        .bind(&owner_id.0)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load deleted vault", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots = key_slots(&mut tx, vault_id).await?;
+
+        row.into_vault(key_slots).map(Some)
+    }
+
+    async fn list_by_owner(
+        &self,
+        owner_id: &OwnerSub,
+    ) -> Result<Vec<VaultSummary>, RepositoryError> {
+        let rows: Vec<SummaryRow> = sqlx::query_as(
+            "SELECT id, revision, etag, updated_at, \
+                 metadata_nonce, metadata_aad, metadata_ciphertext \
+             FROM vaults WHERE owner_id = ? AND deleted_at IS NULL \
+             ORDER BY created_at, id",
+        )
+        .bind(&owner_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list vaults", e))?;
+
+        rows.into_iter().map(VaultSummary::try_from).collect()
+    }
+
+    async fn create(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let header = &vault.package.header;
+        let blob = &vault.package.blob;
+        let metadata = vault.package.metadata.as_ref();
+        let stored = vault.package.stored_ciphertext.as_ref();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        sqlx::query(&format!(
+            "INSERT INTO vaults ({VAULT_COLUMNS}) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        ))
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .bind(revision_column(vault.revision)?)
+        .bind(&vault.etag.0)
+        .bind(i64::from(header.crypto_version.0))
+        .bind(&blob.nonce)
+        .bind(&blob.aad)
+        .bind(&blob.ciphertext)
+        .bind(metadata.map(|m| &m.nonce))
+        .bind(metadata.map(|m| &m.aad))
+        .bind(metadata.map(|m| &m.ciphertext))
+        .bind(attachment_ids(&vault.package))
+        .bind(stored.map(|s| s.attachment_id.0))
+        .bind(stored_size_column(&vault.package)?)
+        .bind(stored.map(|s| &s.sha256))
+        .bind(vault.created_at)
+        .bind(vault.updated_at)
+        .bind(vault.deleted_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            if is_id_conflict(&e) {
+                RepositoryError::AlreadyExists {
+                    vault_id: vault.id.0.to_string(),
+                }
+            } else {
+                database("failed to create vault", e)
+            }
+        })?;
+
+        insert_key_slots(&mut tx, &vault.id, header).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit vault creation", e))
+    }
+
+    /// Compare-and-swap inside a `BEGIN IMMEDIATE` transaction: the write
+    /// lock is taken before the etag is read, so no other writer can slip in
+    /// between the check, the archiving of the replaced row and its key slots,
+    /// and the update.
+    async fn update_if_match(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let header = &vault.package.header;
+        let blob = &vault.package.blob;
+        let metadata = vault.package.metadata.as_ref();
+        let stored = vault.package.stored_ciphertext.as_ref();
+
+        let mut tx = self
+            .pool
+            .begin_with("BEGIN IMMEDIATE")
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        let current: Option<(String, bool)> = sqlx::query_as(
+            "SELECT etag, deleted_at IS NOT NULL FROM vaults WHERE id = ? AND owner_id = ?",
+        )
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to read vault etag", e))?;
+
+        match current {
+            None => {
+                return Err(RepositoryError::VaultNotFound {
+                    vault_id: vault.id.0.to_string(),
+                });
+            }
+            Some((etag, deleted)) if deleted || etag != expected_etag.0 => {
+                return Err(RepositoryError::ConcurrencyConflict {
+                    vault_id: vault.id.0.to_string(),
+                });
+            }
+            Some(_) => {}
+        }
+
+        sqlx::query(&format!(
+            "INSERT INTO vault_revisions \
+                 (vault_id, revision, etag, {PACKAGE_COLUMNS}, saved_at, archived_at) \
+             SELECT id, revision, etag, {PACKAGE_COLUMNS}, updated_at, ? FROM vaults WHERE id = ?"
+        ))
+        .bind(vault.updated_at)
+        .bind(vault.id.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to archive vault revision", e))?;
+
+        sqlx::query(&format!(
+            "INSERT INTO vault_revision_key_slots \
+                 (vault_id, revision, position, {KEY_SLOT_COLUMNS}) \
+             SELECT vault_key_slots.vault_id, vaults.revision, position, {KEY_SLOT_COLUMNS} \
+             FROM vault_key_slots JOIN vaults ON vaults.id = vault_key_slots.vault_id \
+             WHERE vault_key_slots.vault_id = ?"
+        ))
+        .bind(vault.id.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to archive key slots", e))?;
+
+        sqlx::query(
+            "UPDATE vaults SET \
+                 revision = ?, etag = ?, crypto_version = ?, \
+                 nonce = ?, aad = ?, ciphertext = ?, metadata_nonce = ?, metadata_aad = ?, \
+                 metadata_ciphertext = ?, attachment_ids = ?, stored_ciphertext_id = ?, \
+                 stored_ciphertext_size = ?, stored_ciphertext_sha256 = ?, updated_at = ? \
+             WHERE id = ?",
+        )
+        .bind(revision_column(vault.revision)?)
+        .bind(&vault.etag.0)
+        .bind(i64::from(header.crypto_version.0))
+        .bind(&blob.nonce)
+        .bind(&blob.aad)
+        .bind(&blob.ciphertext)
+        .bind(metadata.map(|m| &m.nonce))
+        .bind(metadata.map(|m| &m.aad))
+        .bind(metadata.map(|m| &m.ciphertext))
+        .bind(attachment_ids(&vault.package))
+        .bind(stored.map(|s| s.attachment_id.0))
+        .bind(stored_size_column(&vault.package)?)
+        .bind(stored.map(|s| &s.sha256))
+        .bind(vault.updated_at)
+        .bind(vault.id.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| database("failed to update vault", e))?;
+
+        sqlx::query("DELETE FROM vault_key_slots WHERE vault_id = ?")
+            .bind(vault.id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| database("failed to replace key slots", e))?;
+        insert_key_slots(&mut tx, &vault.id, header).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit vault update", e))
+    }
+
+    async fn mark_deleted(
+        &self,
+        vault: &Vault,
+        expected_etag: &Etag,
+    ) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE vaults SET deleted_at = ? \
+             WHERE id = ? AND owner_id = ? AND etag = ? AND deleted_at IS NULL",
+        )
+        .bind(vault.deleted_at)
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .bind(&expected_etag.0)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to delete vault", e))?;
+
+        if result.rows_affected() == 0 {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM vaults WHERE id = ? AND owner_id = ?)",
+            )
+            .bind(vault.id.0)
+            .bind(&vault.owner_id.0)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| database("failed to read vault", e))?;
+
+            return Err(if exists {
+                RepositoryError::ConcurrencyConflict {
+                    vault_id: vault.id.0.to_string(),
+                }
+            } else {
+                RepositoryError::VaultNotFound {
+                    vault_id: vault.id.0.to_string(),
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn restore_deleted(&self, vault: &Vault) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE vaults SET deleted_at = NULL \
+             WHERE id = ? AND owner_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(vault.id.0)
+        .bind(&vault.owner_id.0)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| database("failed to restore vault", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::VaultNotFound {
+                vault_id: vault.id.0.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// History rows go with their vault through `ON DELETE CASCADE`.
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM vaults WHERE deleted_at < ?")
+            .bind(deleted_before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to purge deleted vaults", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_revisions(
+        &self,
+        vault_id: &VaultId,
+    ) -> Result<Vec<RevisionSummary>, RepositoryError> {
+        let rows: Vec<RevisionSummaryRow> = sqlx::query_as(
+            "SELECT revision, etag, saved_at, archived_at FROM vault_revisions \
+             WHERE vault_id = ? ORDER BY revision DESC",
+        )
+        .bind(vault_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list vault revisions", e))?;
+
+        rows.into_iter().map(RevisionSummary::try_from).collect()
+    }
+
+    async fn find_revision(
+        &self,
+        vault_id: &VaultId,
+        revision: Revision,
+    ) -> Result<Option<VaultRevision>, RepositoryError> {
+        let revision = revision_column(revision)?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        let row: Option<RevisionRow> = sqlx::query_as(&format!(
+            "SELECT vault_id, revision, etag, {PACKAGE_COLUMNS}, saved_at, archived_at \
+             FROM vault_revisions WHERE vault_id = ? AND revision = ?"
+        ))
+        .bind(vault_id.0)
+        .bind(revision)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load vault revision", e))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let key_slots: Vec<KeySlotRow> = sqlx::query_as(&format!(
+            "SELECT {KEY_SLOT_COLUMNS} FROM vault_revision_key_slots \
+             WHERE vault_id = ? AND revision = ? ORDER BY position"
+        ))
+        .bind(vault_id.0)
+        .bind(revision)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| database("failed to load key slots", e))?;
+
+        row.into_revision(key_slots).map(Some)
+    }
+
+    async fn delete_revisions(
+        &self,
+        vault_id: &VaultId,
+        revisions: &[Revision],
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| database("failed to begin transaction", e))?;
+
+        for revision in revisions {
+            sqlx::query("DELETE FROM vault_revisions WHERE vault_id = ? AND revision = ?")
+                .bind(vault_id.0)
+                .bind(revision_column(*revision)?)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| database("failed to delete vault revision", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| database("failed to commit revision deletion", e))
+    }
+
+    async fn purge_revisions(
+        &self,
+        archived_before: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let result = sqlx::query("DELETE FROM vault_revisions WHERE archived_at < ?")
+            .bind(archived_before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| database("failed to purge vault revisions", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn referenced_attachments(&self) -> Result<HashSet<AttachmentId>, RepositoryError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT value FROM vaults, json_each(vaults.attachment_ids) \
+             UNION SELECT value FROM vault_revisions, json_each(vault_revisions.attachment_ids)",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list referenced attachments", e))?;
+
+        let stored: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT stored_ciphertext_id FROM vaults WHERE stored_ciphertext_id IS NOT NULL \
+             UNION SELECT stored_ciphertext_id FROM vault_revisions \
+                 WHERE stored_ciphertext_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| database("failed to list referenced attachments", e))?;
+
+        ids.iter()
+            .map(|id| {
+                Uuid::parse_str(id)
+                    .map(AttachmentId)
+                    .map_err(|e| corrupt("attachment_ids", e))
+            })
+            .chain(stored.into_iter().map(|id| Ok(AttachmentId(id))))
+            .collect()
+    }
+}
+
+/// Vaults, history and attachments share the database, so usage is one
+/// query.
+impl UsageRepository for SqliteVaultRepository {
+    async fn usage(&self, owner_id: &OwnerSub) -> Result<StorageUsage, RepositoryError> {
+        let row: UsageRow = sqlx::query_as(&format!(
+            "SELECT \
+                 (SELECT count(*) FROM vaults WHERE owner_id = ?1) AS vaults, \
+                 (SELECT coalesce(sum({PACKAGE_BYTES}), 0) FROM vaults \
+                  WHERE owner_id = ?1) AS vault_bytes, \
+                 (SELECT coalesce(sum({PACKAGE_BYTES}), 0) FROM vault_revisions \
+                  WHERE vault_id IN (SELECT id FROM vaults WHERE owner_id = ?1)) AS history_bytes, \
+                 (SELECT coalesce(sum(size), 0) FROM attachments \
+                  WHERE owner_id = ?1) AS attachment_bytes"
+        ))
+        .bind(&owner_id.0)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| database("failed to measure storage usage", e))?;
+
+        row.try_into()
+    }
+}
+
+/// The key slots of a vault, in header order. Callers read them in the
+/// transaction that read the vault row, so both come from one snapshot.
+async fn key_slots(
+    conn: &mut SqliteConnection,
+    vault_id: &VaultId,
+) -> Result<Vec<KeySlotRow>, RepositoryError> {
+    sqlx::query_as(&format!(
+        "SELECT {KEY_SLOT_COLUMNS} FROM vault_key_slots WHERE vault_id = ? ORDER BY position"
+    ))
+    .bind(vault_id.0)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| database("failed to load key slots", e))
+}
+
+async fn insert_key_slots(
+    conn: &mut SqliteConnection,
+    vault_id: &VaultId,
+    header: &VaultHeader,
+) -> Result<(), RepositoryError> {
+    for (index, slot) in header.key_slots.iter().enumerate() {
+        let row = KeySlotRow::from(slot);
+
+        sqlx::query(&format!(
+            "INSERT INTO vault_key_slots (vault_id, position, {KEY_SLOT_COLUMNS}) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        ))
+        .bind(vault_id.0)
+        .bind(slot_position(index)?)
+        .bind(row.slot_id)
+        .bind(row.kind)
+        .bind(row.kdf_alg)
+        .bind(row.kdf_salt)
+        .bind(row.kdf.m_kib)
+        .bind(row.kdf.t)
+        .bind(row.kdf.p)
+        .bind(row.kdf.log_n)
+        .bind(row.kdf.r)
+        .bind(row.kdf.iterations)
+        .bind(row.salt)
+        .bind(row.device_id)
+        .bind(row.credential_id)
+        .bind(row.prf_salt)
+        .bind(row.wrapped_vault_key)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| database("failed to store key slot", e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::SqliteVaultRepository;
+
+    async fn database() -> (SqliteVaultRepository, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let repository = SqliteVaultRepository::open(dir.path().join("vaults.db"))
+            .await
+            .unwrap();
+        repository.migrate().await.unwrap();
+
+        (repository, dir)
+    }
+
+    ports::vault_repository_conformance!(database());
+
+    ports::usage_repository_conformance!(async {
+        let (repository, dir) = database().await;
+        let attachments = repository.attachments();
+        ((repository.clone(), attachments, repository), dir)
+    });
+
+    #[tokio::test]
+    async fn opens_in_wal_mode() {
+        let (repository, _dir) = database().await;
+
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(mode, "wal");
+    }
+}